@@ -1,8 +1,10 @@
+use crate::history::*;
 use crate::project::*;
 use clap::{crate_authors, crate_version, Clap};
 use egui::Key;
 use egui::*;
 use quartz_engine::core::game_state;
+use quartz_engine::core::scene::SceneFormat;
 use quartz_engine::{
     core::editor_bridge::*,
     prelude::{Vec2, *},
@@ -39,14 +41,15 @@ impl GameState {
         }
     }
 
-    pub fn deserialize<'de, D: quartz_engine::core::serde::Deserializer<'de>>(
-        deserializer: D,
+    pub fn deserialize(
+        scene: &[u8],
+        format: SceneFormat,
         path: impl AsRef<Path>,
         instance: &Instance,
     ) -> Self {
         let bridge = unsafe { Bridge::load(path.as_ref()) }.unwrap();
         let state = bridge
-            .deserialize(deserializer, instance, TARGET_FORMAT)
+            .load_scene(scene, format, instance, TARGET_FORMAT)
             .unwrap();
 
         Self {
@@ -56,18 +59,19 @@ impl GameState {
         }
     }
 
-    pub fn reload<'de, D: quartz_engine::core::serde::Deserializer<'de>>(
-        &mut self,
-        deserializer: D,
-        instance: &Instance,
-    ) {
-        self.state = Some(
-            self.bridge
-                .as_ref()
-                .unwrap()
-                .deserialize(deserializer, instance, TARGET_FORMAT)
-                .unwrap(),
+    pub fn reload(&mut self, scene: &[u8], format: SceneFormat, instance: &Instance) {
+        let state = self.bridge.as_ref().unwrap().load_scene(
+            scene,
+            format,
+            instance,
+            TARGET_FORMAT,
         );
+
+        // keep the current state rather than losing the scene
+        match state {
+            Ok(state) => self.state = Some(state),
+            Err(err) => log::error!("{}", err),
+        }
     }
 }
 
@@ -141,6 +145,9 @@ pub struct EditorState {
     pub building: Option<std::process::Child>,
     pub selection: Selection,
     pub viewports: Vec<Viewport>,
+    pub game_focused: bool,
+    pub game_viewport_rect: Rect,
+    pub history: History,
     pub mesh: Mesh,
 }
 
@@ -241,6 +248,9 @@ impl EditorState {
                     ty: ViewportType::Game,
                 },
             ],
+            game_focused: false,
+            game_viewport_rect: Rect::NOTHING,
+            history: History::new(),
             mesh,
         }
     }
@@ -282,7 +292,7 @@ impl EditorState {
     }
 
     pub fn load_scene(&self) -> Option<Vec<u8>> {
-        let path = self.project.path.join("scene.scn");
+        let path = self.project.scene_path();
 
         log::debug!("loading scene from: {}", path.display());
 
@@ -294,34 +304,72 @@ impl EditorState {
     }
 
     pub fn save_scene(&self) {
-        if let Some(game) = &self.game {
-            if !game.running {
-                let path = self.project.path.join("scene.scn");
+        self.save_scene_as(self.project.scene_format);
+    }
+
+    /// Saves the scene in `format`, next to the scene file of the project.
+    pub fn save_scene_as(&self, format: SceneFormat) -> bool {
+        let state = match &self.game {
+            Some(game) if !game.running => match &game.state {
+                Some(state) => state,
+                None => return false,
+            },
+            _ => return false,
+        };
 
-                log::debug!("saving scene to: {}", path.display());
+        let path = self.project.path.join(scene_file(format));
 
-                if let Ok(file) = std::fs::File::create(path) {
-                    let mut serializer =
-                        ron::Serializer::new(file, Some(ron::ser::PrettyConfig::default()), true)
-                            .unwrap();
-                    //serde_cbor::Serializer::new(serde_cbor::ser::IoWrite::new(file));
+        log::debug!("saving scene to: {}", path.display());
 
-                    if let Some(state) = &game.state {
-                        state.serialize_scene(&mut serializer).unwrap();
-                    }
+        let result = state
+            .save_scene(format)
+            .map_err(|err| err.to_string())
+            .and_then(|data| std::fs::write(&path, data).map_err(|err| err.to_string()));
+
+        match result {
+            Ok(()) => true,
+            Err(err) => {
+                log::error!("failed to save scene to {}: {}", path.display(), err);
+                false
+            }
+        }
+    }
+
+    /// Converts the scene to `format`, which the project is saved in from then on.
+    ///
+    /// The scene file in the old format is renamed with a `.bak` extension, so it isn't picked
+    /// over the new one when the project is opened again.
+    pub fn convert_scene(&mut self, format: SceneFormat) {
+        if format == self.project.scene_format {
+            return;
+        }
+
+        if self.save_scene_as(format) {
+            log::info!("converted scene to {}", format.name());
+
+            let old_path = self.project.scene_path();
+            self.project.scene_format = format;
+
+            if old_path.exists() {
+                let mut backup = old_path.clone().into_os_string();
+                backup.push(".bak");
+
+                if let Err(err) = std::fs::rename(&old_path, &backup) {
+                    log::error!("failed to rename {}: {}", old_path.display(), err);
                 }
             }
         }
     }
 
     pub fn reload_game(&mut self, scene: &[u8], instance: &Instance) {
-        if let Some(game) = &mut self.game {
-            let mut deserializer = ron::Deserializer::from_bytes(scene).unwrap();
-            //serde_cbor::Deserializer::from_slice(scene);
+        let format = SceneFormat::detect(self.project.scene_path(), scene);
 
-            game.reload(&mut deserializer, instance);
+        if let Some(game) = &mut self.game {
+            game.reload(scene, format, instance);
 
             if let Some(state) = &mut game.state {
+                state.plugins.assets.set_root(&self.project.path);
+                state.tree.sync_prefabs(&self.project.path);
                 state.editor_start(TARGET_FORMAT, instance);
             }
         } else {
@@ -331,11 +379,9 @@ impl EditorState {
 
     pub fn load(&mut self, scene: Option<&[u8]>, instance: &Instance) {
         let mut game = if let Some(scene) = scene {
-            let mut deserializer = ron::Deserializer::from_bytes(scene).unwrap();
-            //serde_cbor::Deserializer::from_slice(scene);
-
             GameState::deserialize(
-                &mut deserializer,
+                scene,
+                SceneFormat::detect(self.project.scene_path(), scene),
                 &self
                     .project
                     .path
@@ -355,11 +401,89 @@ impl EditorState {
         };
 
         if let Some(state) = &mut game.state {
+            state.plugins.assets.set_root(&self.project.path);
+            state.tree.sync_prefabs(&self.project.path);
             state.editor_start(TARGET_FORMAT, instance);
         }
 
         self.game = Some(game);
     }
+
+    /// Forwards window events to the game while the game viewport has focus.
+    pub fn forward_game_input(&mut self, event: &WindowEvent) {
+        if !self.game_focused {
+            return;
+        }
+
+        if let Some(game) = &mut self.game {
+            if !game.running {
+                return;
+            }
+
+            if let Some(state) = &mut game.state {
+                state.input.handle_event(event);
+
+                // winit reports physical pixels, the viewport is laid out in egui points
+                if let WindowEvent::CursorMoved { position, .. } = event {
+                    let pixels_per_point = self.egui_ctx.pixels_per_point();
+
+                    state.input.mouse.position = Vec2::new(
+                        position.x as f32 / pixels_per_point - self.game_viewport_rect.min.x,
+                        position.y as f32 / pixels_per_point - self.game_viewport_rect.min.y,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Saves `node_id` as the prefab `name`, updating existing instances if the prefab exists.
+pub fn save_prefab_as(
+    project_path: &Path,
+    name: &str,
+    state: &mut game_state::GameState,
+    node_id: NodeId,
+) {
+    let prefab = match Prefab::from_tree(&state.tree, node_id, name) {
+        Ok(prefab) => prefab,
+        Err(err) => {
+            log::error!("failed to create prefab {}: {}", name, err);
+            return;
+        }
+    };
+
+    log::debug!("saving prefab to: {}", project_path.join(name).display());
+
+    let old = Prefab::load(project_path, name).ok();
+
+    if let Err(err) = prefab.save(project_path) {
+        log::error!("failed to save prefab {}: {}", name, err);
+        return;
+    }
+
+    state.tree.link_prefab(node_id, &prefab);
+
+    if let Some(old) = old {
+        state.tree.update_prefab_instances(&old, &prefab);
+    }
+}
+
+/// Instantiates the prefab at `path` under `parent`, returning the new root.
+pub fn instantiate_prefab(
+    project_path: &Path,
+    path: &Path,
+    state: &mut game_state::GameState,
+    parent: Option<NodeId>,
+) -> Option<NodeId> {
+    let name = path.strip_prefix(project_path).unwrap_or(path);
+
+    match Prefab::load(project_path, name.to_string_lossy()) {
+        Ok(prefab) => state.tree.instantiate(&prefab, parent),
+        Err(err) => {
+            log::error!("failed to load prefab {}: {}", path.display(), err);
+            None
+        }
+    }
 }
 
 impl State for EditorState {
@@ -394,19 +518,42 @@ impl State for EditorState {
         if let Some(game) = &mut self.game {
             if let Some(state) = &mut game.state {
                 if game.running {
-                    state.update(TARGET_FORMAT, ctx.instance);
+                    state.update(ctx.delta_time, TARGET_FORMAT, ctx.instance);
                 } else {
-                    state.editor_update(TARGET_FORMAT, ctx.instance);
+                    state.editor_update(ctx.delta_time, TARGET_FORMAT, ctx.instance);
                 }
             }
         }
 
-        self.project.update_files().unwrap();
+        match self.project.update_files() {
+            Ok(changed) => {
+                if let Some(state) = self.game.as_mut().and_then(|game| game.state.as_mut()) {
+                    let reload_pipelines = !changed.is_empty();
+
+                    for path in changed {
+                        log::debug!("reloading: {}", path.display());
+
+                        state.plugins.assets.reload(&path.to_string_lossy());
+                    }
+
+                    // pipelines check their own shader files
+                    if reload_pipelines {
+                        let plugins = &state.plugins;
+                        plugins.pipelines.reload(&plugins.assets, ctx.instance);
+                    }
+                }
+            }
+            Err(err) => log::error!("failed to update project files: {}", err),
+        }
 
         Trans::None
     }
 
     fn handle_event(&mut self, _instance: &Instance, event: &event::Event<()>) -> Trans {
+        if let event::Event::WindowEvent { event, .. } = event {
+            self.forward_game_input(event);
+        }
+
         match event {
             event::Event::WindowEvent { event, .. } => match event {
                 WindowEvent::MouseWheel { delta, .. } => {
@@ -0,0 +1,364 @@
+use quartz_engine::core::{
+    game_state::GameState,
+    node::{ComponentsSnapshot, NodeId},
+    prefab::PrefabLink,
+    scene::SceneFormat,
+    transform::Transform,
+    tree::Tree,
+};
+use std::collections::{HashMap, HashSet};
+
+/// A single node, with its parent and components.
+#[derive(Clone, PartialEq)]
+pub struct NodeSnapshot {
+    pub name: String,
+    pub transform: Transform,
+    pub components: ComponentsSnapshot,
+    pub prefab: Option<PrefabLink>,
+    pub parent: Option<NodeId>,
+}
+
+impl NodeSnapshot {
+    pub fn take(tree: &Tree, node_id: NodeId) -> Option<Self> {
+        let node = tree.get_node(node_id)?;
+
+        // snapshots are never read by people, so the smaller binary format is used
+        let components = match node.snapshot_components(SceneFormat::Cbor) {
+            Ok(components) => components,
+            Err(err) => {
+                log::warn!("failed to snapshot node {:?}: {}", node_id, err);
+                return None;
+            }
+        };
+
+        Some(Self {
+            name: node.name.clone(),
+            transform: node.transform.clone(),
+            components,
+            prefab: node.prefab.clone(),
+            parent: tree.get_parent(node_id),
+        })
+    }
+
+    /// Snapshots `node_id` and all of its descendants.
+    pub fn take_recursive(tree: &Tree, node_id: NodeId, snapshots: &mut Vec<(NodeId, Self)>) {
+        if let Some(snapshot) = Self::take(tree, node_id) {
+            snapshots.push((node_id, snapshot));
+
+            for child in tree.get_children(node_id).clone() {
+                Self::take_recursive(tree, child, snapshots);
+            }
+        }
+    }
+}
+
+pub struct NodeEdit {
+    pub node_id: NodeId,
+    pub before: Option<NodeSnapshot>,
+    pub after: Option<NodeSnapshot>,
+}
+
+pub enum Edit {
+    Nodes(Vec<NodeEdit>),
+    Plugin {
+        plugin_id: String,
+        before: String,
+        after: String,
+    },
+}
+
+impl Edit {
+    fn apply(&self, state: &mut GameState, undo: bool) {
+        match self {
+            Edit::Nodes(edits) => {
+                let tree = &mut state.tree;
+
+                let target = |edit: &NodeEdit| {
+                    if undo {
+                        edit.before.clone()
+                    } else {
+                        edit.after.clone()
+                    }
+                };
+
+                let removed: HashSet<NodeId> = edits
+                    .iter()
+                    .filter(|edit| target(edit).is_none())
+                    .map(|edit| edit.node_id)
+                    .collect();
+
+                // spawn everything first, so parents exist when reparenting
+                for edit in edits {
+                    if target(edit).is_some() {
+                        tree.spawn_with_id(edit.node_id);
+                    }
+                }
+
+                for edit in edits {
+                    match target(edit) {
+                        Some(snapshot) => {
+                            if let Some(mut node) = tree.get_node(edit.node_id) {
+                                node.name = snapshot.name;
+                                node.transform = snapshot.transform;
+                                node.prefab = snapshot.prefab;
+                                node.restore_components(
+                                    &snapshot.components,
+                                    &state.components,
+                                    &state.plugins,
+                                );
+                            }
+
+                            tree.set_parent(edit.node_id, snapshot.parent);
+                        }
+                        None => {
+                            let parent_removed = tree
+                                .get_parent(edit.node_id)
+                                .map_or(false, |parent| removed.contains(&parent));
+
+                            if !parent_removed {
+                                tree.despawn(edit.node_id);
+                            }
+                        }
+                    }
+                }
+            }
+            Edit::Plugin {
+                plugin_id,
+                before,
+                after,
+            } => {
+                let data = if undo { before } else { after };
+
+                let result = state.plugins.get_mut_dyn(plugin_id, |plugin| {
+                    let mut deserializer = match ron::Deserializer::from_str(data) {
+                        Ok(deserializer) => deserializer,
+                        Err(err) => {
+                            log::error!("failed to restore plugin {}: {}", plugin_id, err);
+                            return;
+                        }
+                    };
+
+                    let result = plugin.try_reflect(
+                        &mut <dyn quartz_engine::core::erased_serde::Deserializer>::erase(
+                            &mut deserializer,
+                        ),
+                    );
+
+                    if let Err(err) = result {
+                        log::error!("failed to restore plugin {}: {}", plugin_id, err);
+                    }
+                });
+
+                if result.is_err() {
+                    log::warn!("plugin not found: {}", plugin_id);
+                }
+            }
+        }
+    }
+}
+
+/// An inspector edit in progress, see [`History::begin_node`].
+enum Pending {
+    Node {
+        node_id: NodeId,
+        before: Option<NodeSnapshot>,
+    },
+    Plugin {
+        plugin_id: String,
+        before: Option<String>,
+    },
+}
+
+fn plugin_snapshot(state: &GameState, plugin_id: &str) -> Option<String> {
+    state
+        .plugins
+        .get_mut_dyn(plugin_id, |plugin| ron::ser::to_string(&plugin).ok())
+        .ok()
+        .flatten()
+}
+
+/// Undo and redo stacks for the editor.
+///
+/// Inspector edits are snapshotted when an interaction starts and again when it ends, so
+/// dragging a value can be undone all at once, and nothing is serialized while idle.
+pub struct History {
+    /// Edits are only recorded while enabled, eg. not while the game is running.
+    pub enabled: bool,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    pending: Option<Pending>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            pending: None,
+        }
+    }
+
+    pub fn push(&mut self, edit: Edit) {
+        self.redo.clear();
+        self.undo.push(edit);
+    }
+
+    pub fn undo(&mut self, state: &mut GameState) {
+        self.pending = None;
+
+        if let Some(edit) = self.undo.pop() {
+            edit.apply(state, true);
+            self.redo.push(edit);
+        }
+    }
+
+    pub fn redo(&mut self, state: &mut GameState) {
+        self.pending = None;
+
+        if let Some(edit) = self.redo.pop() {
+            edit.apply(state, false);
+            self.undo.push(edit);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.pending = None;
+    }
+
+    /// Starts recording an edit of the node `node_id`, unless an edit is already in
+    /// progress.
+    pub fn begin_node(&mut self, state: &GameState, node_id: NodeId) {
+        if self.enabled && self.pending.is_none() {
+            self.pending = Some(Pending::Node {
+                node_id,
+                before: NodeSnapshot::take(&state.tree, node_id),
+            });
+        }
+    }
+
+    /// Starts recording an edit of the plugin `plugin_id`, unless an edit is already in
+    /// progress.
+    pub fn begin_plugin(&mut self, state: &GameState, plugin_id: &str) {
+        if self.enabled && self.pending.is_none() {
+            self.pending = Some(Pending::Plugin {
+                plugin_id: plugin_id.to_string(),
+                before: plugin_snapshot(state, plugin_id),
+            });
+        }
+    }
+
+    /// Ends the edit in progress, and pushes it if anything changed.
+    pub fn end(&mut self, state: &GameState) {
+        match self.pending.take() {
+            Some(Pending::Node { node_id, before }) => {
+                let after = NodeSnapshot::take(&state.tree, node_id);
+
+                if before != after {
+                    self.push(Edit::Nodes(vec![NodeEdit {
+                        node_id,
+                        before,
+                        after,
+                    }]));
+                }
+            }
+            Some(Pending::Plugin { plugin_id, before }) => {
+                let after = plugin_snapshot(state, &plugin_id);
+
+                if let (Some(before), Some(after)) = (before, after) {
+                    if before != after {
+                        self.push(Edit::Plugin {
+                            plugin_id,
+                            before,
+                            after,
+                        });
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Records nodes spawned, despawned and reparented by `f`.
+    pub fn record_tree<O>(
+        &mut self,
+        state: &mut GameState,
+        f: impl FnOnce(&mut GameState) -> O,
+    ) -> O {
+        if !self.enabled {
+            return f(state);
+        }
+
+        let parents: HashMap<NodeId, Option<NodeId>> = state
+            .tree
+            .nodes()
+            .into_iter()
+            .map(|node_id| (node_id, state.tree.get_parent(node_id)))
+            .collect();
+        let despawn_len = state.tree.despawn_queue().len();
+
+        let out = f(state);
+
+        // create the components of instantiated prefabs, so they're in the snapshots
+        state
+            .tree
+            .init_prefab_components(&state.components, &state.plugins);
+
+        let tree = &state.tree;
+        let mut edits = Vec::new();
+
+        for node_id in tree.nodes() {
+            match parents.get(&node_id) {
+                None => {
+                    edits.push(NodeEdit {
+                        node_id,
+                        before: None,
+                        after: NodeSnapshot::take(tree, node_id),
+                    });
+                }
+                Some(parent) if *parent != tree.get_parent(node_id) => {
+                    let after = NodeSnapshot::take(tree, node_id);
+                    let before = after.clone().map(|snapshot| NodeSnapshot {
+                        parent: *parent,
+                        ..snapshot
+                    });
+
+                    edits.push(NodeEdit {
+                        node_id,
+                        before,
+                        after,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let mut despawned = Vec::new();
+
+        for node_id in &tree.despawn_queue()[despawn_len.min(tree.despawn_queue().len())..] {
+            NodeSnapshot::take_recursive(tree, *node_id, &mut despawned);
+        }
+
+        let mut seen = HashSet::new();
+
+        for (node_id, snapshot) in despawned {
+            if !seen.insert(node_id) {
+                continue;
+            }
+
+            edits.push(NodeEdit {
+                node_id,
+                before: Some(snapshot),
+                after: None,
+            });
+        }
+
+        if !edits.is_empty() {
+            self.push(Edit::Nodes(edits));
+        }
+
+        out
+    }
+}
@@ -1,4 +1,5 @@
 mod editor_state;
+mod history;
 mod project;
 mod ui;
 
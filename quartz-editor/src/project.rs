@@ -1,14 +1,25 @@
 use egui::*;
+use quartz_engine::core::scene::SceneFormat;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the project directory is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Directories that aren't shown or watched, build output changes too often.
+const IGNORED_DIRS: &[&str] = &["target", ".git"];
 
 pub struct File {
     pub name: String,
+    pub modified: SystemTime,
 }
 
 impl File {
-    pub fn new(name: impl Into<String>) -> Self {
-        Self { name: name.into() }
+    pub fn new(name: impl Into<String>, modified: SystemTime) -> Self {
+        Self {
+            name: name.into(),
+            modified,
+        }
     }
 }
 
@@ -42,58 +53,155 @@ impl Dir {
             let path = entry.path();
 
             if path.is_dir() {
-                dir.dirs.push(Dir::load(name, &path)?);
+                if !IGNORED_DIRS.contains(&name.as_str()) {
+                    dir.dirs.push(Dir::load(name, &path)?);
+                }
             } else {
-                dir.files.push(File::new(name));
+                dir.files
+                    .push(File::new(name, entry.metadata()?.modified()?));
             }
         }
 
         Ok(dir)
     }
 
-    pub fn update(&mut self) -> std::io::Result<()> {
-        let meta = std::fs::metadata(&self.path)?;
+    /// Refreshes the tree and pushes the paths of files that were modified or added to
+    /// `changed`.
+    pub fn update(&mut self, changed: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        let modified = std::fs::metadata(&self.path)?.modified()?;
+
+        if modified > self.modified {
+            // entries were added or removed
+            let Dir { files, dirs, .. } = Dir::load(self.name.clone(), &self.path)?;
 
-        if meta.modified()? > self.modified {
-            for dir in &mut self.dirs {
-                dir.update()?;
+            for file in &files {
+                if !self.files.iter().any(|old| old.name == file.name) {
+                    changed.push(self.path.join(&file.name));
+                }
             }
+
+            self.files = files;
+            self.modified = modified;
+
+            self.dirs
+                .retain(|dir| dirs.iter().any(|new| new.name == dir.name));
+
+            for dir in dirs {
+                if !self.dirs.iter().any(|old| old.name == dir.name) {
+                    dir.files(changed);
+                    self.dirs.push(dir);
+                }
+            }
+        }
+
+        for file in &mut self.files {
+            let path = self.path.join(&file.name);
+
+            if let Ok(modified) = std::fs::metadata(&path).and_then(|meta| meta.modified()) {
+                if modified > file.modified {
+                    file.modified = modified;
+                    changed.push(path);
+                }
+            }
+        }
+
+        for dir in &mut self.dirs {
+            dir.update(changed)?;
         }
 
         Ok(())
     }
 
-    pub fn ui(&mut self, ui: &mut Ui) {
+    /// Pushes the paths of all files in the tree.
+    fn files(&self, paths: &mut Vec<PathBuf>) {
+        for file in &self.files {
+            paths.push(self.path.join(&file.name));
+        }
+
+        for dir in &self.dirs {
+            dir.files(paths);
+        }
+    }
+
+    /// Returns the path of the prefab clicked, if any.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<PathBuf> {
+        let mut clicked = None;
+
         for dir in &mut self.dirs {
             ui.collapsing(&dir.name.clone(), |ui| {
-                dir.ui(ui);
+                if let Some(path) = dir.ui(ui) {
+                    clicked = Some(path);
+                }
             });
         }
 
         for file in &self.files {
-            ui.label(&file.name);
+            if file.name.ends_with(".prefab") {
+                if ui.button(&file.name).clicked() {
+                    clicked = Some(self.path.join(&file.name));
+                }
+            } else {
+                ui.label(&file.name);
+            }
         }
+
+        clicked
     }
 }
 
 pub struct Project {
     pub path: PathBuf,
     pub files: Dir,
+    pub last_update: Instant,
+    /// The format the scene is saved in, picked from the existing scene file.
+    pub scene_format: SceneFormat,
 }
 
 impl Project {
     pub fn new(path: impl Into<PathBuf>) -> std::io::Result<Self> {
         let path = path.into();
 
+        let scene_format = SceneFormat::ALL
+            .iter()
+            .copied()
+            .find(|format| path.join(scene_file(*format)).exists())
+            .unwrap_or_default();
+
         Ok(Self {
             files: Dir::load(".", &path)?,
             path,
+            last_update: Instant::now(),
+            scene_format,
         })
     }
 
-    pub fn update_files(&mut self) -> std::io::Result<()> {
-        self.files.update()?;
+    pub fn scene_path(&self) -> PathBuf {
+        self.path.join(scene_file(self.scene_format))
+    }
 
-        Ok(())
+    /// Checks the project directory for changes, at most every [`POLL_INTERVAL`].
+    ///
+    /// Returns the paths of changed files, relative to the project directory.
+    pub fn update_files(&mut self) -> std::io::Result<Vec<PathBuf>> {
+        if self.last_update.elapsed() < POLL_INTERVAL {
+            return Ok(Vec::new());
+        }
+
+        self.last_update = Instant::now();
+
+        let mut changed = Vec::new();
+        self.files.update(&mut changed)?;
+
+        Ok(changed
+            .into_iter()
+            .map(|path| match path.strip_prefix(&self.path) {
+                Ok(path) => path.to_path_buf(),
+                Err(_) => path,
+            })
+            .collect())
     }
 }
+
+pub fn scene_file(format: SceneFormat) -> String {
+    format!("scene.{}", format.extension())
+}
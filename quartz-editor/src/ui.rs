@@ -2,6 +2,7 @@ use crate::editor_state::*;
 use egui::*;
 use quartz_engine::core::node::NodeId;
 use quartz_engine::core::plugin::PluginCtx;
+use quartz_engine::core::scene::SceneFormat;
 use quartz_engine::render::prelude::{Vec2, *};
 
 impl EditorState {
@@ -12,22 +13,85 @@ impl EditorState {
             self.save_scene();
         }
 
+        // text fields handle ctrl+z themselves
+        let history_keys = input.key_pressed(Key::Z)
+            && input.modifiers.ctrl
+            && !self.egui_ctx.wants_keyboard_input();
+        let undo = history_keys && !input.modifiers.shift;
+        let redo = history_keys && input.modifiers.shift;
+
+        if let Some(game) = &mut self.game {
+            self.history.enabled = !game.running;
+
+            if let Some(state) = &mut game.state {
+                if !game.running {
+                    if undo {
+                        self.history.undo(state);
+                    } else if redo {
+                        self.history.redo(state);
+                    }
+                }
+            }
+        }
+
         self.top_panel_ui(instance);
         self.left_panel_ui();
         self.inspector_panel_ui(instance);
         self.viewport_ui(instance);
+        self.errors_ui();
+    }
+
+    /// Shows asset and shader errors reported to [`Assets`](quartz_engine::core::asset::Assets).
+    pub fn errors_ui(&mut self) {
+        let state = match self.game.as_ref().and_then(|game| game.state.as_ref()) {
+            Some(state) => state,
+            None => return,
+        };
+
+        let errors = state.plugins.assets.errors();
+
+        if errors.is_empty() {
+            return;
+        }
+
+        Window::new("Errors").show(&self.egui_ctx, |ui| {
+            for (path, err) in &errors {
+                ui.label(format!("{}: {}", path, err));
+            }
+
+            if ui.button("Clear").clicked() {
+                state.plugins.assets.clear_errors();
+            }
+        });
     }
 
     pub fn top_panel_ui(&mut self, instance: &Instance) {
         let game = &mut self.game;
         let building = &self.building;
+        let scene_format = self.project.scene_format;
         let mut reload = false;
         let mut start = false;
+        let mut save = false;
+        let mut convert = None;
 
         let build = TopPanel::top("top_panel")
             .show(&self.egui_ctx, |ui| {
                 ui.horizontal(|ui| {
-                    let _file_response = ui.button("File");
+                    menu::menu(ui, "File", |ui| {
+                        if ui.button("Save Scene").clicked() {
+                            save = true;
+                        }
+
+                        for &format in SceneFormat::ALL.iter() {
+                            if format != scene_format {
+                                let label = format!("Convert Scene to {}", format.name());
+
+                                if ui.button(label).clicked() {
+                                    convert = Some(format);
+                                }
+                            }
+                        }
+                    });
 
                     let build_response = ui.add(Button::new("Build").enabled(building.is_none()));
 
@@ -50,6 +114,14 @@ impl EditorState {
             })
             .inner;
 
+        if save {
+            self.save_scene();
+        }
+
+        if let Some(format) = convert {
+            self.convert_scene(format);
+        }
+
         if build {
             self.save_scene();
 
@@ -57,6 +129,9 @@ impl EditorState {
                 drop(game.state.take());
             }
 
+            // component types may change with the rebuild, so old snapshots can't be trusted
+            self.history.clear();
+
             self.build().unwrap();
         }
 
@@ -72,9 +147,11 @@ impl EditorState {
     }
 
     pub fn left_panel_ui(&mut self) {
+        let project_path = &self.project.path;
         let files = &mut self.project.files;
         let game = &mut self.game;
         let selection = &mut self.selection;
+        let history = &mut self.history;
 
         SidePanel::left("left_panel", 200.0).show(&self.egui_ctx, |ui| {
             ui.separator();
@@ -113,12 +190,14 @@ impl EditorState {
                         ScrollArea::from_max_height(available_size.y / 3.0)
                             .id_source("nodes_scroll_area")
                             .show(ui, |ui| {
-                                state.tree.nodes_ui(
-                                    ui,
-                                    &state.components,
-                                    &state.plugins,
-                                    &mut selected_node,
-                                );
+                                history.record_tree(state, |state| {
+                                    state.tree.nodes_ui(
+                                        ui,
+                                        &state.components,
+                                        &state.plugins,
+                                        &mut selected_node,
+                                    );
+                                });
                             });
                     });
 
@@ -130,43 +209,113 @@ impl EditorState {
                 }
             }
 
+            let mut clicked_prefab = None;
+
             ScrollArea::auto_sized()
                 .id_source("file_scroll_area")
                 .show(ui, |ui| {
-                    files.ui(ui);
+                    clicked_prefab = files.ui(ui);
                 });
+
+            if let Some(path) = clicked_prefab {
+                if let Some(state) = game.as_mut().and_then(|game| game.state.as_mut()) {
+                    let parent = if let Selection::Node(node_id) = selection {
+                        Some(*node_id)
+                    } else {
+                        None
+                    };
+
+                    let node_id = history.record_tree(state, |state| {
+                        instantiate_prefab(project_path, &path, state, parent)
+                    });
+
+                    if let Some(node_id) = node_id {
+                        *selection = Selection::Node(node_id);
+                    }
+                }
+            }
         });
     }
 
     pub fn inspector_panel_ui(&mut self, instance: &Instance) {
         let egui_ctx = &self.egui_ctx;
+        let history = &mut self.history;
+
+        // an edit starts with a click or with typing into a focused widget, and lasts until
+        // the pointer is released and nothing has keyboard focus
+        let input = egui_ctx.input();
+        let begin = input.pointer.any_pressed()
+            || (egui_ctx.wants_keyboard_input() && !input.events.is_empty());
+
         if let Some(game) = &mut self.game {
             if let Some(state) = &mut game.state {
                 match &self.selection {
                     Selection::Node(node_id) => {
-                        if let Some(mut node) = state.tree.get_node(node_id) {
+                        let node_id = *node_id;
+                        let mut save_prefab = None;
+
+                        if begin {
+                            history.begin_node(state, node_id);
+                        }
+
+                        let found = if let Some(mut node) = state.tree.get_node(node_id) {
                             SidePanel::left("inspector_panel", 300.0).show(egui_ctx, |ui| {
+                                match &node.prefab {
+                                    Some(link) if link.node == 0 => {
+                                        if ui.button("Apply to prefab").clicked() {
+                                            save_prefab = Some(link.prefab.clone());
+                                        }
+                                    }
+                                    _ => {
+                                        if ui.button("Save as prefab").clicked() {
+                                            save_prefab = Some(format!("{}.prefab", node.name));
+                                        }
+                                    }
+                                }
+
+                                ui.separator();
+
                                 node.inspector_ui(
                                     &state.plugins,
                                     &state.components,
-                                    node_id,
+                                    &node_id,
                                     &mut state.tree,
+                                    &state.time,
+                                    &state.input,
                                     instance,
                                     ui,
                                 );
                             });
+
+                            true
                         } else {
+                            false
+                        };
+
+                        if !found {
                             self.selection = Selection::None;
                         }
+
+                        if let Some(name) = save_prefab {
+                            save_prefab_as(&self.project.path, &name, state, node_id);
+                        }
                     }
                     Selection::Plugin(plugin_id) => {
+                        if begin {
+                            history.begin_plugin(state, plugin_id);
+                        }
+
                         let tree = &mut state.tree;
                         let plugins = &state.plugins;
+                        let time = &state.time;
+                        let input = &state.input;
                         plugins
                             .get_mut_dyn(plugin_id, |plugin| {
                                 let ctx = PluginCtx {
                                     tree: tree,
                                     plugins: plugins,
+                                    time,
+                                    input,
                                     target_format: TARGET_FORMAT,
                                     instance,
                                 };
@@ -179,6 +328,10 @@ impl EditorState {
                     }
                     _ => {}
                 }
+
+                if !egui_ctx.input().pointer.any_down() && !egui_ctx.wants_keyboard_input() {
+                    history.end(state);
+                }
             }
         }
     }
@@ -189,6 +342,8 @@ impl EditorState {
         let viewports = &mut self.viewports;
         let selection = &mut self.selection;
         let pick_texture = &self.pick_texture;
+        let game_focused = &mut self.game_focused;
+        let game_viewport_rect = &mut self.game_viewport_rect;
 
         CentralPanel::default().show(&self.egui_ctx, |ui| {
             if game.is_some() {
@@ -222,6 +377,33 @@ impl EditorState {
                             );
                         }
 
+                        if let ViewportType::Game = &viewport.ty {
+                            *game_viewport_rect = response.rect;
+
+                            if response.clicked() || response.drag_started() {
+                                // start from the cursor, the game didn't see it move while
+                                // unfocused
+                                if !*game_focused {
+                                    let game_state = game.as_mut().and_then(|g| g.state.as_mut());
+
+                                    if let (Some(state), Some(pos)) =
+                                        (game_state, response.interact_pointer_pos())
+                                    {
+                                        let pos = pos - response.rect.min;
+                                        state.input.mouse.reset_position(Vec2::new(pos.x, pos.y));
+                                    }
+                                }
+
+                                *game_focused = true;
+                            } else if ui.input().pointer.any_pressed() && !response.hovered() {
+                                *game_focused = false;
+
+                                if let Some(state) = game.as_mut().and_then(|g| g.state.as_mut()) {
+                                    state.input.release_all();
+                                }
+                            }
+                        }
+
                         if let ViewportType::Editor { camera } = &mut viewport.ty {
                             if response.clicked_by(PointerButton::Primary) {
                                 if let Some(mut pos) = response.interact_pointer_pos() {
@@ -0,0 +1,476 @@
+use crate::render3d::*;
+use quartz_engine_core::prelude::*;
+use std::path::Path;
+
+/// A single glTF primitive.
+pub struct GltfPrimitive {
+    /// Asset label of the mesh, eg. `Mesh0/Primitive1`.
+    pub label: String,
+    pub mesh: Mesh,
+    /// Index into [`GltfScene::materials`], the default material is used if `None`.
+    pub material: Option<usize>,
+}
+
+pub struct GltfNode {
+    pub name: String,
+    pub transform: Transform,
+    pub primitives: Vec<GltfPrimitive>,
+    /// Indices into [`GltfScene::nodes`].
+    pub children: Vec<usize>,
+}
+
+/// A glTF scene loaded into memory, doesn't require a gpu.
+pub struct GltfScene {
+    pub name: String,
+    /// Path relative to the asset root, used for the mesh and material handles.
+    pub path: String,
+    pub materials: Vec<StandardMaterial>,
+    pub nodes: Vec<GltfNode>,
+    /// Indices into [`GltfScene::nodes`] of the nodes without a parent.
+    pub roots: Vec<usize>,
+}
+
+impl GltfScene {
+    /// Loads the default scene of a .gltf or .glb file at `path` relative to `root`, or the
+    /// first scene if there's no default.
+    pub fn load(root: impl AsRef<Path>, path: &str) -> gltf::Result<Self> {
+        let (document, buffers, _images) = gltf::import(root.as_ref().join(path))?;
+
+        let name = Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| String::from("Gltf"));
+
+        let dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+        let mut scene = Self {
+            name,
+            path: String::from(path),
+            materials: document
+                .materials()
+                .map(|material| load_material(&material, dir))
+                .collect(),
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+
+        if let Some(gltf_scene) = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+        {
+            for node in gltf_scene.nodes() {
+                let index = scene.load_node(&node, &buffers);
+                scene.roots.push(index);
+            }
+        }
+
+        Ok(scene)
+    }
+
+    fn load_node(&mut self, node: &gltf::Node, buffers: &[gltf::buffer::Data]) -> usize {
+        let (translation, rotation, scale) = node.transform().decomposed();
+
+        let primitives = node
+            .mesh()
+            .map(|mesh| {
+                mesh.primitives()
+                    .filter_map(|primitive| {
+                        let mut primitive = load_primitive(&primitive, buffers)?;
+                        primitive.label = format!("Mesh{}/{}", mesh.index(), primitive.label);
+                        Some(primitive)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let index = self.nodes.len();
+
+        self.nodes.push(GltfNode {
+            name: node
+                .name()
+                .map(String::from)
+                .unwrap_or_else(|| format!("Node {}", node.index())),
+            transform: Transform {
+                translation: Vec3::from(translation),
+                rotation: Quat::from_xyzw(rotation[0], rotation[1], rotation[2], rotation[3]),
+                scale: Vec3::from(scale),
+            },
+            primitives,
+            children: Vec::new(),
+        });
+
+        for child in node.children() {
+            let child = self.load_node(&child, buffers);
+            self.nodes[index].children.push(child);
+        }
+
+        index
+    }
+
+    /// Spawns the scene under a new node named after the file, and returns its id.
+    ///
+    /// Nodes with a single primitive get a [`Mesh3d`] directly, nodes with several get a
+    /// child node per primitive. The meshes and materials are inserted into `assets` so
+    /// they aren't loaded again.
+    pub fn spawn(
+        &self,
+        tree: &mut Tree,
+        assets: &Assets,
+        parent: impl Into<Option<NodeId>>,
+    ) -> NodeId {
+        let root = tree.spawn();
+
+        if let Some(parent) = parent.into() {
+            tree.set_parent(root, parent);
+        }
+
+        tree.get_node(root).unwrap().name = self.name.clone();
+
+        let materials: Vec<MaterialHandle> = self
+            .materials
+            .iter()
+            .enumerate()
+            .map(|(i, material)| {
+                let path = format!("{}#Material{}", self.path, i);
+                assets.insert::<Box<dyn Material>>(path, Box::new(material.clone()))
+            })
+            .collect();
+
+        for index in &self.roots {
+            self.spawn_node(*index, tree, assets, &materials, root);
+        }
+
+        root
+    }
+
+    fn spawn_node(
+        &self,
+        index: usize,
+        tree: &mut Tree,
+        assets: &Assets,
+        materials: &[MaterialHandle],
+        parent: NodeId,
+    ) {
+        let gltf_node = &self.nodes[index];
+        let node_id = tree.spawn_child(parent).unwrap();
+
+        let mut node = tree.get_node(node_id).unwrap();
+        node.name = gltf_node.name.clone();
+        node.transform = gltf_node.transform.clone();
+
+        if let [primitive] = gltf_node.primitives.as_slice() {
+            self.add_primitive(&mut node, assets, materials, primitive);
+        }
+
+        drop(node);
+
+        if gltf_node.primitives.len() > 1 {
+            for (i, primitive) in gltf_node.primitives.iter().enumerate() {
+                let child = tree.spawn_child(node_id).unwrap();
+
+                let mut node = tree.get_node(child).unwrap();
+                node.name = format!("{} {}", gltf_node.name, i);
+                self.add_primitive(&mut node, assets, materials, primitive);
+            }
+        }
+
+        for child in &gltf_node.children {
+            self.spawn_node(*child, tree, assets, materials, node_id);
+        }
+    }
+
+    fn add_primitive(
+        &self,
+        node: &mut Node,
+        assets: &Assets,
+        materials: &[MaterialHandle],
+        primitive: &GltfPrimitive,
+    ) {
+        let path = format!("{}#{}", self.path, primitive.label);
+
+        node.add_component(Mesh3d {
+            mesh: assets.insert(path, primitive.mesh.clone()),
+            material: primitive
+                .material
+                .map(|index| materials[index].clone())
+                .unwrap_or_default(),
+        });
+    }
+}
+
+/// Loads single meshes from .gltf and .glb files, labeled `Mesh{mesh}/Primitive{primitive}`.
+pub struct GltfMeshLoader;
+
+impl AssetLoader for GltfMeshLoader {
+    type Asset = Mesh;
+
+    fn extensions(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+
+    fn load(&self, ctx: &LoadCtx) -> Result<Mesh, AssetError> {
+        let label = ctx.label.ok_or("gltf meshes require a label")?;
+
+        let (mesh, primitive) = parse_label(label)
+            .ok_or_else(|| format!("invalid label '{}', expected 'Mesh0/Primitive0'", label))?;
+
+        let (document, buffers, _images) = gltf::import(ctx.path)?;
+
+        let primitive = document
+            .meshes()
+            .nth(mesh)
+            .and_then(|mesh| mesh.primitives().nth(primitive))
+            .ok_or_else(|| format!("'{}' not found", label))?;
+
+        let primitive = load_primitive(&primitive, &buffers)
+            .ok_or_else(|| format!("'{}' isn't a triangle primitive", label))?;
+
+        Ok(primitive.mesh)
+    }
+}
+
+/// Loads materials from .gltf and .glb files, labeled `Material{material}`.
+pub struct GltfMaterialLoader;
+
+impl AssetLoader for GltfMaterialLoader {
+    type Asset = Box<dyn Material>;
+
+    fn extensions(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+
+    fn load(&self, ctx: &LoadCtx) -> Result<Box<dyn Material>, AssetError> {
+        let label = ctx.label.ok_or("gltf materials require a label")?;
+
+        let index: usize = label
+            .strip_prefix("Material")
+            .and_then(|index| index.parse().ok())
+            .ok_or_else(|| format!("invalid label '{}', expected 'Material0'", label))?;
+
+        let document = gltf::Gltf::open(ctx.path)?;
+
+        let material = document
+            .materials()
+            .nth(index)
+            .ok_or_else(|| format!("'{}' not found", label))?;
+
+        let dir = Path::new(ctx.file)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+
+        Ok(Box::new(load_material(&material, dir)))
+    }
+}
+
+fn parse_label(label: &str) -> Option<(usize, usize)> {
+    let mut parts = label.split('/');
+
+    let mesh = parts.next()?.strip_prefix("Mesh")?.parse().ok()?;
+    let primitive = parts.next()?.strip_prefix("Primitive")?.parse().ok()?;
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some((mesh, primitive))
+}
+
+/// Reads a triangle primitive into a mesh with the attributes used by the pbr shader.
+///
+/// Missing normals are computed from the faces, missing uvs are zeroed and missing
+/// vertex colors are white.
+fn load_primitive(
+    primitive: &gltf::Primitive,
+    buffers: &[gltf::buffer::Data],
+) -> Option<GltfPrimitive> {
+    if primitive.mode() != gltf::mesh::Mode::Triangles {
+        return None;
+    }
+
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+    let positions: Vec<Vec3> = reader.read_positions()?.map(Vec3::from).collect();
+    let len = positions.len();
+
+    let indices: Vec<u32> = reader
+        .read_indices()
+        .map(|indices| indices.into_u32().collect())
+        .unwrap_or_else(|| (0..len as u32).collect());
+
+    let normals: Vec<Vec3> = reader
+        .read_normals()
+        .map(|normals| normals.map(Vec3::from).collect())
+        .unwrap_or_else(|| compute_normals(&positions, &indices));
+
+    let uvs: Vec<Vec2> = reader
+        .read_tex_coords(0)
+        .map(|uvs| uvs.into_f32().map(Vec2::from).collect())
+        .unwrap_or_else(|| vec![Vec2::ZERO; len]);
+
+    let colors: Vec<Vec4> = reader
+        .read_colors(0)
+        .map(|colors| colors.into_rgba_f32().map(Vec4::from).collect())
+        .unwrap_or_else(|| vec![Vec4::ONE; len]);
+
+    let mut mesh = Mesh::new();
+    mesh.set_attribute("vertex_position", positions);
+    mesh.set_attribute("vertex_normal", normals);
+    mesh.set_attribute("vertex_uv", uvs);
+    mesh.set_attribute("vertex_color", colors);
+    mesh.set_indices(indices);
+
+    Some(GltfPrimitive {
+        label: format!("Primitive{}", primitive.index()),
+        mesh,
+        material: primitive.material().index(),
+    })
+}
+
+/// Reads a material, `dir` is the directory of the gltf file relative to the asset root.
+///
+/// Only textures referring to image files are used, embedded images are ignored.
+fn load_material(material: &gltf::Material, dir: &Path) -> StandardMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    let [er, eg, eb] = material.emissive_factor();
+
+    let texture = |info: Option<gltf::texture::Info>| match info
+        .map(|info| info.texture().source().source())
+    {
+        Some(gltf::image::Source::Uri { uri, .. }) if !uri.starts_with("data:") => {
+            Handle::new(dir.join(uri).to_string_lossy())
+        }
+        _ => Handle::default(),
+    };
+
+    StandardMaterial {
+        albedo: Color::rgba(r, g, b, a),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        emissive: Color::rgb(er, eg, eb),
+        albedo_map: texture(pbr.base_color_texture()),
+        emissive_map: texture(material.emissive_texture()),
+    }
+}
+
+fn compute_normals(positions: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let a = positions[triangle[0] as usize];
+        let b = positions[triangle[1] as usize];
+        let c = positions[triangle[2] as usize];
+
+        let normal = (b - a).cross(c - a);
+
+        for index in triangle {
+            normals[*index as usize] += normal;
+        }
+    }
+
+    normals
+        .into_iter()
+        .map(|normal| normal.normalize_or_zero())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(path: &str) -> GltfScene {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        GltfScene::load(root, path).unwrap()
+    }
+
+    #[test]
+    fn parse_labels() {
+        assert_eq!(parse_label("Mesh0/Primitive1"), Some((0, 1)));
+        assert_eq!(parse_label("Mesh12/Primitive3"), Some((12, 3)));
+        assert_eq!(parse_label("Mesh0"), None);
+        assert_eq!(parse_label("Mesh0/Primitive0/Extra"), None);
+        assert_eq!(parse_label("Material0"), None);
+    }
+
+    #[test]
+    fn load_triangle() {
+        let scene = load("triangle.gltf");
+
+        assert_eq!(scene.name, "triangle");
+        assert_eq!(scene.roots, vec![0]);
+        assert_eq!(scene.nodes.len(), 1);
+        assert_eq!(scene.nodes[0].name, "Triangle");
+
+        let primitives = &scene.nodes[0].primitives;
+        assert_eq!(primitives.len(), 1);
+        assert_eq!(primitives[0].label, "Mesh0/Primitive0");
+        assert_eq!(primitives[0].material, Some(0));
+
+        let mesh = &primitives[0].mesh;
+        assert_eq!(mesh.indices(), &vec![0, 1, 2]);
+        assert_eq!(
+            mesh.get_attribute::<Vec3>("vertex_position").unwrap(),
+            &[Vec3::ZERO, Vec3::X, Vec3::Y]
+        );
+        // the fixture has no normals, uvs or colors
+        assert_eq!(
+            mesh.get_attribute::<Vec3>("vertex_normal").unwrap(),
+            &[Vec3::Z; 3]
+        );
+        assert_eq!(
+            mesh.get_attribute::<Vec2>("vertex_uv").unwrap(),
+            &[Vec2::ZERO; 3]
+        );
+        assert_eq!(
+            mesh.get_attribute::<Vec4>("vertex_color").unwrap(),
+            &[Vec4::ONE; 3]
+        );
+    }
+
+    #[test]
+    fn load_material_factors() {
+        let scene = load("triangle.gltf");
+
+        assert_eq!(scene.materials.len(), 1);
+
+        let material = &scene.materials[0];
+        assert_eq!(material.albedo, Color::rgba(1.0, 0.5, 0.0, 1.0));
+        assert_eq!(material.metallic, 0.25);
+        assert_eq!(material.roughness, 0.5);
+        assert_eq!(material.emissive, Color::rgb(0.0, 0.0, 1.0));
+        assert_eq!(material.albedo_map.path(), "textures/albedo.png");
+        assert!(material.emissive_map.path().is_empty());
+    }
+
+    #[test]
+    fn load_hierarchy() {
+        let scene = load("hierarchy.gltf");
+
+        assert_eq!(scene.roots, vec![0]);
+        assert_eq!(scene.nodes.len(), 2);
+
+        let root = &scene.nodes[0];
+        assert_eq!(root.name, "Root");
+        assert_eq!(root.children, vec![1]);
+        assert!(root.primitives.is_empty());
+
+        let child = &scene.nodes[1];
+        assert_eq!(child.name, "Child");
+        assert_eq!(child.transform.translation, Vec3::new(1.0, 2.0, 3.0));
+
+        // the line primitive is skipped
+        let labels: Vec<&str> = child
+            .primitives
+            .iter()
+            .map(|primitive| primitive.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["Mesh0/Primitive0", "Mesh0/Primitive1"]);
+        assert_eq!(child.primitives[0].material, None);
+        assert_eq!(child.primitives[1].material, Some(0));
+
+        let material = &scene.materials[0];
+        assert_eq!(material.metallic, 1.0);
+        assert_eq!(material.roughness, 0.1);
+        assert_eq!(material.albedo, Color::rgba(1.0, 1.0, 1.0, 1.0));
+    }
+}
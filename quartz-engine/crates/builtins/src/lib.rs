@@ -1,8 +1,17 @@
+pub mod gltf;
 pub mod render3d;
+pub mod texture;
 
 use quartz_engine_core::types::Types;
+pub use crate::gltf::*;
 pub use render3d::*;
+pub use texture::*;
 
 pub fn register_types(types: &mut Types) {
     render3d::register_types(types);
+
+    types.register_asset_loader(GltfMeshLoader);
+    types.register_asset_loader(GltfMaterialLoader);
+    types.register_asset_loader(TextureLoader);
+    types.register_asset_loader(NativeTextureLoader);
 }
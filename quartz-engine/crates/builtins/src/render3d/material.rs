@@ -0,0 +1,224 @@
+use super::{quartz_render, Render3dPlugin};
+use quartz_engine_core::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::any::TypeId;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+
+pub struct MaterialCtx<'a> {
+    pub assets: &'a Assets,
+    pub instance: &'a Instance,
+    /// White 1x1 texture, used in place of missing texture maps.
+    pub default_texture: &'a Arc<Texture2d>,
+    pub sampler: &'a Sampler,
+}
+
+impl<'a> MaterialCtx<'a> {
+    /// Returns the texture for `handle`, or the default texture if it's empty or failed to
+    /// load.
+    pub fn texture(&self, handle: &Handle<Texture2d>) -> Arc<Texture2d> {
+        self.assets
+            .get(handle, self.instance)
+            .unwrap_or_else(|| self.default_texture.clone())
+    }
+}
+
+/// A shader and the values it's rendered with.
+///
+/// The shader must declare the same set 0 and set 1 bindings as `pbr.vert` and `pbr.frag`,
+/// the material's own bindings go in set 2. Meshes are drawn instanced, so the model matrix
+/// is the `mat4 instance_model` vertex input.
+pub trait Material: Send + Sync + 'static {
+    /// Loads the shader, `root` is the asset root. Shaders loaded with [`Shader::load`] are
+    /// rebuilt when their files change.
+    fn shader(root: &Path) -> Result<Shader, Box<dyn std::error::Error>>
+    where
+        Self: Sized;
+
+    fn bind(&self, bindings: &mut Bindings, ctx: &MaterialCtx);
+
+    fn material_type(&self) -> TypeId {
+        TypeId::of::<Self>()
+    }
+}
+
+pub type MaterialHandle = Handle<Box<dyn Material>>;
+
+#[derive(Uniform)]
+pub struct MaterialRaw {
+    pub albedo: Color,
+    pub emissive: Color,
+    pub data: Vec4,
+}
+
+/// The default pbr material, loaded from `.material` files.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StandardMaterial {
+    pub albedo: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: Color,
+    pub albedo_map: Handle<Texture2d>,
+    pub emissive_map: Handle<Texture2d>,
+}
+
+impl StandardMaterial {
+    pub fn raw(&self) -> MaterialRaw {
+        MaterialRaw {
+            albedo: self.albedo,
+            emissive: self.emissive,
+            data: Vec4::new(self.metallic, self.roughness, 0.0, 0.0),
+        }
+    }
+}
+
+impl Default for StandardMaterial {
+    fn default() -> Self {
+        Self {
+            albedo: Color::rgb(1.0, 1.0, 1.0),
+            metallic: 0.0,
+            roughness: 1.0,
+            emissive: Color::rgb(0.0, 0.0, 0.0),
+            albedo_map: Handle::default(),
+            emissive_map: Handle::default(),
+        }
+    }
+}
+
+impl Material for StandardMaterial {
+    /// Loaded from the engine sources if they're available, so the shader can be edited
+    /// while the editor runs.
+    fn shader(_root: &Path) -> Result<Shader, Box<dyn std::error::Error>> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/render3d");
+        let vs_path = dir.join("pbr.vert");
+        let fs_path = dir.join("pbr.frag");
+
+        if vs_path.exists() && fs_path.exists() {
+            Ok(Shader::load(vs_path, fs_path)?)
+        } else {
+            Ok(Shader::from_glsl(
+                include_str!("pbr.vert"),
+                include_str!("pbr.frag"),
+            )?)
+        }
+    }
+
+    fn bind(&self, bindings: &mut Bindings, ctx: &MaterialCtx) {
+        bindings.bind(2, 0, &self.raw());
+        bindings.bind(2, 1, &ctx.texture(&self.albedo_map).view());
+        bindings.bind(2, 2, &ctx.texture(&self.emissive_map).view());
+        bindings.bind(2, 3, ctx.sampler);
+    }
+}
+
+/// Loads materials of type `M` from ron files.
+pub struct MaterialLoader<M> {
+    extensions: [&'static str; 1],
+    _marker: PhantomData<fn() -> M>,
+}
+
+impl<M> MaterialLoader<M> {
+    pub fn new(extension: &'static str) -> Self {
+        Self {
+            extensions: [extension],
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<M: Material + DeserializeOwned> AssetLoader for MaterialLoader<M> {
+    type Asset = Box<dyn Material>;
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+
+    fn load(&self, ctx: &LoadCtx) -> Result<Box<dyn Material>, AssetError> {
+        let source = std::fs::read_to_string(ctx.path)?;
+        let material: M = ron::from_str(&source)?;
+
+        Ok(Box::new(material))
+    }
+}
+
+/// The pipeline of a registered material type, built when the material is first drawn so
+/// the shader can be loaded from the asset root.
+pub struct MaterialPipeline {
+    name: &'static str,
+    shader: fn(&Path) -> Result<Shader, Box<dyn std::error::Error>>,
+    target_format: format::TargetFormat,
+    pipeline: Option<SharedPipeline<RenderPipeline>>,
+}
+
+impl MaterialPipeline {
+    pub fn new<M: Material>(target_format: format::TargetFormat) -> Self {
+        Self {
+            name: std::any::type_name::<M>(),
+            shader: M::shader,
+            target_format,
+            pipeline: None,
+        }
+    }
+
+    /// Returns the pipeline, building it and adding it to [`Plugins::pipelines`] if it isn't
+    /// built yet.
+    ///
+    /// Errors are reported to [`Assets`] under the material's type name, building isn't
+    /// retried until the error is cleared.
+    pub fn get(
+        &mut self,
+        plugins: &Plugins,
+        instance: &Instance,
+    ) -> Option<SharedPipeline<RenderPipeline>> {
+        if self.pipeline.is_none() && plugins.assets.error(self.name).is_none() {
+            match self.build(&plugins.assets.root(), instance) {
+                Ok(pipeline) => {
+                    plugins.assets.clear_error(self.name);
+                    self.pipeline = Some(plugins.pipelines.add(self.name, pipeline));
+                }
+                Err(err) => plugins.assets.set_error(self.name, err),
+            }
+        }
+
+        self.pipeline.clone()
+    }
+
+    fn build(
+        &self,
+        root: &Path,
+        instance: &Instance,
+    ) -> Result<RenderPipeline, Box<dyn std::error::Error>> {
+        RenderPipeline::new(
+            PipelineDescriptor::default_settings(
+                (self.shader)(root)?,
+                ColorState::default_settings(self.target_format),
+                Default::default(),
+            ),
+            instance,
+        )
+    }
+}
+
+/// Registers a custom material, loaded from `.{extension}` files.
+///
+/// Fails if [`Render3dPlugin`] isn't registered. Shader errors are reported to [`Assets`]
+/// under the material's type name when the material is first drawn.
+pub fn register_material<M: Material + DeserializeOwned>(
+    types: &mut Types,
+    extension: &'static str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target_format = types.target_format;
+
+    types
+        .plugins
+        .get_mut::<Render3dPlugin>()
+        .ok_or("Render3dPlugin must be registered before materials")?
+        .materials
+        .insert(TypeId::of::<M>(), MaterialPipeline::new::<M>(target_format));
+
+    types.register_asset_loader(MaterialLoader::<M>::new(extension));
+
+    Ok(())
+}
@@ -1,7 +1,14 @@
-use std::collections::HashMap;
+mod material;
+
+pub use material::*;
+
+use std::any::TypeId;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 
 use quartz_engine_core::egui::Ui;
 use quartz_engine_core::prelude::*;
+use quartz_engine_core::reflect::Value;
 use quartz_engine_core::render::wgpu;
 use serde::{Deserialize, Serialize};
 
@@ -12,23 +19,53 @@ use quartz_engine_core::render as quartz_render;
 
 pub const MAX_POINT_LIGHTS: u32 = 64;
 pub const MAX_DIR_LIGHTS: u32 = 8;
+pub const MAX_CASCADES: u32 = 4;
+/// Point lights casting shadows, each renders 6 cube faces.
+pub const MAX_POINT_SHADOWS: u32 = 8;
+pub const DIRECTIONAL_SHADOW_MAP_SIZE: u32 = 2048;
+pub const POINT_SHADOW_MAP_SIZE: u32 = 1024;
 
 pub fn register_types(types: &mut Types) {
     types.register_plugin::<Render3dPlugin>();
     types.register_component::<PointLight3d>();
-    types.register_component::<DirectionalLight3d>();
+    types
+        .register_component::<DirectionalLight3d>()
+        .migration(0, DirectionalLight3d::migrate_v0);
     types.register_component::<Camera3d>();
     types.register_component::<Mesh3d>();
     types.register_component::<ProceduralMesh3d>();
+    types.register_component::<Material3d>();
+    types.register_asset_loader(MaterialLoader::<StandardMaterial>::new("material"));
 }
 
 #[derive(Reflect, Inspect)]
 pub struct Render3dPlugin {
     pub ambient_light: AmbientLight,
 
+    /// Multisampling of the main render pass.
+    pub msaa: Msaa,
+
+    /// Nodes drawn and culled in the last frame.
+    #[reflect(ignore)]
+    pub culling: CullingStats,
+
+    /// Pipelines of the registered materials, see [`register_material`].
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub materials: HashMap<TypeId, MaterialPipeline>,
+
+    /// Used by meshes without a material.
     #[reflect(ignore)]
     #[inspect(ignore)]
-    pub pbr_pipeline: RenderPipeline,
+    pub default_material: StandardMaterial,
+
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub default_texture: Arc<Texture2d>,
+
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub material_sampler: Sampler,
 
     #[reflect(ignore)]
     #[inspect(ignore)]
@@ -58,20 +95,64 @@ pub struct Render3dPlugin {
     #[inspect(ignore)]
     pub directional_lights: UniformBuffer<DirectionalLightRaw, MAX_DIR_LIGHTS>,
 
+    /// Cube maps of the point lights casting shadows.
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub point_light_maps: TextureCubeArray<format::Depth32Float>,
+
+    /// View projection of each cube face in `point_light_maps`.
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub point_shadows: UniformBuffer<Mat4, { MAX_POINT_SHADOWS * 6 }>,
+
+    /// Keyed by light and cascade or cube face.
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub shadow_bindings: HashMap<(NodeId, u32), Bindings>,
+
+    /// Camera, lights and shadow maps, shared by every draw.
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub scene_bindings: Bindings,
+
+    /// Model matrices of the [`Mesh3d`]s sharing a mesh and material, keyed by material and
+    /// mesh path.
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub instances: HashMap<(String, String), Instances>,
+
+    /// Model matrices of the [`Mesh3d`]s sharing a mesh visible to a light, keyed by light,
+    /// cascade or cube face and mesh path.
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub shadow_instances: HashMap<(NodeId, u32, String), Instances>,
+
+    /// Model matrix of each [`ProceduralMesh3d`].
+    #[reflect(ignore)]
+    #[inspect(ignore)]
+    pub procedural_instances: HashMap<NodeId, Instances>,
+
+    /// Keyed by material path.
     #[reflect(ignore)]
     #[inspect(ignore)]
-    pub shadow_bindings: HashMap<NodeId, Bindings>,
+    pub material_bindings: HashMap<String, Bindings>,
 }
 
 impl Plugin for Render3dPlugin {
     fn init(ctx: PluginInitCtx) -> Self {
-        let pbr_shader =
-            Shader::from_glsl(include_str!("pbr.vert"), include_str!("pbr.frag")).unwrap();
-        let pbr_pipeline = RenderPipeline::new(
-            PipelineDescriptor::default_settings(pbr_shader, ColorState::default_settings(ctx.target_format), Default::default()),
+        let mut materials = HashMap::new();
+        materials.insert(
+            TypeId::of::<StandardMaterial>(),
+            MaterialPipeline::new::<StandardMaterial>(ctx.target_format),
+        );
+
+        let mut default_texture = Texture::new(
+            &TextureDescriptor::default_settings(D2::new(1, 1)),
             ctx.instance,
-        )
-        .unwrap();
+        );
+        default_texture.write(ctx.instance, |data| {
+            data[0][0] = Color::rgb(1.0, 1.0, 1.0);
+        });
 
         let shadow_shader =
             Shader::from_glsl(include_str!("shadow.vert"), include_str!("shadow.frag")).unwrap();
@@ -84,15 +165,29 @@ impl Plugin for Render3dPlugin {
                     ..Default::default()
                 },
                 depth_stencil: Default::default(),
+                multisample: Default::default(),
             },
             ctx.instance,
         )
         .unwrap();
 
         let shadow_map_sampler = Sampler::new(&SamplerDescriptor::default(), ctx.instance);
+        let material_sampler = Sampler::new(&SamplerDescriptor::default(), ctx.instance);
 
         let directional_light_maps = Texture2dArray::new(
-            &TextureDescriptor::default_settings(D2Array::new(4096, 4096, MAX_DIR_LIGHTS)),
+            &TextureDescriptor::default_settings(D2Array::new(
+                DIRECTIONAL_SHADOW_MAP_SIZE,
+                DIRECTIONAL_SHADOW_MAP_SIZE,
+                MAX_DIR_LIGHTS * MAX_CASCADES,
+            )),
+            ctx.instance,
+        );
+
+        let point_light_maps = TextureCubeArray::new(
+            &TextureDescriptor::default_settings(CubeArray::new(
+                POINT_SHADOW_MAP_SIZE,
+                MAX_POINT_SHADOWS,
+            )),
             ctx.instance,
         );
 
@@ -101,7 +196,12 @@ impl Plugin for Render3dPlugin {
                 color: Default::default(),
                 intensity: 0.0,
             },
-            pbr_pipeline,
+            msaa: Msaa::Off,
+            culling: CullingStats::default(),
+            materials,
+            default_material: StandardMaterial::default(),
+            default_texture: Arc::new(default_texture),
+            material_sampler,
             shadow_pipeline,
             main_camera: None,
             view_proj: None,
@@ -109,7 +209,14 @@ impl Plugin for Render3dPlugin {
             point_lights: UniformBuffer::new(),
             directional_light_maps,
             directional_lights: UniformBuffer::new(),
+            point_light_maps,
+            point_shadows: UniformBuffer::new(),
             shadow_bindings: HashMap::new(),
+            scene_bindings: Bindings::new(),
+            instances: HashMap::new(),
+            shadow_instances: HashMap::new(),
+            procedural_instances: HashMap::new(),
+            material_bindings: HashMap::new(),
         }
     }
 
@@ -121,81 +228,502 @@ impl Plugin for Render3dPlugin {
         self.editor_update(ctx);
     }
 
-    fn editor_update(&mut self, ctx: PluginCtx) {
+    fn editor_update(&mut self, _ctx: PluginCtx) {
         self.point_lights.clear();
         self.directional_lights.clear();
+    }
+
+    fn render(&mut self, ctx: PluginRenderCtx) {
+        if let Some(main_camera) = self.main_camera {
+            if let Some(node) = ctx.tree.get_node(main_camera) {
+                if let Some(camera) = node.get_component::<Camera3d>() {
+                    let mut projection = camera.projection.clone();
+                    projection.aspect = ctx.target.width() as f32 / ctx.target.height() as f32;
+                    let view_proj =
+                        projection.matrix() * node.global_transform().matrix().inverse();
+                    self.view_proj = Some(view_proj);
+                }
+            }
+        } else {
+            self.view_proj = None;
+        }
+
+        if let Some(view_proj) = ctx.viewport_camera.or(self.view_proj) {
+            self.render_shadows(ctx, view_proj);
+        }
+    }
 
-        let mut render_ctx = ctx.instance.render();
+    fn sample_count(&self) -> u32 {
+        self.msaa.sample_count()
+    }
+
+    /// Draws [`Mesh3d`]s and [`ProceduralMesh3d`]s grouped by material, so each material's
+    /// pipeline and bindings are only set once, and [`Mesh3d`]s sharing a mesh are drawn
+    /// instanced.
+    fn draw(&mut self, ctx: PluginDrawCtx) {
+        let view_proj = match ctx.viewport_camera.or(self.view_proj) {
+            Some(view_proj) => view_proj,
+            None => return,
+        };
+
+        let frustum = Frustum::from_view_proj(&view_proj);
+
+        self.culling.drawn = 0;
+        self.culling.culled = 0;
+
+        // meshes drawn with each material, keyed by material path
+        let mut batches: BTreeMap<String, MaterialBatch> = BTreeMap::new();
 
         for node_id in ctx.tree.nodes() {
-            let node = ctx.tree.get_node(node_id).unwrap();
+            if let Some(node) = ctx.tree.get_node(node_id) {
+                let model = node.global_transform().matrix();
+                let inline = node
+                    .get_component::<Material3d>()
+                    .map(|material| material.standard());
+
+                if let Some(mesh) = node.get_component::<Mesh3d>() {
+                    MaterialBatch::get(&mut batches, node_id, &mesh.material, inline)
+                        .meshes
+                        .entry(mesh.mesh.path().to_string())
+                        .or_insert_with(|| (mesh.mesh.clone(), Vec::new()))
+                        .1
+                        .push(model);
+                } else if let Some(mesh) = node.get_component::<ProceduralMesh3d>() {
+                    if !is_visible(&frustum, mesh.mesh.aabb(), &model) {
+                        self.culling.culled += 1;
+                        continue;
+                    }
 
-            let light_pos = node.global_transform().translation;
+                    self.culling.drawn += 1;
 
-            let light = node.get_component::<DirectionalLight3d>();
+                    let instances = self
+                        .procedural_instances
+                        .entry(node_id)
+                        .or_insert_with(Instances::new);
+                    instances.set_attribute("instance_model", &[model], ctx.instance);
 
-            if let Some(light) = light {
-                if light.shadows {
-                    let light_view_proj =
-                        light.projection() * node.global_transform().matrix().inverse();
+                    MaterialBatch::get(&mut batches, node_id, &mesh.material, inline)
+                        .procedural_meshes
+                        .push(node_id);
+                }
+            }
+        }
 
-                    let desc = RenderPassDescriptor {
-                        label: Some(String::from("Shadow pass")),
-                        color_attachments: (),
-                        depth_attachment: DepthAttachment::default_settings(
-                            self.directional_light_maps.layer_view(light.index),
-                        ),
-                    };
+        self.instances.retain(|(material, mesh), _| {
+            batches
+                .get(material)
+                .map_or(false, |batch| batch.meshes.contains_key(mesh))
+        });
+        let procedural_meshes: HashSet<NodeId> = batches
+            .values()
+            .flat_map(|batch| batch.procedural_meshes.iter().copied())
+            .collect();
+        self.procedural_instances
+            .retain(|node_id, _| procedural_meshes.contains(node_id));
+        self.material_bindings
+            .retain(|path, _| batches.contains_key(path));
+
+        let camera = CameraRaw {
+            view_proj,
+            position: camera_position(&view_proj),
+        };
 
-                    let mut pass = render_ctx.render_pass(&desc, &self.shadow_pipeline);
+        self.scene_bindings.bind(0, 1, &camera);
+        self.scene_bindings.bind(0, 2, &self.point_lights);
+        self.scene_bindings.bind(0, 3, &self.directional_lights);
+        self.scene_bindings.bind(0, 4, &self.ambient_light);
+        self.scene_bindings.bind(0, 5, &self.point_shadows);
+        self.scene_bindings.bind(1, 0, &self.directional_light_maps.view());
+        self.scene_bindings.bind(1, 1, &self.shadow_map_sampler);
+        self.scene_bindings.bind(1, 2, &self.point_light_maps.view());
+
+        let material_ctx = MaterialCtx {
+            assets: &ctx.plugins.assets,
+            instance: ctx.instance,
+            default_texture: &self.default_texture,
+            sampler: &self.material_sampler,
+        };
 
-                    for node_id in ctx.tree.nodes() {
-                        if let Some(node) = ctx.tree.get_node(node_id) {
-                            if let Some(mesh) = node.get_component::<ProceduralMesh3d>() {
-                                let model = node.global_transform().matrix();
+        for (path, batch) in &batches {
+            let handle = &batch.material;
+
+            let material = match batch.inline {
+                Some(_) => None,
+                None => ctx.plugins.assets.get(handle, ctx.instance),
+            };
+            let material: &dyn Material = match (&batch.inline, &material) {
+                (Some(inline), _) => inline,
+                (None, Some(material)) => &***material,
+                (None, None) => &self.default_material,
+            };
+
+            let pipeline = match self.materials.get_mut(&material.material_type()) {
+                Some(pipeline) => pipeline,
+                None => {
+                    ctx.plugins
+                        .assets
+                        .set_error(handle.path(), "material type isn't registered");
+                    continue;
+                }
+            };
 
-                                let bindings = self.shadow_bindings.entry(node_id).or_default();
+            // the shader failed to build, the error is already reported
+            let pipeline = match pipeline.get(ctx.plugins, ctx.instance) {
+                Some(pipeline) => pipeline,
+                None => continue,
+            };
+            let pipeline = pipeline.borrow();
 
-                                bindings.bind(0, 0, &model);
-                                bindings.bind(0, 1, &light_view_proj);
-                                
-                                pass.set_bindings(bindings);
-                                pass.draw_mesh(&mesh.mesh);
-                            }
+            let material_bindings = self.material_bindings.entry(path.clone()).or_default();
+            material.bind(material_bindings, &material_ctx);
 
-                            if let Some(mesh) = node.get_component::<Mesh3d>() {
-                                let model = node.global_transform().matrix();
+            let mut pass = ctx.render_pass.with_pipeline(&pipeline);
+            pass.set_bindings(&mut self.scene_bindings);
+            pass.set_bindings(material_bindings);
 
-                                let bindings = self.shadow_bindings.entry(node_id).or_default();
+            for (mesh_path, (mesh, models)) in &batch.meshes {
+                if let Some(mesh) = ctx.plugins.assets.get(mesh, ctx.instance) {
+                    let visible = cull(&frustum, mesh.aabb(), models);
 
-                                bindings.bind(0, 0, &model);
-                                bindings.bind(0, 1, &light_view_proj);
+                    self.culling.drawn += visible.len() as u32;
+                    self.culling.culled += (models.len() - visible.len()) as u32;
 
-                                pass.set_bindings(bindings);
-                                pass.draw_mesh(&mesh.mesh);
-                            }
-                        }
+                    if visible.is_empty() {
+                        continue;
+                    }
+
+                    let instances = self
+                        .instances
+                        .entry((path.clone(), mesh_path.clone()))
+                        .or_insert_with(Instances::new);
+                    instances.set_attribute("instance_model", &visible, ctx.instance);
+
+                    pass.draw_mesh_instanced(&mesh, instances);
+                }
+            }
+
+            for node_id in &batch.procedural_meshes {
+                if let Some(node) = ctx.tree.get_node(*node_id) {
+                    if let Some(mesh) = node.get_component::<ProceduralMesh3d>() {
+                        pass.draw_mesh_instanced(&mesh.mesh, &self.procedural_instances[node_id]);
                     }
                 }
             }
         }
     }
+}
 
-    fn render(&mut self, ctx: PluginRenderCtx) {
-        if let Some(main_camera) = self.main_camera {
-            if let Some(node) = ctx.tree.get_node(main_camera) {
-                if let Some(camera) = node.get_component::<Camera3d>() {
-                    let mut projection = camera.projection.clone();
-                    projection.aspect = ctx.target.width() as f32 / ctx.target.height() as f32;
-                    let view_proj =
-                        projection.matrix() * node.global_transform().matrix().inverse();
-                    self.view_proj = Some(view_proj);
+impl Render3dPlugin {
+    /// Renders the shadow maps of the lights casting shadows, directional light cascades are
+    /// fitted to the camera `view_proj`.
+    fn render_shadows(&mut self, ctx: PluginRenderCtx, view_proj: Mat4) {
+        let mut meshes: BTreeMap<String, (Handle<Mesh>, Vec<Mat4>)> = BTreeMap::new();
+        let mut procedural_meshes = Vec::new();
+        let mut passes = Vec::new();
+
+        self.point_shadows.clear();
+
+        for node_id in ctx.tree.nodes() {
+            if let Some(node) = ctx.tree.get_node(node_id) {
+                let model = node.global_transform().matrix();
+
+                if let Some(mesh) = node.get_component::<Mesh3d>() {
+                    meshes
+                        .entry(mesh.mesh.path().to_string())
+                        .or_insert_with(|| (mesh.mesh.clone(), Vec::new()))
+                        .1
+                        .push(model);
+                } else if node.get_component::<ProceduralMesh3d>().is_some() {
+                    let instances = self
+                        .procedural_instances
+                        .entry(node_id)
+                        .or_insert_with(Instances::new);
+                    instances.set_attribute("instance_model", &[model], ctx.instance);
+
+                    procedural_meshes.push(node_id);
+                }
+            }
+        }
+
+        for node_id in ctx.tree.query::<DirectionalLight3d>() {
+            let node = match ctx.tree.get_node(node_id) {
+                Some(node) => node,
+                None => continue,
+            };
+
+            let light = node.get_component::<DirectionalLight3d>().unwrap();
+
+            if light.shadows && light.index < self.directional_lights.len() {
+                let cascades = light.cascade_view_projs(node.global_transform(), &view_proj);
+
+                for (cascade, light_view_proj) in cascades.into_iter().enumerate() {
+                    self.directional_lights[light.index].cascades[cascade] = light_view_proj;
+
+                    passes.push(ShadowPass {
+                        light: node_id,
+                        index: cascade as u32,
+                        view_proj: light_view_proj,
+                        layer: light.index * MAX_CASCADES + cascade as u32,
+                        resolution: light.shadow_resolution(),
+                        point: false,
+                    });
                 }
             }
-        } else {
-            self.view_proj = None;
+        }
+
+        for node_id in ctx.tree.query::<PointLight3d>() {
+            let node = match ctx.tree.get_node(node_id) {
+                Some(node) => node,
+                None => continue,
+            };
+
+            let light = node.get_component::<PointLight3d>().unwrap();
+            let shadow_index = self.point_shadows.len() / 6;
+
+            if light.shadows
+                && light.index < self.point_lights.len()
+                && shadow_index < MAX_POINT_SHADOWS
+            {
+                self.point_lights[light.index].shadow_index = shadow_index as i32;
+
+                let faces = light.face_view_projs(node.global_transform().translation);
+
+                for (face, light_view_proj) in faces.iter().enumerate() {
+                    self.point_shadows.push(*light_view_proj).unwrap();
+
+                    passes.push(ShadowPass {
+                        light: node_id,
+                        index: face as u32,
+                        view_proj: *light_view_proj,
+                        layer: shadow_index * 6 + face as u32,
+                        resolution: light.shadow_resolution(),
+                        point: true,
+                    });
+                }
+            }
+        }
+
+        self.culling.shadow_drawn = 0;
+        self.culling.shadow_culled = 0;
+
+        let mut shadow_instances = HashSet::new();
+
+        for shadow_pass in &passes {
+            let view = if shadow_pass.point {
+                self.point_light_maps.face_view(shadow_pass.layer / 6, shadow_pass.layer % 6)
+            } else {
+                self.directional_light_maps.layer_view(shadow_pass.layer)
+            };
+
+            let desc = RenderPassDescriptor {
+                label: Some(String::from("Shadow pass")),
+                color_attachments: (),
+                depth_attachment: DepthAttachment::default_settings(view),
+            };
+
+            let frustum = Frustum::from_view_proj(&shadow_pass.view_proj);
+
+            let bindings = self
+                .shadow_bindings
+                .entry((shadow_pass.light, shadow_pass.index))
+                .or_default();
+            bindings.bind(0, 1, &shadow_pass.view_proj);
+
+            let resolution = shadow_pass.resolution as f32;
+
+            let mut pass = ctx.render_ctx.render_pass(&desc, &self.shadow_pipeline);
+            pass.set_viewport(0.0, 0.0, resolution, resolution);
+            pass.set_bindings(bindings);
+
+            for (path, (handle, models)) in &meshes {
+                if let Some(mesh) = ctx.plugins.assets.get(handle, ctx.instance) {
+                    let visible = cull(&frustum, mesh.aabb(), models);
+
+                    self.culling.shadow_drawn += visible.len() as u32;
+                    self.culling.shadow_culled += (models.len() - visible.len()) as u32;
+
+                    if visible.is_empty() {
+                        continue;
+                    }
+
+                    let key = (shadow_pass.light, shadow_pass.index, path.clone());
+                    let instances = self
+                        .shadow_instances
+                        .entry(key.clone())
+                        .or_insert_with(Instances::new);
+                    instances.set_attribute("instance_model", &visible, ctx.instance);
+                    shadow_instances.insert(key);
+
+                    pass.draw_mesh_instanced(&mesh, instances);
+                }
+            }
+
+            for node_id in &procedural_meshes {
+                if let Some(node) = ctx.tree.get_node(*node_id) {
+                    if let Some(mesh) = node.get_component::<ProceduralMesh3d>() {
+                        let model = node.global_transform().matrix();
+
+                        if !is_visible(&frustum, mesh.mesh.aabb(), &model) {
+                            self.culling.shadow_culled += 1;
+                            continue;
+                        }
+
+                        self.culling.shadow_drawn += 1;
+
+                        pass.draw_mesh_instanced(&mesh.mesh, &self.procedural_instances[node_id]);
+                    }
+                }
+            }
+        }
+
+        self.shadow_instances.retain(|key, _| shadow_instances.contains(key));
+        self.shadow_bindings.retain(|key, _| {
+            passes
+                .iter()
+                .any(|shadow_pass| (shadow_pass.light, shadow_pass.index) == *key)
+        });
+    }
+}
+
+/// A shadow map layer rendered from a light, `index` is the cascade or cube face.
+struct ShadowPass {
+    light: NodeId,
+    index: u32,
+    view_proj: Mat4,
+    layer: u32,
+    resolution: u32,
+    /// Renders into the point light maps instead of the directional light maps.
+    point: bool,
+}
+
+/// Returns true if a mesh with bounds `aabb` transformed by `model` may be inside `frustum`,
+/// meshes without bounds are always visible.
+fn is_visible(frustum: &Frustum, aabb: Option<Aabb>, model: &Mat4) -> bool {
+    aabb.map_or(true, |aabb| frustum.intersects_aabb(&aabb.transformed(model)))
+}
+
+/// Model matrices of the instances of a mesh with bounds `aabb` that may be inside `frustum`.
+fn cull(frustum: &Frustum, aabb: Option<Aabb>, models: &[Mat4]) -> Vec<Mat4> {
+    models
+        .iter()
+        .copied()
+        .filter(|model| is_visible(frustum, aabb, model))
+        .collect()
+}
+
+/// Number of nodes drawn and skipped for being outside the view, in the main pass and summed
+/// over the shadow passes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CullingStats {
+    pub drawn: u32,
+    pub culled: u32,
+    pub shadow_drawn: u32,
+    pub shadow_culled: u32,
+}
+
+impl Inspect for CullingStats {
+    fn inspect(&mut self, ui: &mut Ui) -> bool {
+        ui.label(format!("Drawn: {}, culled: {}", self.drawn, self.culled));
+        ui.label(format!(
+            "Shadows drawn: {}, culled: {}",
+            self.shadow_drawn, self.shadow_culled
+        ));
+
+        false
+    }
+}
+
+/// Nodes drawn with one material, [`Mesh3d`]s sharing a mesh are drawn in one instanced draw.
+struct MaterialBatch {
+    material: MaterialHandle,
+    /// Set by a [`Material3d`], used instead of `material`.
+    inline: Option<StandardMaterial>,
+    /// Mesh handle and model matrices, keyed by mesh path.
+    meshes: BTreeMap<String, (Handle<Mesh>, Vec<Mat4>)>,
+    procedural_meshes: Vec<NodeId>,
+}
+
+impl MaterialBatch {
+    fn new(material: MaterialHandle, inline: Option<StandardMaterial>) -> Self {
+        Self {
+            material,
+            inline,
+            meshes: BTreeMap::new(),
+            procedural_meshes: Vec::new(),
         }
     }
+
+    /// Gets the batch of `material`, nodes with a [`Material3d`] get a batch of their own.
+    fn get<'a>(
+        batches: &'a mut BTreeMap<String, MaterialBatch>,
+        node_id: NodeId,
+        material: &MaterialHandle,
+        inline: Option<StandardMaterial>,
+    ) -> &'a mut Self {
+        match inline {
+            Some(inline) => batches
+                .entry(format!("#Material3d/{}", node_id.0))
+                .or_insert_with(|| Self::new(MaterialHandle::default(), Some(inline))),
+            None => batches
+                .entry(material.path().to_string())
+                .or_insert_with(|| Self::new(material.clone(), None)),
+        }
+    }
+}
+
+#[derive(Uniform)]
+pub struct CameraRaw {
+    pub view_proj: Mat4,
+    /// Position of the camera with w = 1, or the direction towards it with w = 0 for
+    /// orthographic projections.
+    pub position: Vec4,
+}
+
+/// Gets the position of the camera rendering with `view_proj`, see [`CameraRaw::position`].
+fn camera_position(view_proj: &Mat4) -> Vec4 {
+    // the camera is where all points on the near and far planes project from
+    let p = view_proj.inverse() * Vec4::new(0.0, 0.0, 1.0, 0.0);
+
+    if p.w.abs() > f32::EPSILON {
+        p / p.w
+    } else {
+        (-p.truncate().normalize()).extend(0.0)
+    }
+}
+
+/// Sample count of the main render pass, only 4 is guaranteed to be supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Msaa {
+    Off,
+    X2,
+    X4,
+    X8,
+}
+
+impl Msaa {
+    pub fn sample_count(self) -> u32 {
+        match self {
+            Self::Off => 1,
+            Self::X2 => 2,
+            Self::X4 => 4,
+            Self::X8 => 8,
+        }
+    }
+}
+
+impl Inspect for Msaa {
+    fn inspect(&mut self, ui: &mut Ui) -> bool {
+        let prev = *self;
+
+        ui.horizontal(|ui| {
+            ui.radio_value(self, Self::Off, "Off");
+            ui.radio_value(self, Self::X2, "2x");
+            ui.radio_value(self, Self::X4, "4x");
+            ui.radio_value(self, Self::X8, "8x");
+        });
+
+        *self != prev
+    }
 }
 
 #[derive(Uniform, Inspect, Serialize, Deserialize)]
@@ -208,19 +736,75 @@ pub struct AmbientLight {
 pub struct PointLightRaw {
     pub color: Color,
     pub data: Vec4,
+    /// Index of the light's cube faces in the point shadow maps, -1 without shadows.
+    pub shadow_index: i32,
+    pub shadow_bias: f32,
+    /// Part of each shadow map layer rendered to.
+    pub shadow_scale: f32,
 }
 
+/// Near plane of the point light shadow cube faces.
+const POINT_SHADOW_NEAR: f32 = 0.5;
+
 #[derive(Reflect, Inspect)]
 pub struct PointLight3d {
+    #[inspect(ignore)]
+    pub index: u32,
     pub color: Color,
     pub intensity: f32,
+    /// Only the first [`MAX_POINT_SHADOWS`] lights with shadows cast them.
+    pub shadows: bool,
+    /// Distance from the light shadows are cast up to.
+    pub shadow_range: f32,
+    /// Size of each cube face's shadow map, at most [`POINT_SHADOW_MAP_SIZE`].
+    pub shadow_resolution: u32,
+    pub shadow_bias: f32,
+}
+
+impl PointLight3d {
+    pub fn shadow_resolution(&self) -> u32 {
+        self.shadow_resolution.clamp(1, POINT_SHADOW_MAP_SIZE)
+    }
+
+    /// View projections of the cube faces around `position`, in the order +x, -x, +y, -y, +z
+    /// and -z.
+    pub fn face_view_projs(&self, position: Vec3) -> [Mat4; 6] {
+        let faces = [
+            (Vec3::X, -Vec3::Y),
+            (-Vec3::X, -Vec3::Y),
+            (Vec3::Y, Vec3::Z),
+            (-Vec3::Y, -Vec3::Z),
+            (Vec3::Z, -Vec3::Y),
+            (-Vec3::Z, -Vec3::Y),
+        ];
+
+        let proj = Mat4::perspective_rh(
+            std::f32::consts::FRAC_PI_2,
+            1.0,
+            POINT_SHADOW_NEAR,
+            self.shadow_range.max(POINT_SHADOW_NEAR * 2.0),
+        );
+
+        let mut view_projs = [Mat4::IDENTITY; 6];
+
+        for (view_proj, (direction, up)) in view_projs.iter_mut().zip(faces.iter()) {
+            *view_proj = proj * Mat4::look_at_rh(position, position + *direction, *up);
+        }
+
+        view_projs
+    }
 }
 
 impl Default for PointLight3d {
     fn default() -> Self {
         Self {
+            index: 0,
             color: Color::rgb(1.0, 1.0, 1.0),
             intensity: 10.0,
+            shadows: false,
+            shadow_range: 50.0,
+            shadow_resolution: POINT_SHADOW_MAP_SIZE,
+            shadow_bias: 0.0001,
         }
     }
 }
@@ -240,8 +824,13 @@ impl Component for PointLight3d {
         let light_raw = PointLightRaw {
             color: self.color,
             data: ctx.global_transform.translation.extend(self.intensity),
+            shadow_index: -1,
+            shadow_bias: self.shadow_bias,
+            shadow_scale: self.shadow_resolution() as f32 / POINT_SHADOW_MAP_SIZE as f32,
         };
 
+        self.index = render.point_lights.len();
+
         render
             .point_lights
             .push(light_raw)
@@ -254,11 +843,20 @@ pub struct DirectionalLightRaw {
     pub color: Color,
     pub pos: Vec3,
     pub data: Vec4,
-    pub view_proj: Mat4,
+    /// View projection of each cascade, set when the shadow maps are rendered.
+    pub cascades: [Mat4; MAX_CASCADES as usize],
+    pub shadow_bias: f32,
+    /// Part of each shadow map layer rendered to.
+    pub shadow_scale: f32,
+    pub cascade_count: u32,
     pub shadows: bool,
 }
 
+/// How far behind a cascade shadow casters are rendered from, towards the light.
+const SHADOW_CASTER_DISTANCE: f32 = 500.0;
+
 #[derive(Reflect, Inspect)]
+#[reflect(version = 1)]
 pub struct DirectionalLight3d {
     #[inspect(ignore)]
     pub index: u32,
@@ -266,24 +864,89 @@ pub struct DirectionalLight3d {
     pub direction: Vec3,
     pub intensity: f32,
     pub shadows: bool,
-    pub area: f32,
+    /// Number of cascades the camera's view is split into, at most [`MAX_CASCADES`].
+    pub cascades: u32,
+    /// Distance from the camera's near plane each cascade ends at.
+    pub cascade_splits: Vec4,
+    /// Size of each cascade's shadow map, at most [`DIRECTIONAL_SHADOW_MAP_SIZE`].
+    pub shadow_resolution: u32,
+    pub shadow_bias: f32,
 }
 
 impl DirectionalLight3d {
-    pub fn projection(&self) -> Mat4 {
-        let proj = OrthographicProjection {
-            left: -self.area / 2.0,
-            right: self.area / 2.0,
-            top: self.area / 2.0,
-            bottom: -self.area / 2.0,
-            far: 500.0,
-            near: -500.0,
-        };
+    /// Version 0 had a fixed shadow `area`, replaced by the cascades.
+    fn migrate_v0(mut data: Value) -> Value {
+        data.remove_field("area");
+
+        data
+    }
+
+    pub fn cascade_count(&self) -> u32 {
+        self.cascades.clamp(1, MAX_CASCADES)
+    }
+
+    pub fn shadow_resolution(&self) -> u32 {
+        self.shadow_resolution.clamp(1, DIRECTIONAL_SHADOW_MAP_SIZE)
+    }
+
+    /// View matrix of the shadow cascades.
+    pub fn view(&self, global_transform: &Transform) -> Mat4 {
         let mut dir = self.direction.normalize();
         dir.y *= -1.0;
         let rot = Quat::from_rotation_arc(-Vec3::Z, dir);
 
-        proj.matrix() * Mat4::from_quat(rot)
+        Mat4::from_quat(rot) * global_transform.matrix().inverse()
+    }
+
+    /// View projection of each cascade, fitted around its slice of the camera's frustum.
+    pub fn cascade_view_projs(&self, global_transform: &Transform, camera: &Mat4) -> Vec<Mat4> {
+        let view = self.view(global_transform);
+        let inverse = camera.inverse();
+
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)];
+        let edges: Vec<(Vec3, Vec3)> = corners
+            .iter()
+            .map(|&(x, y)| {
+                (
+                    inverse.project_point3(Vec3::new(x, y, 0.0)),
+                    inverse.project_point3(Vec3::new(x, y, 1.0)),
+                )
+            })
+            .collect();
+
+        let depth = (inverse.project_point3(Vec3::Z) - inverse.project_point3(Vec3::ZERO)).length();
+        let splits = self.cascade_splits.to_array();
+
+        let mut start = 0.0;
+
+        splits[..self.cascade_count() as usize]
+            .iter()
+            .map(|split| {
+                let end = (*split / depth).max(start).min(1.0);
+
+                let points: Vec<Vec3> = edges
+                    .iter()
+                    .flat_map(|(near, far)| vec![near.lerp(*far, start), near.lerp(*far, end)])
+                    .map(|point| view.transform_point3(point))
+                    .collect();
+
+                start = end;
+
+                let aabb = Aabb::from_points(&points).unwrap();
+
+                // the view looks down -z
+                let proj = Mat4::orthographic_rh(
+                    aabb.min.x,
+                    aabb.max.x,
+                    aabb.min.y,
+                    aabb.max.y,
+                    -aabb.max.z - SHADOW_CASTER_DISTANCE,
+                    -aabb.min.z,
+                );
+
+                proj * view
+            })
+            .collect()
     }
 }
 
@@ -295,7 +958,10 @@ impl Default for DirectionalLight3d {
             direction: Vec3::new(0.0, -1.0, 0.0),
             intensity: 10.0,
             shadows: true,
-            area: 120.0,
+            cascades: MAX_CASCADES,
+            cascade_splits: Vec4::new(10.0, 30.0, 80.0, 200.0),
+            shadow_resolution: DIRECTIONAL_SHADOW_MAP_SIZE,
+            shadow_bias: 0.001,
         }
     }
 }
@@ -330,14 +996,14 @@ impl Component for DirectionalLight3d {
             .rotation
             .mul_vec3(self.direction.normalize());
 
-        let proj = self.projection();
-        let view_proj = proj * ctx.global_transform.matrix().inverse();
-
         let light_raw = DirectionalLightRaw {
             color: self.color,
             pos: ctx.global_transform.translation,
             data: direction.extend(self.intensity),
-            view_proj,
+            cascades: [Mat4::IDENTITY; MAX_CASCADES as usize],
+            shadow_bias: self.shadow_bias,
+            shadow_scale: self.shadow_resolution() as f32 / DIRECTIONAL_SHADOW_MAP_SIZE as f32,
+            cascade_count: self.cascade_count(),
             shadows: self.shadows,
         };
 
@@ -371,63 +1037,68 @@ impl Component for Camera3d {
     }
 }
 
-#[derive(Reflect, Inspect)]
-pub struct Mesh3d {
-    #[inspect(collapsing)]
-    pub mesh: Mesh,
+/// Surface properties overriding the material of a [`Mesh3d`] or [`ProceduralMesh3d`] on the
+/// same node, without a `.material` asset.
+#[derive(Clone, Reflect, Inspect)]
+pub struct Material3d {
+    pub albedo: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: Color,
+}
 
-    #[reflect(ignore)]
-    #[inspect(ignore)]
-    pub bindings: Bindings,
+impl Material3d {
+    pub fn standard(&self) -> StandardMaterial {
+        StandardMaterial {
+            albedo: self.albedo,
+            metallic: self.metallic,
+            roughness: self.roughness,
+            emissive: self.emissive,
+            ..Default::default()
+        }
+    }
 }
 
-impl Default for Mesh3d {
+impl Default for Material3d {
     fn default() -> Self {
-        let mut mesh = Mesh::new();
-
-        mesh.add_attribute::<Vec3>("vertex_position");
-        mesh.add_attribute::<Vec3>("vertex_normal");
-        mesh.add_attribute::<Vec2>("vertex_uv");
-        mesh.add_attribute::<Vec4>("vertex_color");
-
-        Self { mesh, bindings: Default::default() }
+        Self {
+            albedo: Color::rgb(1.0, 1.0, 1.0),
+            metallic: 0.0,
+            roughness: 1.0,
+            emissive: Color::rgb(0.0, 0.0, 0.0),
+        }
     }
 }
 
-impl Component for Mesh3d {
+impl Component for Material3d {
     type Plugins = Render3dPlugin;
 
     fn inspector_ui(&mut self, _: &mut Render3dPlugin, _: ComponentCtx, ui: &mut Ui) {
         self.inspect(ui);
     }
+}
 
-    fn render(&mut self, render: &mut Render3dPlugin, ctx: ComponentRenderCtx) {
-        let camera = if ctx.viewport_camera.is_some() {
-            ctx.viewport_camera
-        } else {
-            &render.view_proj
-        };
+/// Renders a mesh asset, eg. `"models/tree.gltf#Mesh0/Primitive0"`.
+///
+/// Drawn by [`Render3dPlugin`], with the default material if `material` is empty, or the
+/// [`Material3d`] on the same node.
+#[derive(Default, Reflect, Inspect)]
+pub struct Mesh3d {
+    pub mesh: Handle<Mesh>,
+    pub material: MaterialHandle,
+}
 
-        if let Some(view_proj) = camera {
-            let model = ctx.global_transform.matrix();
-
-            self.bindings.bind(0, 0, &model);
-            self.bindings.bind(0, 1, view_proj);
-            self.bindings.bind(0, 2, &render.point_lights);
-            self.bindings.bind(0, 3, &render.directional_lights);
-            self.bindings.bind(0, 4, &render.ambient_light);
-            self.bindings.bind(1, 0, &render.directional_light_maps.view());
-            self.bindings.bind(1, 1, &render.shadow_map_sampler);
-
-            ctx.render_pass
-                .with_pipeline(&render.pbr_pipeline)
-                .set_bindings(&mut self.bindings)
-                .draw_mesh(&self.mesh);
-        }
+impl Component for Mesh3d {
+    type Plugins = Render3dPlugin;
+
+    fn inspector_ui(&mut self, _: &mut Render3dPlugin, _: ComponentCtx, ui: &mut Ui) {
+        self.inspect(ui);
     }
 
     fn viewport_pick_render(&mut self, _: &mut Render3dPlugin, ctx: ComponentPickCtx) {
-        ctx.render_pass.draw_mesh(&self.mesh);
+        if let Some(mesh) = ctx.plugins.assets.get(&self.mesh, ctx.instance) {
+            ctx.render_pass.draw_mesh(&mesh);
+        }
     }
 }
 
@@ -437,9 +1108,7 @@ pub struct ProceduralMesh3d {
     #[inspect(collapsing)]
     pub mesh: Mesh,
 
-    #[reflect(ignore)]
-    #[inspect(ignore)]
-    pub bindings: Bindings,
+    pub material: MaterialHandle,
 }
 
 impl Default for ProceduralMesh3d {
@@ -451,7 +1120,7 @@ impl Default for ProceduralMesh3d {
         mesh.add_attribute::<Vec2>("vertex_uv");
         mesh.add_attribute::<Vec4>("vertex_color");
 
-        Self { mesh, bindings: Default::default() }
+        Self { mesh, material: Default::default() }
     }
 }
 
@@ -462,31 +1131,6 @@ impl Component for ProceduralMesh3d {
         self.inspect(ui);
     }
 
-    fn render(&mut self, render: &mut Render3dPlugin, ctx: ComponentRenderCtx) {
-        let camera = if ctx.viewport_camera.is_some() {
-            ctx.viewport_camera
-        } else {
-            &render.view_proj
-        };
-
-        if let Some(view_proj) = camera {
-            let model = ctx.global_transform.matrix();
-
-            self.bindings.bind(0, 0, &model);
-            self.bindings.bind(0, 1, view_proj);
-            self.bindings.bind(0, 2, &render.point_lights);
-            self.bindings.bind(0, 3, &render.directional_lights);
-            self.bindings.bind(0, 4, &render.ambient_light);
-            self.bindings.bind(1, 0, &render.directional_light_maps.view());
-            self.bindings.bind(1, 1, &render.shadow_map_sampler);
-
-            ctx.render_pass
-                .with_pipeline(&render.pbr_pipeline)
-                .set_bindings(&mut self.bindings)
-                .draw_mesh(&self.mesh);
-        }
-    }
-
     fn viewport_pick_render(&mut self, _: &mut Render3dPlugin, ctx: ComponentPickCtx) {
         //ctx.render_pass.draw_mesh(&self.mesh);
     }
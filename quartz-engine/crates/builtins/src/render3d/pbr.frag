@@ -3,11 +3,14 @@
 layout(location = 0) in vec3 v_world_position;
 layout(location = 1) in vec3 v_world_normal;
 layout(location = 2) in vec4 v_color;
+layout(location = 3) in vec2 v_uv;
 
 layout(location = 0) out vec4 out_color;
 
 const int MAX_LIGHTS = 64;
 const int MAX_DIR_LIGHTS = 8;
+const int MAX_CASCADES = 4;
+const int MAX_POINT_SHADOWS = 8;
 
 struct AmbientLightRaw {
     vec4 color;
@@ -17,16 +20,28 @@ struct AmbientLightRaw {
 struct PointLight {
     vec4 color;
     vec4 data;
+    int shadow_index;
+    float shadow_bias;
+    float shadow_scale;
 };
 
 struct DirectionalLight {
     vec4 color;
     vec3 pos;
     vec4 data;
-    mat4 view_proj;
+    mat4 cascades[MAX_CASCADES];
+    float shadow_bias;
+    float shadow_scale;
+    uint cascade_count;
     bool shadows;
 };
 
+layout(set = 0, binding = 1) uniform Camera {
+    mat4 view_proj;
+    // w is 0 for orthographic cameras, xyz is then the direction towards the camera
+    vec4 camera_position;
+};
+
 layout(set = 0, binding = 2) uniform PointLights {
     uint num_point_lights;
     PointLight point_lights[MAX_LIGHTS];
@@ -41,17 +56,160 @@ layout(set = 0, binding = 4) uniform AmbientLight {
     AmbientLightRaw ambient;
 };
 
+layout(set = 0, binding = 5) uniform PointShadows {
+    uint num_point_shadow_faces;
+    mat4 point_shadows[MAX_POINT_SHADOWS * 6];
+};
+
 layout(set = 1, binding = 0) uniform texture2DArray DirectionalShadowMaps;
 layout(set = 1, binding = 1) uniform sampler ShadowSampler;
+layout(set = 1, binding = 2) uniform textureCubeArray PointShadowMaps;
+
+layout(set = 2, binding = 0) uniform Material {
+    vec4 albedo;
+    vec4 emissive;
+    vec4 material_data;
+};
+
+layout(set = 2, binding = 1) uniform texture2D AlbedoMap;
+layout(set = 2, binding = 2) uniform texture2D EmissiveMap;
+layout(set = 2, binding = 3) uniform sampler MaterialSampler;
+
+// direction sampling `face` of a cube map at `uv`, faces are in the order +x, -x, +y, -y, +z, -z
+vec3 cube_direction(int face, vec2 uv) {
+    vec2 st = uv * 2.0 - 1.0;
+
+    switch (face) {
+        case 0: return vec3(1.0, -st.y, -st.x);
+        case 1: return vec3(-1.0, -st.y, st.x);
+        case 2: return vec3(st.x, 1.0, st.y);
+        case 3: return vec3(st.x, -1.0, -st.y);
+        case 4: return vec3(st.x, -st.y, 1.0);
+        default: return vec3(-st.x, -st.y, -1.0);
+    }
+}
+
+// depth stored in a shadow map at `uv`, `layer` is the cube face index for point shadows
+float shadow_depth(bool point, vec2 uv, int layer) {
+    if (point) {
+        vec3 direction = cube_direction(layer % 6, uv);
+
+        return texture(
+            samplerCubeArray(PointShadowMaps, ShadowSampler),
+            vec4(direction, layer / 6)
+        ).r;
+    } else {
+        return texture(sampler2DArray(DirectionalShadowMaps, ShadowSampler), vec3(uv, layer)).r;
+    }
+}
+
+// how lit a point at `p`, in the clip space of a shadow map layer or cube face, is
+// only `scale` of each layer is rendered to
+float shadow_factor(bool point, vec3 p, int layer, float scale, float bias) {
+    if (p.z > 1.0) {
+        return 1.0;
+    }
+
+    vec2 uv = p.xy;
+    uv /= 2.0;
+    uv.y *= -1.0;
+    uv += 0.5;
+
+    const int BLUR = 1;
+    float lit = 0.0;
+    vec2 size = point
+        ? vec2(textureSize(samplerCubeArray(PointShadowMaps, ShadowSampler), 0).xy)
+        : vec2(textureSize(sampler2DArray(DirectionalShadowMaps, ShadowSampler), 0).xy);
+    vec2 texel_size = 1.0 / (size * scale);
+
+    for (int x = -BLUR; x <= BLUR; x++) {
+        for (int y = -BLUR; y <= BLUR; y++) {
+            vec2 offset_uv = uv + vec2(x, y) * texel_size / BLUR;
+
+            if (any(lessThanEqual(offset_uv, vec2(0.0))) || any(greaterThanEqual(offset_uv, vec2(1.0)))) {
+                lit += 1.0;
+            } else {
+                float depth = shadow_depth(point, offset_uv * scale, layer);
+
+                if (p.z < depth + bias) {
+                    lit += 1.0;
+                }
+            }
+        }
+    }
+
+    return lit / pow(BLUR * 2 + 1, 2);
+}
+
+const float PI = 3.14159265359;
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+float distribution_ggx(float n_dot_h, float roughness) {
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+
+    return a2 / (PI * d * d);
+}
+
+float geometry_smith(float n_dot_v, float n_dot_l, float roughness) {
+    float r = roughness + 1.0;
+    float k = r * r / 8.0;
+
+    return n_dot_v / (n_dot_v * (1.0 - k) + k) * n_dot_l / (n_dot_l * (1.0 - k) + k);
+}
+
+// light reflected towards `v` from a light in direction `l`, per unit of light
+// the diffuse term isn't divided by pi, so light intensities keep their meaning
+vec3 brdf(vec3 n, vec3 v, vec3 l, vec3 albedo, float metallic, float roughness) {
+    float n_dot_l = max(dot(n, l), 0.0);
+
+    if (n_dot_l <= 0.0) {
+        return vec3(0.0);
+    }
+
+    vec3 h = normalize(v + l);
+    float n_dot_v = max(dot(n, v), 0.0001);
+
+    vec3 f0 = mix(vec3(0.04), albedo, metallic);
+    vec3 f = fresnel_schlick(max(dot(h, v), 0.0), f0);
+
+    vec3 specular = distribution_ggx(max(dot(n, h), 0.0), roughness)
+        * geometry_smith(n_dot_v, n_dot_l, roughness)
+        * f
+        / (4.0 * n_dot_v * n_dot_l);
+
+    vec3 diffuse = (1.0 - f) * (1.0 - metallic) * albedo;
+
+    return (diffuse + specular) * n_dot_l;
+}
 
 void main() {
-    vec3 color = v_color.rgb;
+    vec4 albedo_color = albedo * texture(sampler2D(AlbedoMap, MaterialSampler), v_uv);
+    vec3 emissive_color = emissive.rgb * texture(sampler2D(EmissiveMap, MaterialSampler), v_uv).rgb;
+
+    vec3 base_color = v_color.rgb * albedo_color.rgb;
+    float metallic = clamp(material_data.x, 0.0, 1.0);
+    // fully smooth surfaces make the specular highlight infinitely small
+    float roughness = clamp(material_data.y, 0.04, 1.0);
+
+    vec3 normal = normalize(v_world_normal);
+    vec3 view;
+
+    if (camera_position.w == 0.0) {
+        view = normalize(camera_position.xyz);
+    } else {
+        view = normalize(camera_position.xyz - v_world_position);
+    }
 
     vec3 ambient = ambient.color.rgb * ambient.intensity;
 
-    float sky_diffuse = 0.5 + 0.5 * max(dot(normalize(v_world_normal), vec3(0.0, 1.0, 0.0)), 0.0);
+    float sky_diffuse = 0.5 + 0.5 * max(dot(normal, vec3(0.0, 1.0, 0.0)), 0.0);
 
-    vec3 light = ambient * sky_diffuse;
+    vec3 color = ambient * sky_diffuse * base_color;
     
     for (int i = 0; i < num_point_lights; i++) {
         PointLight point_light = point_lights[i];
@@ -62,54 +220,54 @@ void main() {
         float falloff = 1.0 / (dist * dist);
         float intensity = falloff * point_light.data.w;
 
-        light += point_light.color.rgb * max(dot(normalize(v_world_normal), direction), 0.0) * intensity;
+        float lit = 1.0;
+
+        if (point_light.shadow_index >= 0) {
+            // pick the cube face the fragment is on, in the order +x, -x, +y, -y, +z, -z
+            vec3 a = abs(delta);
+            int face;
+
+            if (a.x >= a.y && a.x >= a.z) {
+                face = delta.x < 0.0 ? 0 : 1;
+            } else if (a.y >= a.z) {
+                face = delta.y < 0.0 ? 2 : 3;
+            } else {
+                face = delta.z < 0.0 ? 4 : 5;
+            }
+
+            int layer = point_light.shadow_index * 6 + face;
+            vec4 p = point_shadows[layer] * vec4(v_world_position, 1.0);
+
+            lit = shadow_factor(true, p.xyz / p.w, layer, point_light.shadow_scale, point_light.shadow_bias);
+        }
+
+        vec3 reflected = brdf(normal, view, direction, base_color, metallic, roughness);
+        color += point_light.color.rgb * reflected * intensity * lit;
     }
 
     for (int i = 0; i < num_directional_lights; i++) {
         DirectionalLight dlight = directional_lights[i];
 
-        vec4 p = (dlight.view_proj * vec4(v_world_position, 1.0));
-        vec2 uv = p.xy;
-        uv /= 2.0;
-        uv.y *= -1.0;
-        uv += 0.5;
-
-        const int BLUR = 1;
-        float shadow = 0.0;
-        vec2 texel_size = 1.0 / vec2(textureSize(sampler2DArray(DirectionalShadowMaps, ShadowSampler), 0));
+        float shadow = 1.0;
 
         if (dlight.shadows) {
-            for (int x = -BLUR; x <= BLUR; x++) {
-                for (int y = -BLUR; y <= BLUR; y++) {
-                    vec2 offset = vec2(x, y) * texel_size / BLUR;
-
-                    float depth = texture(
-                        sampler2DArray(DirectionalShadowMaps, ShadowSampler), 
-                        vec3(uv + offset, i)
-                    ).r;
-
-                    float dist = p.z;
-
-                    if (any(lessThanEqual(uv + offset, vec2(0.0))) || any(greaterThanEqual(uv + offset, vec2(1.0)))) {
-                        shadow += 1.0;
-                    } else if (dist < depth + 0.001) {
-                        shadow += 1.0;
-                    }
+            // the first cascade containing the fragment has the most detail
+            for (uint c = 0u; c < dlight.cascade_count; c++) {
+                vec4 p = dlight.cascades[c] * vec4(v_world_position, 1.0);
+
+                if (all(lessThan(abs(p.xy), vec2(1.0))) && p.z >= 0.0 && p.z <= 1.0) {
+                    int layer = i * MAX_CASCADES + int(c);
+                    shadow = shadow_factor(false, p.xyz, layer, dlight.shadow_scale, dlight.shadow_bias);
+                    break;
                 }
             }
-
-            shadow /= pow(BLUR * 2 + 1, 2);
-        } else {
-            shadow = 1.0;
         }
 
-
-        float diffuse = max(dot(normalize(v_world_normal), -dlight.data.xyz), 0.0);
-
-        light += dlight.color.rgb * dlight.data.w * shadow * diffuse;
+        vec3 reflected = brdf(normal, view, -dlight.data.xyz, base_color, metallic, roughness);
+        color += dlight.color.rgb * dlight.data.w * shadow * reflected;
     }
 
-    color *= light;
+    color += emissive_color;
 
-    out_color = vec4(color, 1.0);
-}
\ No newline at end of file
+    out_color = vec4(color, v_color.a * albedo_color.a);
+}
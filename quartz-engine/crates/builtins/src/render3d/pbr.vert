@@ -4,22 +4,22 @@ layout(location = 0) in vec3 vertex_position;
 layout(location = 1) in vec3 vertex_normal;
 layout(location = 2) in vec2 vertex_uv;
 layout(location = 3) in vec4 vertex_color;
+layout(location = 4) in mat4 instance_model;
 
 layout(location = 0) out vec3 v_world_position;
 layout(location = 1) out vec3 v_world_normal;
 layout(location = 2) out vec4 v_color;
-
-layout(set = 0, binding = 0) uniform Transform {
-    mat4 model;
-};
+layout(location = 3) out vec2 v_uv;
 
 layout(set = 0, binding = 1) uniform Camera {
     mat4 view_proj;
+    vec4 camera_position;
 };
 
 void main() {
-    v_world_position = (model * vec4(vertex_position, 1.0)).xyz;
-    v_world_normal = normalize((model * vec4(vertex_normal, 0.0)).xyz);
+    v_world_position = (instance_model * vec4(vertex_position, 1.0)).xyz;
+    v_world_normal = normalize((instance_model * vec4(vertex_normal, 0.0)).xyz);
     v_color = vertex_color;
-    gl_Position = view_proj * model * vec4(vertex_position, 1.0);
+    v_uv = vertex_uv;
+    gl_Position = view_proj * instance_model * vec4(vertex_position, 1.0);
 }
\ No newline at end of file
@@ -1,15 +1,12 @@
 #version 450 
 
 layout(location = 0) in vec3 vertex_position;
-
-layout(set = 0, binding = 0) uniform Transform {
-    mat4 model;
-};
+layout(location = 1) in mat4 instance_model;
 
 layout(set = 0, binding = 1) uniform Camera {
     mat4 view_proj;
 };
 
 void main() {
-    gl_Position = view_proj * model * vec4(vertex_position, 1.0);
+    gl_Position = view_proj * instance_model * vec4(vertex_position, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,35 @@
+use quartz_engine_core::prelude::*;
+
+/// Loads image files into [`Texture2d`]s, see [`Texture::load`].
+///
+/// Images are decoded into 8 bit srgb, see [`NativeTextureLoader`] for keeping compressed and
+/// hdr formats.
+pub struct TextureLoader;
+
+impl AssetLoader for TextureLoader {
+    type Asset = Texture2d;
+
+    fn extensions(&self) -> &[&str] {
+        &["png", "jpg", "jpeg", "hdr", "ktx2", "dds"]
+    }
+
+    fn load(&self, ctx: &LoadCtx) -> Result<Texture2d, AssetError> {
+        Ok(Texture2d::load(ctx.path, ctx.instance)?)
+    }
+}
+
+/// Loads image files into [`Texture2d`]s keeping the format they're stored in, see
+/// [`Texture::load_native`].
+pub struct NativeTextureLoader;
+
+impl AssetLoader for NativeTextureLoader {
+    type Asset = Texture2d<format::AnyFormat>;
+
+    fn extensions(&self) -> &[&str] {
+        &["png", "jpg", "jpeg", "hdr", "ktx2", "dds"]
+    }
+
+    fn load(&self, ctx: &LoadCtx) -> Result<Texture2d<format::AnyFormat>, AssetError> {
+        Ok(Texture2d::load_native(ctx.path, ctx.instance)?)
+    }
+}
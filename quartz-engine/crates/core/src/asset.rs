@@ -0,0 +1,587 @@
+use crate::inspect::*;
+use egui::*;
+use quartz_render::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, Weak};
+
+pub type AssetError = Box<dyn std::error::Error + Send + Sync>;
+
+pub trait Asset: Send + Sync + 'static {}
+
+impl<T: Send + Sync + 'static> Asset for T {}
+
+pub struct LoadCtx<'a> {
+    /// Full path of the file, including the asset root.
+    pub path: &'a Path,
+    /// Path of the file relative to the asset root, for handles to other assets.
+    pub file: &'a str,
+    /// The part of the asset path after `#`, used to pick sub assets.
+    pub label: Option<&'a str>,
+    pub instance: &'a Instance,
+}
+
+pub trait AssetLoader: Send + Sync + 'static {
+    type Asset: Asset;
+
+    /// File extensions handled by the loader, without the leading dot.
+    fn extensions(&self) -> &[&str];
+
+    fn load(&self, ctx: &LoadCtx) -> Result<Self::Asset, AssetError>;
+}
+
+trait ErasedLoader: Send + Sync {
+    fn load(&self, ctx: &LoadCtx) -> Result<Arc<dyn Any + Send + Sync>, AssetError>;
+}
+
+impl<L: AssetLoader> ErasedLoader for L {
+    fn load(&self, ctx: &LoadCtx) -> Result<Arc<dyn Any + Send + Sync>, AssetError> {
+        Ok(Arc::new(AssetLoader::load(self, ctx)?))
+    }
+}
+
+/// Refers to an asset by its path relative to the asset root, eg. `"models/tree.gltf#Mesh0"`.
+///
+/// Serializes as the path, so components can store handles instead of the asset itself.
+pub struct Handle<T> {
+    path: Arc<String>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: Arc::new(path.into()),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The path without the label.
+    pub fn file(&self) -> &str {
+        split_label(&self.path).0
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        split_label(&self.path).1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.path.is_empty()
+    }
+}
+
+fn split_label(path: &str) -> (&str, Option<&str>) {
+    match path.find('#') {
+        Some(index) => (&path[..index], Some(&path[index + 1..])),
+        None => (path, None),
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for Handle<T> {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Handle({:?})", self.path)
+    }
+}
+
+impl<T> Serialize for Handle<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.path.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Handle<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::new(String::deserialize(deserializer)?))
+    }
+}
+
+impl<T> Inspect for Handle<T> {
+    fn inspect(&mut self, ui: &mut Ui) -> bool {
+        let mut path = self.path.to_string();
+
+        if ui.text_edit_singleline(&mut path).changed() {
+            *self = Self::new(path);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct AssetEntry {
+    asset: Arc<dyn Any + Send + Sync>,
+    /// Handles that have accessed the asset, it's freed when all of them are dropped.
+    handles: Vec<Weak<String>>,
+}
+
+/// Loads assets lazily from the asset root and caches them until no handles remain.
+pub struct Assets {
+    root: RwLock<PathBuf>,
+    loaders: HashMap<TypeId, HashMap<String, Arc<dyn ErasedLoader>>>,
+    assets: RwLock<HashMap<(TypeId, String), AssetEntry>>,
+    errors: RwLock<HashMap<String, String>>,
+}
+
+impl Default for Assets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        Self {
+            root: RwLock::new(PathBuf::from(".")),
+            loaders: HashMap::new(),
+            assets: RwLock::new(HashMap::new()),
+            errors: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the directory asset paths are relative to, usually the project directory.
+    pub fn set_root(&self, root: impl Into<PathBuf>) {
+        *self.root.write().unwrap() = root.into();
+    }
+
+    pub fn root(&self) -> PathBuf {
+        self.root.read().unwrap().clone()
+    }
+
+    pub fn register_loader<L: AssetLoader>(&mut self, loader: L) {
+        let extensions: Vec<String> = loader
+            .extensions()
+            .iter()
+            .map(|extension| extension.to_lowercase())
+            .collect();
+        let loader: Arc<dyn ErasedLoader> = Arc::new(loader);
+
+        let loaders = self.loaders.entry(TypeId::of::<L::Asset>()).or_default();
+
+        for extension in extensions {
+            loaders.insert(extension, loader.clone());
+        }
+    }
+
+    /// Inserts an asset that wasn't loaded from a file, eg. a generated mesh.
+    pub fn insert<T: Asset>(&self, path: impl Into<String>, asset: T) -> Handle<T> {
+        let handle = Handle::new(path);
+
+        self.assets.write().unwrap().insert(
+            (TypeId::of::<T>(), handle.path().to_string()),
+            AssetEntry {
+                asset: Arc::new(asset),
+                handles: vec![Arc::downgrade(&handle.path)],
+            },
+        );
+
+        handle
+    }
+
+    /// Returns the asset for `handle`, loading it if it isn't cached.
+    ///
+    /// Returns `None` if the handle is empty or loading failed, see [`Assets::error`].
+    pub fn get<T: Asset>(&self, handle: &Handle<T>, instance: &Instance) -> Option<Arc<T>> {
+        if handle.is_empty() {
+            return None;
+        }
+
+        let key = (TypeId::of::<T>(), handle.path().to_string());
+
+        if let Some(entry) = self.assets.write().unwrap().get_mut(&key) {
+            if !entry
+                .handles
+                .iter()
+                .any(|weak| weak.as_ptr() == Arc::as_ptr(&handle.path))
+            {
+                entry.handles.push(Arc::downgrade(&handle.path));
+            }
+
+            return entry.asset.clone().downcast().ok();
+        }
+
+        if self.errors.read().unwrap().contains_key(handle.path()) {
+            return None;
+        }
+
+        match self.load_asset::<T>(handle, instance) {
+            Ok(asset) => {
+                self.assets.write().unwrap().insert(
+                    key,
+                    AssetEntry {
+                        asset: asset.clone(),
+                        handles: vec![Arc::downgrade(&handle.path)],
+                    },
+                );
+
+                asset.downcast().ok()
+            }
+            Err(err) => {
+                log::error!("failed to load asset {}: {}", handle.path(), err);
+
+                self.errors
+                    .write()
+                    .unwrap()
+                    .insert(handle.path().to_string(), err.to_string());
+
+                None
+            }
+        }
+    }
+
+    fn load_asset<T: Asset>(
+        &self,
+        handle: &Handle<T>,
+        instance: &Instance,
+    ) -> Result<Arc<dyn Any + Send + Sync>, AssetError> {
+        let path = self.root().join(handle.file());
+
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        let loader = self
+            .loaders
+            .get(&TypeId::of::<T>())
+            .and_then(|loaders| loaders.get(&extension))
+            .ok_or_else(|| {
+                format!(
+                    "no loader for '{}' files producing {}",
+                    extension,
+                    std::any::type_name::<T>()
+                )
+            })?;
+
+        loader.load(&LoadCtx {
+            path: &path,
+            file: handle.file(),
+            label: handle.label(),
+            instance,
+        })
+    }
+
+    /// The error from the last attempt to load `path`, if it failed.
+    pub fn error(&self, path: &str) -> Option<String> {
+        self.errors.read().unwrap().get(path).cloned()
+    }
+
+    /// All load errors, sorted by path.
+    pub fn errors(&self) -> Vec<(String, String)> {
+        let mut errors: Vec<_> = self
+            .errors
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(path, err)| (path.clone(), err.clone()))
+            .collect();
+
+        errors.sort();
+        errors
+    }
+
+    /// Reports an error for `path` that wasn't caused by a loader, eg. a shader failing to
+    /// compile, so the editor can show it.
+    pub fn set_error(&self, path: impl Into<String>, error: impl ToString) {
+        self.errors
+            .write()
+            .unwrap()
+            .insert(path.into(), error.to_string());
+    }
+
+    /// Clears the error reported for `path`.
+    pub fn clear_error(&self, path: &str) {
+        self.errors.write().unwrap().remove(path);
+    }
+
+    /// Clears all errors, failed assets are loaded again when next accessed.
+    pub fn clear_errors(&self) {
+        self.errors.write().unwrap().clear();
+    }
+
+    /// Drops cached assets loaded from `file`, they're loaded again when next accessed.
+    ///
+    /// `file` is relative to the asset root.
+    pub fn reload(&self, file: &str) {
+        self.assets
+            .write()
+            .unwrap()
+            .retain(|(_, path), _| split_label(path).0 != file);
+        self.errors
+            .write()
+            .unwrap()
+            .retain(|path, _| split_label(path).0 != file);
+    }
+
+    /// Drops assets that no handle refers to anymore.
+    pub fn free_unused(&self) {
+        self.assets.write().unwrap().retain(|_, entry| {
+            entry.handles.retain(|weak| weak.strong_count() > 0);
+            !entry.handles.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, PartialEq)]
+    struct Text(String);
+
+    /// Loads text files, counting how often it's called.
+    #[derive(Default)]
+    struct TextLoader {
+        loads: Arc<AtomicUsize>,
+    }
+
+    impl AssetLoader for TextLoader {
+        type Asset = Text;
+
+        fn extensions(&self) -> &[&str] {
+            &["txt", "MD"]
+        }
+
+        fn load(&self, ctx: &LoadCtx) -> Result<Text, AssetError> {
+            self.loads.fetch_add(1, Ordering::SeqCst);
+
+            let text = std::fs::read_to_string(ctx.path)?;
+
+            match ctx.label {
+                Some(label) => Ok(Text(format!("{}#{}", text, label))),
+                None => Ok(Text(text)),
+            }
+        }
+    }
+
+    /// Parses numbers from the same files as [`TextLoader`].
+    struct NumberLoader;
+
+    impl AssetLoader for NumberLoader {
+        type Asset = u32;
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+
+        fn load(&self, ctx: &LoadCtx) -> Result<u32, AssetError> {
+            Ok(std::fs::read_to_string(ctx.path)?.trim().parse()?)
+        }
+    }
+
+    fn instance() -> Option<Instance> {
+        futures::executor::block_on(Instance::headless(Default::default()))
+    }
+
+    /// Assets rooted in a new temporary directory containing `files`.
+    fn assets(name: &str, files: &[(&str, &str)]) -> (Assets, Arc<AtomicUsize>) {
+        let root = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&root).unwrap();
+
+        for (file, contents) in files {
+            std::fs::write(root.join(file), contents).unwrap();
+        }
+
+        let loader = TextLoader::default();
+        let loads = loader.loads.clone();
+
+        let mut assets = Assets::default();
+        assets.set_root(root);
+        assets.register_loader(loader);
+        assets.register_loader(NumberLoader);
+
+        (assets, loads)
+    }
+
+    fn cached(assets: &Assets) -> usize {
+        assets.assets.read().unwrap().len()
+    }
+
+    #[test]
+    fn frees_assets_without_handles() {
+        let assets = Assets::default();
+        let mesh = assets.insert("mesh", Text(String::from("mesh")));
+        let copy = mesh.clone();
+        let texture = assets.insert("texture", 5u32);
+
+        drop(mesh);
+        assets.free_unused();
+        assert_eq!(cached(&assets), 2);
+
+        drop(copy);
+        assets.free_unused();
+        assert_eq!(cached(&assets), 1);
+
+        drop(texture);
+        assets.free_unused();
+        assert_eq!(cached(&assets), 0);
+    }
+
+    #[test]
+    fn splits_labels() {
+        let handle = Handle::<Text>::new("models/tree.gltf#Mesh0");
+        assert_eq!(handle.file(), "models/tree.gltf");
+        assert_eq!(handle.label(), Some("Mesh0"));
+
+        let handle = Handle::<Text>::new("models/tree.gltf");
+        assert_eq!(handle.file(), "models/tree.gltf");
+        assert_eq!(handle.label(), None);
+        assert!(Handle::<Text>::default().is_empty());
+    }
+
+    #[test]
+    fn loads_by_extension_and_type() {
+        // nothing to test against without an adapter
+        let instance = match instance() {
+            Some(instance) => instance,
+            None => return,
+        };
+
+        let files = [
+            ("hello.txt", "12"),
+            ("notes.md", "notes"),
+            ("upper.TXT", "up"),
+        ];
+        let (assets, loads) = assets("quartz-test-assets-load", &files);
+
+        let text = assets.get(&Handle::<Text>::new("hello.txt"), &instance);
+        assert_eq!(text.unwrap().0, "12");
+        let number = assets.get(&Handle::<u32>::new("hello.txt"), &instance);
+        assert_eq!(*number.unwrap(), 12);
+
+        // extensions are matched case insensitively
+        let notes = assets.get(&Handle::<Text>::new("notes.md"), &instance);
+        assert_eq!(notes.unwrap().0, "notes");
+        let upper = assets.get(&Handle::<Text>::new("upper.TXT"), &instance);
+        assert_eq!(upper.unwrap().0, "up");
+
+        let labeled = assets.get(&Handle::<Text>::new("hello.txt#first"), &instance);
+        assert_eq!(labeled.unwrap().0, "12#first");
+
+        assert_eq!(loads.load(Ordering::SeqCst), 4);
+        assert!(assets.get(&Handle::<Text>::default(), &instance).is_none());
+        assert!(assets.errors().is_empty());
+
+        std::fs::remove_dir_all(assets.root()).unwrap();
+    }
+
+    #[test]
+    fn caches_until_unused() {
+        let instance = match instance() {
+            Some(instance) => instance,
+            None => return,
+        };
+
+        let (assets, loads) = assets("quartz-test-assets-cache", &[("a.txt", "a")]);
+
+        let handle = Handle::<Text>::new("a.txt");
+        let other = Handle::<Text>::new("a.txt");
+        let first = assets.get(&handle, &instance).unwrap();
+        let second = assets.get(&other, &instance).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        // the asset stays while any handle that accessed it is alive
+        drop(handle);
+        assets.free_unused();
+        assert_eq!(cached(&assets), 1);
+
+        drop(other);
+        assets.free_unused();
+        assert_eq!(cached(&assets), 0);
+
+        let handle = Handle::<Text>::new("a.txt");
+        assets.get(&handle, &instance).unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+        assets.reload("a.txt");
+        assert_eq!(cached(&assets), 0);
+        assets.get(&handle, &instance).unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+
+        std::fs::remove_dir_all(assets.root()).unwrap();
+    }
+
+    #[test]
+    fn reports_errors() {
+        let instance = match instance() {
+            Some(instance) => instance,
+            None => return,
+        };
+
+        let (assets, loads) = assets("quartz-test-assets-errors", &[("word.txt", "word")]);
+
+        assert!(assets
+            .get(&Handle::<Text>::new("missing.txt"), &instance)
+            .is_none());
+        assert!(assets
+            .get(&Handle::<u32>::new("word.txt"), &instance)
+            .is_none());
+        assert!(assets
+            .get(&Handle::<Text>::new("word.png"), &instance)
+            .is_none());
+
+        let errors = assets.errors();
+        let paths = errors
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["missing.txt", "word.png", "word.txt"]);
+        assert!(assets
+            .error("word.png")
+            .unwrap()
+            .contains("no loader for 'png'"));
+
+        // failed assets aren't loaded again until the error is cleared
+        assert!(assets
+            .get(&Handle::<Text>::new("missing.txt"), &instance)
+            .is_none());
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        assets.clear_error("missing.txt");
+        assert!(assets.error("missing.txt").is_none());
+        assert!(assets
+            .get(&Handle::<Text>::new("missing.txt"), &instance)
+            .is_none());
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+        assets.set_error("shader.wgsl", "failed to compile");
+        assert_eq!(assets.error("shader.wgsl").unwrap(), "failed to compile");
+
+        assets.clear_errors();
+        assert!(assets.errors().is_empty());
+
+        std::fs::remove_dir_all(assets.root()).unwrap();
+    }
+}
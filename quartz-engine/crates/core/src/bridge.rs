@@ -1,4 +1,5 @@
 use crate::game_state::*;
+use crate::scene::*;
 use crate::tree::*;
 use crate::types::*;
 use libloading::*;
@@ -7,6 +8,31 @@ use serde::{de::DeserializeSeed, Deserializer};
 
 pub type InitFunction = fn(*mut Types);
 
+#[derive(Debug)]
+pub enum BridgeError {
+    Library(Error),
+    /// The scene couldn't be read, components that fail to load are kept on their node
+    /// instead, see [`OpaqueComponent`](crate::component::OpaqueComponent).
+    Scene(String),
+}
+
+impl std::fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Library(err) => write!(f, "{}", err),
+            Self::Scene(err) => write!(f, "failed to load scene: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BridgeError {}
+
+impl From<Error> for BridgeError {
+    fn from(err: Error) -> Self {
+        Self::Library(err)
+    }
+}
+
 pub struct Bridge {
     lib: Library,
 }
@@ -43,12 +69,43 @@ impl Bridge {
         ))
     }
 
+    /// Loads the scene `data`, stored in `format`, see [`SceneFormat::detect`].
+    pub fn load_scene(
+        &self,
+        data: &[u8],
+        format: SceneFormat,
+        instance: &Instance,
+        target_format: format::TargetFormat,
+    ) -> Result<GameState, BridgeError> {
+        let new: Symbol<InitFunction> = unsafe { self.lib.get(b"new") }?;
+
+        let mut types = Types::new(instance, target_format);
+
+        new(&mut types as *mut _);
+
+        let seed = crate::reflect::serde::SceneDeserializer {
+            components: &types.components,
+            plugins: &mut types.plugins,
+        };
+
+        let tree = format
+            .deserialize_seed(data, seed)
+            .map_err(|err| BridgeError::Scene(err.to_string()))?;
+
+        Ok(GameState::new(
+            tree,
+            Box::new(types.plugins),
+            Box::new(types.components),
+            instance,
+        ))
+    }
+
     pub fn deserialize<'de, D: Deserializer<'de>>(
         &self,
         deserializer: D,
         instance: &Instance,
         target_format: format::TargetFormat,
-    ) -> Result<GameState, Error> {
+    ) -> Result<GameState, BridgeError> {
         let new: Symbol<InitFunction> = unsafe { self.lib.get(b"new") }?;
 
         let mut types = Types::new(instance, target_format);
@@ -60,7 +117,7 @@ impl Bridge {
             plugins: &mut types.plugins,
         }
         .deserialize(deserializer)
-        .unwrap();
+        .map_err(|err| BridgeError::Scene(err.to_string()))?;
 
         Ok(GameState::new(
             tree,
@@ -1,12 +1,16 @@
+use crate::event::*;
+use crate::input::*;
 use crate::node::*;
 use crate::plugin::*;
 use crate::reflect::*;
+use crate::time::*;
 use crate::transform::*;
 use crate::tree::*;
+use ::serde::{Deserialize, Serialize};
 use egui::Ui;
 use quartz_render::prelude::*;
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub struct ComponentCtx<'a> {
     //pub global_transform: &'a Transform,
@@ -16,9 +20,33 @@ pub struct ComponentCtx<'a> {
     pub node_id: &'a NodeId,
     pub transform: &'a mut Transform,
     pub global_transform: &'a Transform,
+    pub time: &'a Time,
+    pub input: &'a InputState,
     pub instance: &'a Instance,
 }
 
+impl<'a> ComponentCtx<'a> {
+    /// Sends an event, see [`Events`].
+    pub fn send<T: Event>(&self, event: T) {
+        self.plugins.events.send(event);
+    }
+
+    /// Sends an event to `node_id`, it bubbles up to every ancestor of the node.
+    pub fn send_to<T: Event>(&self, node_id: impl Into<NodeId>, event: T) {
+        self.plugins.events.send_to(self.tree, node_id, event);
+    }
+
+    pub fn read<T: Event>(&self, reader: &mut EventReader<T>) -> Vec<T> {
+        self.plugins.events.read(reader)
+    }
+
+    /// Reads events sent to this node or any of its descendants, along with the node they
+    /// were sent to.
+    pub fn read_node<T: Event>(&self, reader: &mut EventReader<T>) -> Vec<(NodeId, T)> {
+        self.plugins.events.read_node(reader, self.node_id)
+    }
+}
+
 pub struct ComponentRenderCtx<'a, 'b, 'c, 'd> {
     //pub global_transform: &'a Transform,
     pub viewport_camera: &'a Option<Mat4>,
@@ -74,8 +102,24 @@ pub trait Component: 'static {
 
     fn editor_start(&mut self, plugins: <Self::Plugins as PluginFetch>::Item, ctx: ComponentCtx) {}
 
+    fn pre_update(&mut self, plugins: <Self::Plugins as PluginFetch>::Item, ctx: ComponentCtx) {}
+
+    /// Runs once per fixed step, see [`Stage::FixedUpdate`].
+    fn fixed_update(&mut self, plugins: <Self::Plugins as PluginFetch>::Item, ctx: ComponentCtx) {}
+
     fn update(&mut self, plugins: <Self::Plugins as PluginFetch>::Item, ctx: ComponentCtx) {}
 
+    fn post_update(&mut self, plugins: <Self::Plugins as PluginFetch>::Item, ctx: ComponentCtx) {}
+
+    /// Runs at the end of the update, after global transforms have been updated.
+    fn pre_render(&mut self, plugins: <Self::Plugins as PluginFetch>::Item, ctx: ComponentCtx) {}
+
+    /// Components with lower priorities run first in each [`Stage`], components with equal
+    /// priorities run in tree order.
+    fn priority(&self) -> i32 {
+        0
+    }
+
     fn editor_update(&mut self, plugins: <Self::Plugins as PluginFetch>::Item, ctx: ComponentCtx) {}
 
     fn render(&mut self, plugins: <Self::Plugins as PluginFetch>::Item, ctx: ComponentRenderCtx) {}
@@ -117,10 +161,16 @@ impl ToPod for Box<dyn ComponentPod> {
 pub trait ComponentPod: Reflect + Any {
     fn short_name(&self) -> &str;
     fn long_name(&self) -> &str;
+    fn version(&self) -> u32;
     fn inspector_ui(&mut self, plugins: &Plugins, ctx: ComponentCtx, ui: &mut Ui);
     fn start(&mut self, plugins: &Plugins, ctx: ComponentCtx);
     fn editor_start(&mut self, plugins: &Plugins, ctx: ComponentCtx);
+    fn pre_update(&mut self, plugins: &Plugins, ctx: ComponentCtx);
+    fn fixed_update(&mut self, plugins: &Plugins, ctx: ComponentCtx);
     fn update(&mut self, plugins: &Plugins, ctx: ComponentCtx);
+    fn post_update(&mut self, plugins: &Plugins, ctx: ComponentCtx);
+    fn pre_render(&mut self, plugins: &Plugins, ctx: ComponentCtx);
+    fn priority(&self) -> i32;
     fn editor_update(&mut self, plugins: &Plugins, ctx: ComponentCtx);
     fn render(&mut self, plugins: &Plugins, ctx: ComponentRenderCtx);
     fn viewport_render(&mut self, plugins: &Plugins, ctx: ComponentRenderCtx);
@@ -140,6 +190,10 @@ impl<T: Component + Reflect> ComponentPod for T {
         T::long_name_const()
     }
 
+    fn version(&self) -> u32 {
+        T::version_const()
+    }
+
     fn inspector_ui(&mut self, plugins: &Plugins, ctx: ComponentCtx, ui: &mut Ui) {
         T::Plugins::fetch(plugins, |plugins| {
             Component::inspector_ui(self, plugins, ctx, ui);
@@ -158,12 +212,40 @@ impl<T: Component + Reflect> ComponentPod for T {
         });
     }
 
+    fn pre_update(&mut self, plugins: &Plugins, ctx: ComponentCtx) {
+        T::Plugins::fetch(plugins, |plugins| {
+            Component::pre_update(self, plugins, ctx);
+        });
+    }
+
+    fn fixed_update(&mut self, plugins: &Plugins, ctx: ComponentCtx) {
+        T::Plugins::fetch(plugins, |plugins| {
+            Component::fixed_update(self, plugins, ctx);
+        });
+    }
+
     fn update(&mut self, plugins: &Plugins, ctx: ComponentCtx) {
         T::Plugins::fetch(plugins, |plugins| {
             Component::update(self, plugins, ctx);
         });
     }
 
+    fn post_update(&mut self, plugins: &Plugins, ctx: ComponentCtx) {
+        T::Plugins::fetch(plugins, |plugins| {
+            Component::post_update(self, plugins, ctx);
+        });
+    }
+
+    fn pre_render(&mut self, plugins: &Plugins, ctx: ComponentCtx) {
+        T::Plugins::fetch(plugins, |plugins| {
+            Component::pre_render(self, plugins, ctx);
+        });
+    }
+
+    fn priority(&self) -> i32 {
+        Component::priority(self)
+    }
+
     fn editor_update(&mut self, plugins: &Plugins, ctx: ComponentCtx) {
         T::Plugins::fetch(plugins, |plugins| {
             Component::editor_update(self, plugins, ctx);
@@ -207,9 +289,25 @@ impl<T: Component + Reflect> ComponentPod for T {
     }
 }
 
+/// Serialized data of a component that couldn't be loaded, either because it isn't
+/// registered or because its data couldn't be read.
+///
+/// It's kept on the node and saved with the scene, so nothing is lost.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OpaqueComponent {
+    /// Version the data was saved with.
+    pub version: u32,
+    pub data: Value,
+}
+
+/// Migrates the data of a component from one version to the next.
+pub type Migration = fn(Value) -> Value;
+
 pub struct Components {
     pub inits_short_name: HashMap<&'static str, Box<fn(&Plugins) -> Box<dyn ComponentPod>>>,
     pub inits_long_name: HashMap<&'static str, Box<fn(&Plugins) -> Box<dyn ComponentPod>>>,
+    pub versions: HashMap<&'static str, u32>,
+    pub migrations: HashMap<&'static str, BTreeMap<u32, Migration>>,
 }
 
 fn init<C: InitComponent + Reflect>(plugins: &Plugins) -> Box<dyn ComponentPod> {
@@ -221,6 +319,8 @@ impl Components {
         Self {
             inits_short_name: HashMap::new(),
             inits_long_name: HashMap::new(),
+            versions: HashMap::new(),
+            migrations: HashMap::new(),
         }
     }
 
@@ -228,11 +328,18 @@ impl Components {
         self.inits_short_name.keys().cloned().collect()
     }
 
-    pub fn register_component<C: InitComponent + Reflect>(&mut self) {
+    pub fn register_component<C: InitComponent + Reflect>(&mut self) -> ComponentRegistration<'_> {
         self.inits_short_name
             .insert(C::short_name_const(), Box::new(init::<C>));
         self.inits_long_name
             .insert(C::long_name_const(), Box::new(init::<C>));
+        self.versions
+            .insert(C::long_name_const(), C::version_const());
+
+        ComponentRegistration {
+            name: C::long_name_const(),
+            components: self,
+        }
     }
 
     pub fn init_short_name(
@@ -254,4 +361,48 @@ impl Components {
             .get(component)
             .map(|init| init(plugins))
     }
+
+    /// Current version of a component by long name, [`None`] if it isn't registered.
+    pub fn version(&self, component: &str) -> Option<u32> {
+        self.versions.get(component).cloned()
+    }
+
+    /// Runs every migration of `component` from `version` up to its current version.
+    pub fn migrate(&self, component: &str, version: u32, mut data: Value) -> Value {
+        let current = self.version(component).unwrap_or(version);
+
+        let migrations = self.migrations.get(component).filter(|_| version < current);
+
+        if let Some(migrations) = migrations {
+            for (_, migration) in migrations.range(version..current) {
+                data = migration(data);
+            }
+        }
+
+        data
+    }
+}
+
+/// Returned by [`Components::register_component`], used to add migrations.
+pub struct ComponentRegistration<'a> {
+    name: &'static str,
+    components: &'a mut Components,
+}
+
+impl<'a> ComponentRegistration<'a> {
+    /// Adds a migration from data saved with `version` to `version + 1`, eg.
+    /// `types.register_component::<Light>().migration(0, light_v0_to_v1)`.
+    ///
+    /// Migrations run in order, so data saved with version 0 of a component at version 2 goes
+    /// through the migrations of both version 0 and 1. Fields renamed with
+    /// `#[reflect(rename = "old")]` or added with `#[reflect(default)]` don't need one.
+    pub fn migration(self, version: u32, migration: Migration) -> Self {
+        self.components
+            .migrations
+            .entry(self.name)
+            .or_insert_with(BTreeMap::new)
+            .insert(version, migration);
+
+        self
+    }
 }
@@ -0,0 +1,320 @@
+use crate::node::*;
+use crate::tree::*;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, RwLock};
+
+pub trait Event: Clone + Send + Sync + 'static {}
+
+impl<T: Clone + Send + Sync + 'static> Event for T {}
+
+struct EventInstance<T> {
+    id: usize,
+    /// The node the event was sent to followed by its ancestors, empty if it wasn't sent to
+    /// a node.
+    path: Vec<NodeId>,
+    event: T,
+}
+
+/// Queue of events of type `T`.
+///
+/// Events are kept for two frames, so every [`EventReader`] reading once per frame sees
+/// each event exactly once, regardless of whether it runs before or after the sender.
+pub struct Events<T> {
+    previous: Vec<EventInstance<T>>,
+    current: Vec<EventInstance<T>>,
+    count: usize,
+}
+
+impl<T: Event> Default for Events<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Event> Events<T> {
+    pub fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            count: 0,
+        }
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.push(Vec::new(), event);
+    }
+
+    /// Sends an event to `node_id`, it bubbles up to every ancestor of the node.
+    pub fn send_to(&mut self, tree: &Tree, node_id: impl Into<NodeId>, event: T) {
+        let node_id = node_id.into();
+        let path = std::iter::once(node_id)
+            .chain(tree.ancestors(node_id))
+            .collect();
+
+        self.push(path, event);
+    }
+
+    fn push(&mut self, path: Vec<NodeId>, event: T) {
+        self.current.push(EventInstance {
+            id: self.count,
+            path,
+            event,
+        });
+        self.count += 1;
+    }
+
+    /// Drops the events of the previous frame.
+    pub fn update(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// Id of the oldest event still kept.
+    fn oldest(&self) -> usize {
+        self.count - self.previous.len() - self.current.len()
+    }
+
+    fn unread(&self, last: usize) -> impl Iterator<Item = &EventInstance<T>> {
+        self.previous
+            .iter()
+            .chain(self.current.iter())
+            .filter(move |instance| instance.id >= last)
+    }
+}
+
+/// Reads [`Events`] sent since the last time it read, keep one around between frames.
+///
+/// [`EventReader::read`] and every node passed to [`EventReader::read_node`] keep track of
+/// what they've read separately, so one reader can read events for several nodes.
+pub struct EventReader<T> {
+    last: usize,
+    nodes: HashMap<NodeId, usize>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EventReader<T> {
+    fn default() -> Self {
+        Self {
+            last: 0,
+            nodes: HashMap::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Event> EventReader<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every unread event, including ones sent to nodes.
+    pub fn read(&mut self, events: &Events<T>) -> Vec<T> {
+        let read = events
+            .unread(self.last)
+            .map(|instance| instance.event.clone())
+            .collect();
+
+        self.last = events.count;
+
+        read
+    }
+
+    /// Unread events sent to `node_id` or any of its descendants, along with the node they
+    /// were sent to.
+    pub fn read_node(
+        &mut self,
+        events: &Events<T>,
+        node_id: impl Into<NodeId>,
+    ) -> Vec<(NodeId, T)> {
+        let node_id = node_id.into();
+        let last = self.nodes.get(&node_id).copied().unwrap_or(0);
+
+        let read = events
+            .unread(last)
+            .filter(|instance| instance.path.contains(&node_id))
+            .map(|instance| (instance.path[0], instance.event.clone()))
+            .collect();
+
+        // nodes that haven't read any of the kept events are the same as new ones
+        let oldest = events.oldest();
+        self.nodes.retain(|_, last| *last > oldest);
+        self.nodes.insert(node_id, events.count);
+
+        read
+    }
+}
+
+trait AnyEvents: Send + Sync {
+    fn update(&self);
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Event> AnyEvents for Mutex<Events<T>> {
+    fn update(&self) {
+        self.lock().unwrap().update();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The [`Events`] of every event type.
+///
+/// Event types are registered the first time they're used, registering them up front with
+/// [`EventBus::register_event`] is optional.
+pub struct EventBus {
+    events: RwLock<HashMap<TypeId, Arc<dyn AnyEvents>>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self {
+            events: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn register_event<T: Event>(&self) {
+        self.events
+            .write()
+            .unwrap()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(Mutex::new(Events::<T>::new())));
+    }
+
+    pub fn is_registered<T: Event>(&self) -> bool {
+        self.events.read().unwrap().contains_key(&TypeId::of::<T>())
+    }
+
+    /// Calls `f` with the events of type `T`, registering `T` if it isn't already.
+    pub fn get<T: Event, O>(&self, f: impl FnOnce(&mut Events<T>) -> O) -> O {
+        let events = self.events.read().unwrap().get(&TypeId::of::<T>()).cloned();
+
+        let events = match events {
+            Some(events) => events,
+            None => {
+                self.register_event::<T>();
+                self.events.read().unwrap()[&TypeId::of::<T>()].clone()
+            }
+        };
+
+        let events = events.as_any().downcast_ref::<Mutex<Events<T>>>().unwrap();
+        let mut events = events.lock().unwrap();
+
+        f(&mut events)
+    }
+
+    pub fn send<T: Event>(&self, event: T) {
+        self.get(|events| events.send(event));
+    }
+
+    pub fn send_to<T: Event>(&self, tree: &Tree, node_id: impl Into<NodeId>, event: T) {
+        self.get(|events| events.send_to(tree, node_id, event));
+    }
+
+    pub fn read<T: Event>(&self, reader: &mut EventReader<T>) -> Vec<T> {
+        self.get(|events| reader.read(events))
+    }
+
+    pub fn read_node<T: Event>(
+        &self,
+        reader: &mut EventReader<T>,
+        node_id: impl Into<NodeId>,
+    ) -> Vec<(NodeId, T)> {
+        self.get(|events| reader.read_node(events, node_id))
+    }
+
+    /// Moves every event type to the next frame, called once at the end of every update.
+    pub fn update(&mut self) {
+        for events in self.events.get_mut().unwrap().values() {
+            events.update();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Hit(u32);
+
+    #[test]
+    fn reads_each_event_once() {
+        let mut events = Events::default();
+        let mut before = EventReader::new();
+        assert!(before.read(&events).is_empty());
+
+        events.send(Hit(1));
+        let mut after = EventReader::new();
+
+        assert_eq!(before.read(&events), vec![Hit(1)]);
+        assert_eq!(after.read(&events), vec![Hit(1)]);
+
+        // the next frame, one reader runs before the sender and one after
+        events.update();
+        assert!(before.read(&events).is_empty());
+        events.send(Hit(2));
+        assert_eq!(after.read(&events), vec![Hit(2)]);
+
+        events.update();
+        assert_eq!(before.read(&events), vec![Hit(2)]);
+        assert!(after.read(&events).is_empty());
+    }
+
+    #[test]
+    fn drops_events_after_two_updates() {
+        let mut events = Events::default();
+        events.send(Hit(1));
+
+        events.update();
+        assert_eq!(EventReader::new().read(&events), vec![Hit(1)]);
+
+        events.update();
+        assert!(EventReader::new().read(&events).is_empty());
+    }
+
+    #[test]
+    fn bubbles_up_to_ancestors() {
+        let mut tree = Tree::new();
+        let parent = tree.spawn();
+        let child = tree.spawn_child(parent).unwrap();
+        let other = tree.spawn();
+
+        let mut events = Events::default();
+        events.send_to(&tree, child, Hit(1));
+        events.send_to(&tree, other, Hit(2));
+
+        let mut reader = EventReader::new();
+        assert_eq!(reader.read_node(&events, parent), vec![(child, Hit(1))]);
+        assert_eq!(reader.read_node(&events, child), vec![(child, Hit(1))]);
+        assert!(reader.read_node(&events, parent).is_empty());
+
+        // reading some nodes doesn't mark events as read for other nodes
+        assert_eq!(reader.read_node(&events, other), vec![(other, Hit(2))]);
+        assert_eq!(reader.read(&events), vec![Hit(1), Hit(2)]);
+    }
+
+    #[test]
+    fn registers_events_on_use() {
+        let mut bus = EventBus::default();
+        let mut reader = EventReader::<Hit>::new();
+        assert!(!bus.is_registered::<Hit>());
+
+        bus.send(Hit(1));
+        assert!(bus.is_registered::<Hit>());
+        assert_eq!(bus.read(&mut reader), vec![Hit(1)]);
+
+        bus.update();
+        bus.update();
+        bus.send(Hit(2));
+        assert_eq!(bus.read(&mut reader), vec![Hit(2)]);
+    }
+}
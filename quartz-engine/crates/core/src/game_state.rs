@@ -1,8 +1,10 @@
 use crate::component::*;
+use crate::input::*;
 use crate::plugin::*;
 use crate::render::prelude::*;
 use crate::render::wgpu;
 use crate::scene::*;
+use crate::time::*;
 use crate::tree::*;
 use serde::Serialize;
 
@@ -10,7 +12,11 @@ pub struct GameState {
     pub tree: Tree,
     pub plugins: Box<Plugins>,
     pub components: Box<Components>,
+    pub time: Time,
+    pub input: InputState,
     pub depth_texture: Texture2d<format::Depth32Float>,
+    /// Main pass color target when multisampling, resolved into the frame.
+    pub msaa_texture: Option<Texture2d<format::TargetFormat>>,
 }
 
 impl GameState {
@@ -29,7 +35,10 @@ impl GameState {
             tree,
             plugins,
             components,
+            time: Time::new(),
+            input: InputState::new(),
             depth_texture,
+            msaa_texture: None,
         }
     }
 
@@ -37,112 +46,244 @@ impl GameState {
         let plugin_ctx = PluginCtx {
             tree: &mut self.tree,
             plugins: &self.plugins,
+            time: &self.time,
+            input: &self.input,
             instance,
             target_format,
         };
 
         self.plugins.start(plugin_ctx);
-        self.tree.start(&self.plugins, instance);
+        self.tree
+            .start(&self.plugins, &self.time, &self.input, instance);
     }
 
     pub fn editor_start(&mut self, target_format: format::TargetFormat, instance: &Instance) {
         let plugin_ctx = PluginCtx {
             tree: &mut self.tree,
             plugins: &self.plugins,
+            time: &self.time,
+            input: &self.input,
             instance,
             target_format,
         };
 
         self.plugins.editor_start(plugin_ctx);
-        self.tree.editor_start(&self.plugins, instance);
+        self.tree
+            .editor_start(&self.plugins, &self.time, &self.input, instance);
     }
 
-    pub fn update(&mut self, target_format: format::TargetFormat, instance: &Instance) {
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        target_format: format::TargetFormat,
+        instance: &Instance,
+    ) {
+        self.time.advance(delta_time);
+        self.input.pre_update();
         self.tree.update_transforms();
 
-        let plugin_ctx = PluginCtx {
-            tree: &mut self.tree,
-            plugins: &self.plugins,
-            instance,
-            target_format,
-        };
+        self.run_stage(Stage::PreUpdate, target_format, instance);
 
-        self.plugins.update(plugin_ctx);
+        for _ in 0..self.time.fixed_steps() {
+            self.run_stage(Stage::FixedUpdate, target_format, instance);
+        }
 
-        self.tree.update(&self.plugins, instance);
+        self.run_stage(Stage::Update, target_format, instance);
+        self.run_stage(Stage::PostUpdate, target_format, instance);
 
         let nodes = std::mem::replace(&mut self.tree.despawn, Vec::new());
 
         for node_id in &nodes {
             self.tree
-                .despawn_recursive(node_id, &self.plugins, instance);
+                .despawn_recursive(node_id, &self.plugins, &self.time, &self.input, instance);
         }
 
         for node_id in nodes {
             self.tree.remove_recursive(node_id);
         }
 
+        self.tree
+            .init_prefab_components(&self.components, &self.plugins);
+
         let nodes = std::mem::replace(&mut self.tree.added, Vec::new());
 
         for node_id in nodes {
             if let Some(mut node) = self.tree.get_node(node_id) {
-                node.start(&self.plugins, &node_id, &mut self.tree, instance);
+                node.start(
+                    &self.plugins,
+                    &node_id,
+                    &mut self.tree,
+                    &self.time,
+                    &self.input,
+                    instance,
+                );
             }
         }
+
+        self.tree.update_transforms();
+        self.run_stage(Stage::PreRender, target_format, instance);
+
+        self.plugins.assets.free_unused();
+        self.plugins.events.update();
+        self.input.post_update();
+    }
+
+    fn run_stage(
+        &mut self,
+        stage: Stage,
+        target_format: format::TargetFormat,
+        instance: &Instance,
+    ) {
+        let plugin_ctx = PluginCtx {
+            tree: &mut self.tree,
+            plugins: &self.plugins,
+            time: &self.time,
+            input: &self.input,
+            instance,
+            target_format,
+        };
+
+        self.plugins.run_stage(stage, plugin_ctx);
+
+        self.tree
+            .update(stage, &self.plugins, &self.time, &self.input, instance);
     }
 
-    pub fn editor_update(&mut self, target_format: format::TargetFormat, instance: &Instance) {
+    pub fn editor_update(
+        &mut self,
+        delta_time: f32,
+        target_format: format::TargetFormat,
+        instance: &Instance,
+    ) {
+        self.time.advance(delta_time);
+        self.input.pre_update();
         self.tree.update_transforms();
 
         let plugin_ctx = PluginCtx {
             tree: &mut self.tree,
             plugins: &self.plugins,
+            time: &self.time,
+            input: &self.input,
             instance,
             target_format,
         };
 
         self.plugins.editor_update(plugin_ctx);
 
-        self.tree.editor_update(&self.plugins, instance);
+        self.tree
+            .editor_update(&self.plugins, &self.time, &self.input, instance);
 
         let nodes = std::mem::replace(&mut self.tree.despawn, Vec::new());
 
         for node_id in &nodes {
             self.tree
-                .despawn_recursive(node_id, &self.plugins, instance);
+                .despawn_recursive(node_id, &self.plugins, &self.time, &self.input, instance);
         }
 
         for node_id in nodes {
             self.tree.remove_recursive(node_id);
         }
 
+        self.tree
+            .init_prefab_components(&self.components, &self.plugins);
+
         let nodes = std::mem::replace(&mut self.tree.added, Vec::new());
 
         for node_id in nodes {
             if let Some(mut node) = self.tree.get_node(node_id) {
-                node.editor_start(&self.plugins, &node_id, &mut self.tree, instance);
+                node.editor_start(
+                    &self.plugins,
+                    &node_id,
+                    &mut self.tree,
+                    &self.time,
+                    &self.input,
+                    instance,
+                );
             }
         }
+
+        self.plugins.assets.free_unused();
+        self.plugins.events.update();
+        self.input.post_update();
     }
 
-    pub fn resize_depth_texture(&mut self, width: u32, height: u32, instance: &Instance) {
+    pub fn resize_depth_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+        instance: &Instance,
+    ) {
         if self.depth_texture.dimensions.width != width
             || self.depth_texture.dimensions.height != height
+            || self.depth_texture.view().sample_count() != sample_count
         {
             self.depth_texture = Texture::new(
-                &TextureDescriptor::default_settings(D2::new(width, height)),
+                &TextureDescriptor::default_settings(D2::new(width, height))
+                    .with_sample_count(sample_count),
                 instance,
             );
         }
     }
 
+    /// Resizes the depth and msaa textures to match `target` and the sample count requested
+    /// by [`Plugins::sample_count`].
+    fn prepare_main_pass(
+        &mut self,
+        target: &TextureView<format::TargetFormat>,
+        instance: &Instance,
+    ) {
+        let sample_count = self.plugins.sample_count();
+
+        self.resize_depth_texture(target.width(), target.height(), sample_count, instance);
+
+        if sample_count == 1 {
+            self.msaa_texture = None;
+            return;
+        }
+
+        let outdated = match &self.msaa_texture {
+            Some(texture) => {
+                let view = texture.view();
+
+                view.width() != target.width()
+                    || view.height() != target.height()
+                    || view.sample_count() != sample_count
+                    || view.format().0 != target.format().0
+            }
+            None => true,
+        };
+
+        if outdated {
+            self.msaa_texture = Some(Texture::new(
+                &TextureDescriptor::new(D2::new(target.width(), target.height()), target.format())
+                    .with_sample_count(sample_count),
+                instance,
+            ));
+        }
+    }
+
+    /// Color attachment of the main pass, rendering into the msaa texture if there is one.
+    fn main_color_attachment<'a>(
+        &self,
+        target: TextureView<'a, format::TargetFormat>,
+    ) -> ColorAttachment<'a, format::TargetFormat> {
+        match &self.msaa_texture {
+            Some(texture) => ColorAttachment {
+                resolve_target: Some(target),
+                ..ColorAttachment::default_settings(texture.view())
+            },
+            None => ColorAttachment::default_settings(target),
+        }
+    }
+
     pub fn render(
         &mut self,
         target: TextureView<format::TargetFormat>,
         render_ctx: &mut RenderCtx,
         instance: &Instance,
     ) {
-        self.resize_depth_texture(target.width(), target.height(), instance);
+        self.prepare_main_pass(&target, instance);
 
         let plugin_ctx = PluginRenderCtx {
             tree: &mut self.tree,
@@ -150,19 +291,28 @@ impl GameState {
             instance: instance,
             render_ctx,
             target: &target,
+            viewport_camera: &None,
         };
 
         self.plugins.render(plugin_ctx);
 
         let desc = RenderPassDescriptor {
             label: Some("Main game render pass".to_string()),
-            color_attachments: ColorAttachment::default_settings(target),
+            color_attachments: self.main_color_attachment(target),
             depth_attachment: DepthAttachment::default_settings(self.depth_texture.view()),
         };
         let mut render_pass = render_ctx.render_pass_empty(&desc);
 
         self.tree
             .render(&self.plugins, &None, instance, &mut render_pass);
+
+        self.plugins.draw(PluginDrawCtx {
+            tree: &self.tree,
+            plugins: &self.plugins,
+            instance,
+            viewport_camera: &None,
+            render_pass: &mut render_pass,
+        });
     }
 
     pub fn viewport_render(
@@ -172,7 +322,7 @@ impl GameState {
         render_ctx: &mut RenderCtx,
         instance: &Instance,
     ) {
-        self.resize_depth_texture(target.width(), target.height(), instance);
+        self.prepare_main_pass(&target, instance);
 
         let plugin_ctx = PluginRenderCtx {
             tree: &mut self.tree,
@@ -180,19 +330,28 @@ impl GameState {
             instance: instance,
             render_ctx,
             target: &target,
+            viewport_camera: camera,
         };
 
         self.plugins.viewport_render(plugin_ctx);
 
         let desc = RenderPassDescriptor {
             label: Some("Viewport render pass".to_string()),
-            color_attachments: ColorAttachment::default_settings(target),
+            color_attachments: self.main_color_attachment(target),
             depth_attachment: DepthAttachment::default_settings(self.depth_texture.view()),
         };
         let mut render_pass = render_ctx.render_pass_empty(&desc);
 
         self.tree
             .viewport_render(&self.plugins, camera, instance, &mut render_pass);
+
+        self.plugins.draw(PluginDrawCtx {
+            tree: &self.tree,
+            plugins: &self.plugins,
+            instance,
+            viewport_camera: camera,
+            render_pass: &mut render_pass,
+        });
     }
 
     pub fn viewport_pick_render(
@@ -235,4 +394,11 @@ impl GameState {
         }
         .serialize(serializer)
     }
+
+    pub fn save_scene(&self, format: SceneFormat) -> Result<Vec<u8>, SceneError> {
+        format.serialize(&Scene {
+            tree: &self.tree,
+            plugins: &self.plugins,
+        })
+    }
 }
@@ -0,0 +1,87 @@
+pub use quartz_framework::input::{Input, InputEvent, Key, MouseButton};
+use quartz_render::prelude::*;
+use winit::event::{ElementState, MouseScrollDelta, WindowEvent};
+
+#[derive(Default)]
+pub struct MouseInput {
+    pub buttons: Input<MouseButton>,
+    pub prev_position: Vec2,
+    pub position: Vec2,
+    pub delta: Vec2,
+    pub scroll: Vec2,
+}
+
+impl MouseInput {
+    pub fn pre_update(&mut self) {
+        self.delta = self.position - self.prev_position;
+    }
+
+    /// Moves the mouse to `position` without producing a delta, e.g. when input starts being
+    /// forwarded from somewhere else.
+    pub fn reset_position(&mut self, position: Vec2) {
+        self.position = position;
+        self.prev_position = position;
+        self.delta = Vec2::ZERO;
+    }
+
+    pub fn post_update(&mut self) {
+        self.prev_position = self.position;
+        self.scroll = Vec2::ZERO;
+        self.buttons.update();
+    }
+}
+
+/// Keyboard and mouse state for the current frame.
+#[derive(Default)]
+pub struct InputState {
+    pub keyboard: Input<Key>,
+    pub mouse: MouseInput,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                if let Some(key) = input.virtual_keycode {
+                    match input.state {
+                        ElementState::Pressed => self.keyboard.press(key),
+                        ElementState::Released => self.keyboard.release(key),
+                    }
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => self.mouse.buttons.press(*button),
+                ElementState::Released => self.mouse.buttons.release(*button),
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse.position = Vec2::new(position.x as f32, position.y as f32);
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.mouse.scroll += match delta {
+                    MouseScrollDelta::LineDelta(x, y) => Vec2::new(*x, *y),
+                    MouseScrollDelta::PixelDelta(pos) => Vec2::new(pos.x as f32, pos.y as f32),
+                };
+            }
+            WindowEvent::Focused(false) => self.release_all(),
+            _ => {}
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.keyboard.release_all();
+        self.mouse.buttons.release_all();
+    }
+
+    pub fn pre_update(&mut self) {
+        self.mouse.pre_update();
+    }
+
+    pub fn post_update(&mut self) {
+        self.keyboard.update();
+        self.mouse.post_update();
+    }
+}
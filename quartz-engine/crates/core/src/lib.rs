@@ -1,10 +1,16 @@
+pub mod asset;
 pub mod component;
+pub mod event;
+pub mod input;
 pub mod inspect;
 pub mod macros;
 pub mod node;
+pub mod pipeline;
 pub mod plugin;
+pub mod prefab;
 pub mod reflect;
 pub mod scene;
+pub mod time;
 pub mod transform;
 pub mod tree;
 pub mod types;
@@ -16,6 +22,12 @@ pub mod editor_ui;
 #[cfg(feature = "editor_bridge")]
 pub mod game_state;
 
+// lets `#[derive(Reflect)]`, which refers to `quartz_engine::core`, be used in tests
+#[cfg(test)]
+mod quartz_engine {
+    pub use crate as core;
+}
+
 pub use egui;
 pub use erased_serde;
 pub use quartz_render as render;
@@ -28,12 +40,23 @@ pub mod editor_bridge {
 }
 
 pub mod prelude {
-    pub use crate::component::{Component, ComponentCtx, ComponentPickCtx, ComponentRenderCtx};
+    pub use crate::asset::{AssetLoader, Assets, Handle, AssetError, LoadCtx};
+    pub use crate::component::{
+        Component, ComponentCtx, ComponentPickCtx, ComponentRegistration, ComponentRenderCtx,
+    };
+    pub use crate::event::{Event, EventReader, Events};
+    pub use crate::input::{InputState, Key, MouseButton};
     pub use crate::inspect::Inspect;
     pub use crate::node::*;
-    pub use crate::plugin::{Plugin, PluginCtx, PluginInitCtx, PluginRenderCtx, Plugins};
+    pub use crate::pipeline::{Pipelines, SharedPipeline};
+    pub use crate::plugin::{
+        Plugin, PluginCtx, PluginDrawCtx, PluginInitCtx, PluginRegistration, PluginRenderCtx,
+        Plugins, Stage,
+    };
+    pub use crate::prefab::Prefab;
     pub use crate::reflect::Reflect;
     pub use crate::render::prelude::*;
+    pub use crate::time::Time;
     pub use crate::transform::*;
     pub use crate::tree::Tree;
     pub use crate::types::*;
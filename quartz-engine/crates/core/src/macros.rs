@@ -21,3 +21,35 @@ macro_rules! register_types {
         }
     };
 }
+
+/// Implements [`Reflect`](crate::reflect::Reflect) for types that are read and written whole
+/// through serde, eg. `reflect_value!(Msaa, Projection);`.
+#[macro_export]
+macro_rules! reflect_value {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl $crate::reflect::Reflect for $ty {
+                fn reflect(&mut self, deserializer: &mut dyn $crate::erased_serde::Deserializer) {
+                    self.try_reflect(deserializer).unwrap();
+                }
+
+                fn try_reflect(
+                    &mut self,
+                    deserializer: &mut dyn $crate::erased_serde::Deserializer,
+                ) -> Result<(), $crate::erased_serde::Error> {
+                    *self = $crate::erased_serde::deserialize(deserializer)?;
+
+                    Ok(())
+                }
+
+                fn as_serialize(&self) -> &dyn $crate::erased_serde::Serialize {
+                    self
+                }
+
+                fn short_name_const() -> &'static str {
+                    stringify!($ty)
+                }
+            }
+        )*
+    };
+}
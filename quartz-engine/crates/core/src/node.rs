@@ -1,6 +1,11 @@
 use crate::component::*;
+use crate::input::InputState;
 use crate::inspect::*;
 use crate::plugin::*;
+use crate::prefab::PrefabLink;
+use crate::reflect::serde::{ComponentDeserializer, ReflectDeserializer};
+use crate::scene::{SceneError, SceneFormat};
+use crate::time::*;
 use crate::transform::*;
 use crate::tree::*;
 use egui::*;
@@ -9,7 +14,8 @@ use quartz_render::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
     any::TypeId,
-    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, Mutex, MutexGuard},
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard, Mutex, MutexGuard},
 };
 
 #[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -27,9 +33,61 @@ impl Into<Option<NodeId>> for &NodeId {
     }
 }
 
+/// The nodes having each component, keyed by the component's long name.
+///
+/// Shared by a [`Tree`] and the [`NodeComponents`] of its nodes, which keep it up to date.
+#[derive(Clone, Default)]
+pub(crate) struct ComponentIndex {
+    nodes: Arc<Mutex<HashMap<String, HashSet<NodeId>>>>,
+}
+
+impl ComponentIndex {
+    pub fn insert(&self, name: &str, node_id: NodeId) {
+        self.nodes
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_default()
+            .insert(node_id);
+    }
+
+    pub fn remove(&self, name: &str, node_id: NodeId) {
+        if let Some(nodes) = self.nodes.lock().unwrap().get_mut(name) {
+            nodes.remove(&node_id);
+        }
+    }
+
+    pub fn remove_node(&self, node_id: NodeId) {
+        for nodes in self.nodes.lock().unwrap().values_mut() {
+            nodes.remove(&node_id);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Vec<NodeId> {
+        match self.nodes.lock().unwrap().get(name) {
+            Some(nodes) => nodes.iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// The components of a node serialized in a [`SceneFormat`], see
+/// [`NodeComponents::snapshot`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentsSnapshot {
+    pub format: SceneFormat,
+    /// Serialized components by long name.
+    pub components: LinkedHashMap<String, Vec<u8>>,
+    pub opaque: LinkedHashMap<String, OpaqueComponent>,
+}
+
 pub struct NodeComponents {
     pub(crate) add: Mutex<Vec<Box<dyn ComponentPod>>>,
     pub(crate) components: LinkedHashMap<String, RwLock<Box<dyn ComponentPod>>>,
+    /// Components that couldn't be loaded, by long name.
+    pub(crate) opaque: LinkedHashMap<String, OpaqueComponent>,
+    /// The node and index of the tree the components are in.
+    pub(crate) index: Option<(NodeId, ComponentIndex)>,
 }
 
 impl NodeComponents {
@@ -37,9 +95,20 @@ impl NodeComponents {
         Self {
             add: Default::default(),
             components: LinkedHashMap::new(),
+            opaque: LinkedHashMap::new(),
+            index: None,
         }
     }
 
+    /// Adds the components to `index`, and keeps it up to date as they're added and removed.
+    pub(crate) fn set_index(&mut self, node_id: NodeId, index: ComponentIndex) {
+        for name in self.components.keys() {
+            index.insert(name, node_id);
+        }
+
+        self.index = Some((node_id, index));
+    }
+
     /// Adds a component to a queue, and is added at the end of the component method.
     /// eg. update
     pub fn add(&self, to_pod: impl ToPod) {
@@ -48,10 +117,33 @@ impl NodeComponents {
 
     pub fn add_direct(&mut self, to_pod: impl ToPod) {
         let component = to_pod.to_pod();
+
+        if let Some((node_id, index)) = &self.index {
+            index.insert(component.long_name(), *node_id);
+        }
+
+        self.opaque.remove(component.long_name());
         self.components
             .insert(component.long_name().to_string(), RwLock::new(component));
     }
 
+    /// Removes the component with the long name `name`.
+    pub fn remove(&mut self, name: &str) {
+        if let Some((node_id, index)) = &self.index {
+            index.remove(name, *node_id);
+        }
+
+        self.components.remove(name);
+        self.opaque.remove(name);
+    }
+
+    /// Components kept as serialized data because they couldn't be loaded, by long name.
+    pub fn opaque(&self) -> impl Iterator<Item = (&str, &OpaqueComponent)> {
+        self.opaque
+            .iter()
+            .map(|(name, component)| (name.as_str(), component))
+    }
+
     pub fn get<T: ComponentPod>(&self) -> Option<RwLockReadGuard<Box<T>>> {
         let component = self.components.get(T::long_name_const())?.read().unwrap();
 
@@ -99,9 +191,82 @@ impl NodeComponents {
         self.components.values().map(|c| c.write().unwrap())
     }
 
+    /// Serializes the components, so they can be restored later with
+    /// [`NodeComponents::restore`], eg. to undo an edit.
+    pub fn snapshot(&self, format: SceneFormat) -> Result<ComponentsSnapshot, SceneError> {
+        let mut components = LinkedHashMap::new();
+
+        for (name, component) in &self.components {
+            let component = component.read().unwrap();
+            components.insert(name.clone(), format.serialize(&component.as_serialize())?);
+        }
+
+        Ok(ComponentsSnapshot {
+            format,
+            components,
+            opaque: self.opaque.clone(),
+        })
+    }
+
+    /// Sets the components to `snapshot`.
+    ///
+    /// Components still on the node are loaded in place, so state that isn't serialized is
+    /// kept, and missing ones are created. Components that fail to load are logged and
+    /// skipped.
+    pub fn restore(
+        &mut self,
+        snapshot: &ComponentsSnapshot,
+        components: &Components,
+        plugins: &Plugins,
+    ) {
+        let format = snapshot.format;
+        let names: Vec<String> = self.components.keys().cloned().collect();
+
+        for name in names {
+            if !snapshot.components.contains_key(&name) {
+                self.remove(&name);
+            }
+        }
+
+        for (name, data) in &snapshot.components {
+            let result = match self.components.get(name) {
+                Some(component) => {
+                    let mut component = component.write().unwrap();
+                    let seed = ReflectDeserializer {
+                        reflect: &mut **component,
+                    };
+
+                    format.deserialize_seed(data, seed)
+                }
+                None => {
+                    let seed = ComponentDeserializer {
+                        name,
+                        plugins,
+                        components,
+                    };
+
+                    format
+                        .deserialize_seed(data, seed)
+                        .map(|component| self.add_direct(component))
+                }
+            };
+
+            if let Err(err) = result {
+                log::warn!("failed to restore component {}: {}", name, err);
+            }
+
+            // keep the order of the snapshot
+            self.components.get_refresh(name);
+        }
+
+        self.opaque = snapshot.opaque.clone();
+    }
+
     pub fn update(&mut self) {
-        for component in self.add.lock().unwrap().drain(..) {
-            self.components.insert(component.long_name().to_string(), RwLock::new(component));
+        let added = std::mem::take(&mut *self.add.lock().unwrap());
+
+        for component in added {
+            self.add_direct(component);
         }
     }
 }
@@ -111,6 +276,8 @@ pub struct Node {
     pub transform: Transform,
     pub(crate) global_transform: Transform,
     pub(crate) components: NodeComponents,
+    /// The prefab node this node was instantiated from, if any.
+    pub prefab: Option<PrefabLink>,
 }
 
 impl Node {
@@ -120,6 +287,7 @@ impl Node {
             transform: Transform::IDENTITY,
             global_transform: Transform::IDENTITY,
             components: NodeComponents::new(),
+            prefab: None,
         }
     }
 
@@ -138,6 +306,24 @@ impl Node {
     pub fn get_component_mut<T: ComponentPod>(&self) -> Option<RwLockWriteGuard<Box<T>>> {
         self.components.get_mut::<T>()
     }
+
+    /// See [`NodeComponents::snapshot`].
+    pub fn snapshot_components(
+        &self,
+        format: SceneFormat,
+    ) -> Result<ComponentsSnapshot, SceneError> {
+        self.components.snapshot(format)
+    }
+
+    /// See [`NodeComponents::restore`].
+    pub fn restore_components(
+        &mut self,
+        snapshot: &ComponentsSnapshot,
+        components: &Components,
+        plugins: &Plugins,
+    ) {
+        self.components.restore(snapshot, components, plugins);
+    }
 }
 
 #[cfg(feature = "editor_bridge")]
@@ -148,6 +334,8 @@ impl Node {
         components: &Components,
         node_id: &NodeId,
         tree: &mut Tree,
+        time: &Time,
+        input: &InputState,
         instance: &Instance,
         ui: &mut Ui,
     ) {
@@ -180,14 +368,30 @@ impl Node {
                     components: &self.components,
                     transform: &mut self.transform,
                     global_transform: &self.global_transform,
+                    time,
+                    input,
                     instance,
                 };
 
                 component.write().unwrap().inspector_ui(plugins, ctx, ui);
             }
 
+            // data of components that couldn't be loaded, kept until removed
+            for name in self.components.opaque.keys() {
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(name)
+                        .on_hover_text("Couldn't be loaded, its data is kept when saving.");
+
+                    if ui.button("-").clicked() {
+                        remove.push(name.clone());
+                    }
+                });
+            }
+
             for remove in remove {
-                self.components.components.remove(&remove);
+                self.components.remove(&remove);
             }
 
             ui.separator();
@@ -220,6 +424,8 @@ impl Node {
         plugins: &Plugins,
         node_id: &NodeId,
         tree: &mut Tree,
+        time: &Time,
+        input: &InputState,
         instance: &Instance,
     ) {
         for mut component in self.components.components_mut() {
@@ -230,6 +436,8 @@ impl Node {
                 components: &self.components,
                 transform: &mut self.transform,
                 global_transform: &self.global_transform,
+                time,
+                input,
                 instance,
             };
 
@@ -244,6 +452,8 @@ impl Node {
         plugins: &Plugins,
         node_id: &NodeId,
         tree: &mut Tree,
+        time: &Time,
+        input: &InputState,
         instance: &Instance,
     ) {
         for mut component in self.components.components_mut() {
@@ -254,6 +464,8 @@ impl Node {
                 components: &self.components,
                 transform: &mut self.transform,
                 global_transform: &self.global_transform,
+                time,
+                input,
                 instance,
             };
 
@@ -263,14 +475,19 @@ impl Node {
         self.components.update();
     }
 
+    /// Runs `stage` for the component with the long name `component`.
     pub fn update(
         &mut self,
+        stage: Stage,
+        component: &str,
         plugins: &Plugins,
         node_id: &NodeId,
         tree: &mut Tree,
+        time: &Time,
+        input: &InputState,
         instance: &Instance,
     ) {
-        for component in self.components.components.values() {
+        if let Some(component) = self.components.components.get(component) {
             let ctx = ComponentCtx {
                 tree,
                 node_id,
@@ -278,10 +495,20 @@ impl Node {
                 components: &self.components,
                 transform: &mut self.transform,
                 global_transform: &self.global_transform,
+                time,
+                input,
                 instance,
             };
 
-            component.write().unwrap().update(plugins, ctx);
+            let mut component = component.write().unwrap();
+
+            match stage {
+                Stage::PreUpdate => component.pre_update(plugins, ctx),
+                Stage::FixedUpdate => component.fixed_update(plugins, ctx),
+                Stage::Update => component.update(plugins, ctx),
+                Stage::PostUpdate => component.post_update(plugins, ctx),
+                Stage::PreRender => component.pre_render(plugins, ctx),
+            }
         }
 
         self.components.update();
@@ -292,6 +519,8 @@ impl Node {
         plugins: &Plugins,
         node_id: &NodeId,
         tree: &mut Tree,
+        time: &Time,
+        input: &InputState,
         instance: &Instance,
     ) {
         for component in self.components.components.values() {
@@ -302,6 +531,8 @@ impl Node {
                 components: &self.components,
                 transform: &mut self.transform,
                 global_transform: &self.global_transform,
+                time,
+                input,
                 instance,
             };
 
@@ -403,6 +634,8 @@ impl Node {
         plugins: &Plugins,
         node_id: &NodeId,
         tree: &mut Tree,
+        time: &Time,
+        input: &InputState,
         instance: &Instance,
     ) {
         for component in self.components.components.values() {
@@ -413,6 +646,8 @@ impl Node {
                 components: &self.components,
                 transform: &mut self.transform,
                 global_transform: &self.global_transform,
+                time,
+                input,
                 instance,
             };
 
@@ -422,3 +657,70 @@ impl Node {
         self.components.update();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quartz_engine;
+    use crate::reflect::{Reflect, Value};
+
+    #[derive(Reflect, Default, Debug, Clone, PartialEq)]
+    struct Stats {
+        health: f32,
+        speed: f32,
+    }
+
+    impl Component for Stats {
+        type Plugins = ();
+    }
+
+    #[derive(Reflect, Default, Debug, Clone, PartialEq)]
+    enum Mode {
+        #[default]
+        Off,
+        Blink(f32),
+        Pulse { from: f32, to: f32 },
+    }
+
+    impl Component for Mode {
+        type Plugins = ();
+    }
+
+    #[test]
+    fn restores_snapshots() {
+        let mut components = Components::new();
+        components.register_component::<Stats>();
+        components.register_component::<Mode>();
+        let plugins = Plugins::new();
+
+        for &format in SceneFormat::ALL.iter() {
+            let mut node = Node::new();
+            let stats = Stats {
+                health: 10.0,
+                speed: 2.0,
+            };
+            node.components.add_direct(stats.clone());
+            node.components.add_direct(Mode::Blink(0.5));
+            let opaque = OpaqueComponent {
+                version: 0,
+                data: Value::from_ron("Unknown(size: 1)").unwrap(),
+            };
+            node.components
+                .opaque
+                .insert(String::from("Unknown"), opaque);
+
+            let snapshot = node.snapshot_components(format).unwrap();
+
+            node.components.remove(Stats::long_name_const());
+            node.components.remove("Unknown");
+            *node.get_component_mut::<Mode>().unwrap() =
+                Box::new(Mode::Pulse { from: 0.0, to: 1.0 });
+
+            node.restore_components(&snapshot, &components, &plugins);
+            assert_eq!(**node.get_component::<Stats>().unwrap(), stats);
+            assert_eq!(**node.get_component::<Mode>().unwrap(), Mode::Blink(0.5));
+            assert_eq!(node.components.opaque().count(), 1);
+            assert_eq!(node.snapshot_components(format).unwrap(), snapshot);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::asset::Assets;
+use crate::render::prelude::*;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// A pipeline that can be rebuilt when its shader files change.
+pub trait ReloadPipeline: 'static {
+    /// See [`RenderPipeline::reload`].
+    fn reload(&mut self, instance: &Instance) -> Result<bool, Box<dyn std::error::Error>>;
+}
+
+impl<T: ColorTargetState + 'static, D: DepthStencilState + 'static> ReloadPipeline
+    for RenderPipeline<T, D>
+{
+    fn reload(&mut self, instance: &Instance) -> Result<bool, Box<dyn std::error::Error>> {
+        RenderPipeline::reload(self, instance)
+    }
+}
+
+impl ReloadPipeline for ComputePipeline {
+    fn reload(&mut self, instance: &Instance) -> Result<bool, Box<dyn std::error::Error>> {
+        ComputePipeline::reload(self, instance)
+    }
+}
+
+/// A pipeline registered in [`Pipelines`].
+pub type SharedPipeline<P> = Rc<RefCell<P>>;
+
+struct PipelineEntry {
+    name: String,
+    pipeline: Weak<RefCell<dyn ReloadPipeline>>,
+}
+
+/// Pipelines built from shader files, reloaded by the editor when project files change.
+#[derive(Default)]
+pub struct Pipelines {
+    pipelines: RefCell<Vec<PipelineEntry>>,
+}
+
+impl Pipelines {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `pipeline`, it's reloaded by [`Pipelines::reload`] until the returned
+    /// pointer is dropped. Errors are reported to [`Assets`] under `name`.
+    pub fn add<P: ReloadPipeline>(
+        &self,
+        name: impl Into<String>,
+        pipeline: P,
+    ) -> SharedPipeline<P> {
+        let pipeline = Rc::new(RefCell::new(pipeline));
+        let weak: Weak<RefCell<dyn ReloadPipeline>> = Rc::downgrade(&pipeline) as _;
+
+        self.pipelines.borrow_mut().push(PipelineEntry {
+            name: name.into(),
+            pipeline: weak,
+        });
+
+        pipeline
+    }
+
+    /// Rebuilds the pipelines whose shader files changed, and drops the pipelines that
+    /// aren't used anymore.
+    ///
+    /// A failed reload keeps the old pipeline and reports the error to `assets`, a successful
+    /// one clears it.
+    pub fn reload(&self, assets: &Assets, instance: &Instance) {
+        let mut pipelines = self.pipelines.borrow_mut();
+        pipelines.retain(|entry| entry.pipeline.strong_count() > 0);
+
+        for entry in pipelines.iter() {
+            let pipeline = match entry.pipeline.upgrade() {
+                Some(pipeline) => pipeline,
+                None => continue,
+            };
+
+            let result = pipeline.borrow_mut().reload(instance);
+
+            match result {
+                Ok(true) => {
+                    log::info!("reloaded pipeline: {}", entry.name);
+                    assets.clear_error(&entry.name);
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    log::error!("failed to reload pipeline {}: {}", entry.name, err);
+                    assets.set_error(entry.name.clone(), err);
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,18 @@
+use crate::asset::*;
 use crate::component::*;
+use crate::event::*;
+use crate::input::*;
+use crate::node::*;
+use crate::pipeline::*;
 use crate::reflect::*;
+use crate::time::*;
 use crate::tree::*;
 use egui::Ui;
+use linked_hash_map::LinkedHashMap;
 use quartz_render::prelude::*;
 use std::any::{Any, TypeId};
 use std::cell::UnsafeCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -92,22 +99,51 @@ impl PluginContainer {
     }
 }
 
+/// A part of the game update, each stage runs for every plugin and then every component
+/// before the next one starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stage {
+    PreUpdate,
+    /// Runs [`Time::fixed_steps`] times per frame, between [`Stage::PreUpdate`] and
+    /// [`Stage::Update`], each step being [`Time::fixed_timestep`] seconds long.
+    FixedUpdate,
+    Update,
+    PostUpdate,
+    /// Runs after global transforms have been updated, right before rendering.
+    PreRender,
+}
+
 pub struct Plugins {
-    pub(crate) plugins: HashMap<String, PluginContainer>,
+    pub(crate) plugins: LinkedHashMap<String, PluginContainer>,
+    /// For each plugin, the plugins it runs after.
+    pub(crate) dependencies: HashMap<String, Vec<String>>,
+    /// Plugin ids in the order they run, dependencies first and otherwise in the order they
+    /// were registered.
+    pub(crate) order: Vec<String>,
+    pub assets: Assets,
+    pub pipelines: Pipelines,
+    pub events: EventBus,
 }
 
 impl Plugins {
     pub fn new() -> Self {
         Self {
-            plugins: HashMap::new(),
+            plugins: LinkedHashMap::new(),
+            dependencies: HashMap::new(),
+            order: Vec::new(),
+            assets: Assets::new(),
+            pipelines: Pipelines::new(),
+            events: EventBus::new(),
         }
     }
 
     pub fn start(&self, ctx: PluginCtx) {
-        for id in self.plugins.keys() {
+        for id in &self.order {
             let ctx = PluginCtx {
                 tree: ctx.tree,
                 plugins: ctx.plugins,
+                time: ctx.time,
+                input: ctx.input,
                 instance: ctx.instance,
                 target_format: ctx.target_format,
             };
@@ -120,10 +156,12 @@ impl Plugins {
     }
 
     pub fn editor_start(&self, ctx: PluginCtx) {
-        for id in self.plugins.keys() {
+        for id in &self.order {
             let ctx = PluginCtx {
                 tree: ctx.tree,
                 plugins: ctx.plugins,
+                time: ctx.time,
+                input: ctx.input,
                 instance: ctx.instance,
                 target_format: ctx.target_format,
             };
@@ -135,27 +173,36 @@ impl Plugins {
         }
     }
 
-    pub fn update(&self, ctx: PluginCtx) {
-        for id in self.plugins.keys() {
+    /// Runs `stage` for every plugin.
+    pub fn run_stage(&self, stage: Stage, ctx: PluginCtx) {
+        for id in &self.order {
             let ctx = PluginCtx {
                 tree: ctx.tree,
                 plugins: ctx.plugins,
+                time: ctx.time,
+                input: ctx.input,
                 instance: ctx.instance,
                 target_format: ctx.target_format,
             };
 
-            self.get_mut_dyn(id, |plugin| {
-                plugin.update(ctx);
+            self.get_mut_dyn(id, |plugin| match stage {
+                Stage::PreUpdate => plugin.pre_update(ctx),
+                Stage::FixedUpdate => plugin.fixed_update(ctx),
+                Stage::Update => plugin.update(ctx),
+                Stage::PostUpdate => plugin.post_update(ctx),
+                Stage::PreRender => plugin.pre_render(ctx),
             })
             .unwrap();
         }
     }
 
     pub fn editor_update(&self, ctx: PluginCtx) {
-        for id in self.plugins.keys() {
+        for id in &self.order {
             let ctx = PluginCtx {
                 tree: ctx.tree,
                 plugins: ctx.plugins,
+                time: ctx.time,
+                input: ctx.input,
                 instance: ctx.instance,
                 target_format: ctx.target_format,
             };
@@ -168,13 +215,14 @@ impl Plugins {
     }
 
     pub fn render(&self, ctx: PluginRenderCtx) {
-        for id in self.plugins.keys() {
+        for id in &self.order {
             let ctx = PluginRenderCtx {
                 tree: ctx.tree,
                 plugins: ctx.plugins,
                 instance: ctx.instance,
                 render_ctx: ctx.render_ctx,
                 target: ctx.target,
+                viewport_camera: ctx.viewport_camera,
             };
 
             self.get_mut_dyn(id, |plugin| {
@@ -184,14 +232,42 @@ impl Plugins {
         }
     }
 
+    pub fn draw(&self, ctx: PluginDrawCtx) {
+        for id in &self.order {
+            let ctx = PluginDrawCtx {
+                tree: ctx.tree,
+                plugins: ctx.plugins,
+                instance: ctx.instance,
+                viewport_camera: ctx.viewport_camera,
+                render_pass: ctx.render_pass,
+            };
+
+            self.get_mut_dyn(id, |plugin| {
+                plugin.draw(ctx);
+            })
+            .unwrap();
+        }
+    }
+
+    /// Sample count for the main render pass, the highest requested by any plugin.
+    pub fn sample_count(&self) -> u32 {
+        self.plugins
+            .values()
+            .filter_map(|plugin| plugin.get())
+            .map(|plugin| plugin.sample_count())
+            .max()
+            .unwrap_or(1)
+    }
+
     pub fn viewport_render(&self, ctx: PluginRenderCtx) {
-        for id in self.plugins.keys() {
+        for id in &self.order {
             let ctx = PluginRenderCtx {
                 tree: ctx.tree,
                 plugins: ctx.plugins,
                 instance: ctx.instance,
                 render_ctx: ctx.render_ctx,
                 target: ctx.target,
+                viewport_camera: ctx.viewport_camera,
             };
 
             self.get_mut_dyn(id, |plugin| {
@@ -225,8 +301,9 @@ impl Plugins {
         }
     }
 
+    /// Plugin ids in the order they run.
     pub fn plugins(&self) -> Vec<String> {
-        self.plugins.keys().cloned().collect()
+        self.order.clone()
     }
 
     pub fn get_mut_dyn<O>(&self, id: &str, f: impl FnOnce(&mut dyn Plugin) -> O) -> Result<O, ()> {
@@ -249,10 +326,64 @@ impl Plugins {
         C::Plugins::fetch(self, |plugins| C::init(plugins))
     }
 
-    pub fn register_plugin<P: Plugin>(&mut self, init_ctx: PluginInitCtx) {
+    pub fn register_plugin<P: Plugin>(
+        &mut self,
+        init_ctx: PluginInitCtx,
+    ) -> PluginRegistration<'_> {
         let id = P::long_name_const().to_string();
-        self.plugins
-            .insert(id, PluginContainer::new(Box::new(P::init(init_ctx))));
+        self.plugins.insert(
+            id.clone(),
+            PluginContainer::new(Box::new(P::init(init_ctx))),
+        );
+        self.sort();
+
+        PluginRegistration { plugins: self, id }
+    }
+
+    /// Makes the plugin `id` run after `dependency` in every stage.
+    ///
+    /// The dependency doesn't have to be registered yet.
+    pub fn add_dependency(&mut self, id: &str, dependency: &str) {
+        self.dependencies
+            .entry(id.to_string())
+            .or_default()
+            .push(dependency.to_string());
+        self.sort();
+    }
+
+    fn sort(&mut self) {
+        fn visit(
+            id: &String,
+            plugins: &Plugins,
+            visiting: &mut HashSet<String>,
+            order: &mut Vec<String>,
+        ) {
+            if order.contains(id) || !plugins.plugins.contains_key(id) {
+                return;
+            }
+
+            if !visiting.insert(id.clone()) {
+                log::warn!("plugin dependency cycle through '{}'", id);
+                return;
+            }
+
+            if let Some(dependencies) = plugins.dependencies.get(id) {
+                for dependency in dependencies {
+                    visit(dependency, plugins, visiting, order);
+                }
+            }
+
+            order.push(id.clone());
+        }
+
+        let mut visiting = HashSet::new();
+        let mut order = Vec::with_capacity(self.plugins.len());
+
+        for id in self.plugins.keys() {
+            visit(id, self, &mut visiting, &mut order);
+        }
+
+        self.order = order;
     }
 
     pub fn take<'a, P: Plugin>(&'a self) -> Option<&'a mut P> {
@@ -274,6 +405,26 @@ impl Plugins {
     }
 }
 
+/// Returned when registering a plugin, to declare what it runs before or after.
+pub struct PluginRegistration<'a> {
+    plugins: &'a mut Plugins,
+    id: String,
+}
+
+impl<'a> PluginRegistration<'a> {
+    /// Runs the plugin after `P` in every stage.
+    pub fn after<P: Plugin>(self) -> Self {
+        self.plugins.add_dependency(&self.id, P::long_name_const());
+        self
+    }
+
+    /// Runs the plugin before `P` in every stage.
+    pub fn before<P: Plugin>(self) -> Self {
+        self.plugins.add_dependency(P::long_name_const(), &self.id);
+        self
+    }
+}
+
 pub trait PluginAny: Any {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
@@ -300,16 +451,55 @@ pub struct PluginInitCtx<'a> {
 pub struct PluginCtx<'a> {
     pub tree: &'a mut Tree,
     pub plugins: &'a Plugins,
+    pub time: &'a Time,
+    pub input: &'a InputState,
     pub instance: &'a Instance,
     pub target_format: format::TargetFormat,
 }
 
+impl<'a> PluginCtx<'a> {
+    /// Sends an event, see [`Events`].
+    pub fn send<T: Event>(&self, event: T) {
+        self.plugins.events.send(event);
+    }
+
+    /// Sends an event to `node_id`, it bubbles up to every ancestor of the node.
+    pub fn send_to<T: Event>(&self, node_id: impl Into<NodeId>, event: T) {
+        self.plugins.events.send_to(self.tree, node_id, event);
+    }
+
+    pub fn read<T: Event>(&self, reader: &mut EventReader<T>) -> Vec<T> {
+        self.plugins.events.read(reader)
+    }
+
+    /// Reads events sent to `node_id` or any of its descendants, along with the node they
+    /// were sent to.
+    pub fn read_node<T: Event>(
+        &self,
+        reader: &mut EventReader<T>,
+        node_id: impl Into<NodeId>,
+    ) -> Vec<(NodeId, T)> {
+        self.plugins.events.read_node(reader, node_id)
+    }
+}
+
 pub struct PluginRenderCtx<'a, 'b, 'c> {
     pub tree: &'a mut Tree,
     pub plugins: &'a Plugins,
     pub instance: &'a Instance,
     pub render_ctx: &'a mut RenderCtx<'b>,
     pub target: &'a TextureView<'c>,
+    /// View projection of the editor camera when rendering the viewport.
+    pub viewport_camera: &'a Option<Mat4>,
+}
+
+pub struct PluginDrawCtx<'a, 'b, 'c, 'd> {
+    pub tree: &'a Tree,
+    pub plugins: &'a Plugins,
+    pub instance: &'a Instance,
+    pub viewport_camera: &'a Option<Mat4>,
+    pub render_pass:
+        &'a mut EmptyRenderPass<'b, 'c, 'd, format::TargetFormat, format::Depth32Float>,
 }
 
 #[allow(unused_variables)]
@@ -324,8 +514,18 @@ pub trait Plugin: PluginAny + Reflect + ReflectName {
 
     fn editor_start(&mut self, ctx: PluginCtx) {}
 
+    fn pre_update(&mut self, ctx: PluginCtx) {}
+
+    /// Runs once per fixed step, see [`Stage::FixedUpdate`].
+    fn fixed_update(&mut self, ctx: PluginCtx) {}
+
     fn update(&mut self, ctx: PluginCtx) {}
 
+    fn post_update(&mut self, ctx: PluginCtx) {}
+
+    /// Runs at the end of the update, after global transforms have been updated.
+    fn pre_render(&mut self, ctx: PluginCtx) {}
+
     fn editor_update(&mut self, ctx: PluginCtx) {}
 
     fn render(&mut self, ctx: PluginRenderCtx) {}
@@ -333,6 +533,16 @@ pub trait Plugin: PluginAny + Reflect + ReflectName {
     fn viewport_render(&mut self, ctx: PluginRenderCtx) {
         self.render(ctx);
     }
+
+    /// Draws into the main render pass after components have rendered, both in game and in
+    /// the editor viewport.
+    fn draw(&mut self, ctx: PluginDrawCtx) {}
+
+    /// Requests multisampling for the main render pass, it's rendered into a multisampled
+    /// target and resolved into the frame if any plugin returns more than 1.
+    fn sample_count(&self) -> u32 {
+        1
+    }
 }
 
 pub trait PluginFetch<'a>: Sized {
@@ -389,3 +599,103 @@ impl_fetch!(A, B, C, D, E, F, G, H, I);
 impl_fetch!(A, B, C, D, E, F, G, H, I, J);
 impl_fetch!(A, B, C, D, E, F, G, H, I, J, K);
 impl_fetch!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quartz_engine;
+
+    macro_rules! test_plugins {
+        ($($ident:ident),+) => {
+            $(
+                #[derive(Reflect, Default)]
+                struct $ident {
+                    value: u32,
+                }
+
+                impl Plugin for $ident {
+                    fn init(_ctx: PluginInitCtx) -> Self {
+                        Self::default()
+                    }
+                }
+            )+
+        };
+    }
+
+    test_plugins!(A, B, C, D);
+
+    /// Registers `P` without an instance to initialize it with.
+    fn register<P: Plugin + Default>(plugins: &mut Plugins) -> PluginRegistration<'_> {
+        let id = P::long_name_const().to_string();
+        plugins
+            .plugins
+            .insert(id.clone(), PluginContainer::new(Box::new(P::default())));
+        plugins.sort();
+
+        PluginRegistration { plugins, id }
+    }
+
+    fn position<P: Plugin>(plugins: &Plugins) -> usize {
+        let id = P::long_name_const();
+        plugins
+            .order
+            .iter()
+            .position(|plugin| plugin == id)
+            .unwrap()
+    }
+
+    #[test]
+    fn keeps_registration_order() {
+        let mut plugins = Plugins::new();
+        register::<B>(&mut plugins);
+        register::<A>(&mut plugins);
+        register::<C>(&mut plugins);
+
+        assert_eq!(position::<B>(&plugins), 0);
+        assert_eq!(position::<A>(&plugins), 1);
+        assert_eq!(position::<C>(&plugins), 2);
+    }
+
+    #[test]
+    fn runs_after_and_before() {
+        let mut plugins = Plugins::new();
+        register::<A>(&mut plugins).after::<B>();
+        register::<B>(&mut plugins);
+        register::<C>(&mut plugins).before::<A>();
+
+        assert_eq!(plugins.plugins(), plugins.order);
+        assert!(position::<B>(&plugins) < position::<A>(&plugins));
+        assert!(position::<C>(&plugins) < position::<A>(&plugins));
+        assert_eq!(plugins.order.len(), 3);
+    }
+
+    #[test]
+    fn sorts_diamond_dependencies() {
+        let mut plugins = Plugins::new();
+        register::<D>(&mut plugins).after::<B>().after::<C>();
+        register::<C>(&mut plugins).after::<A>();
+        register::<B>(&mut plugins).after::<A>();
+        register::<A>(&mut plugins);
+
+        assert_eq!(position::<A>(&plugins), 0);
+        assert!(position::<B>(&plugins) < position::<D>(&plugins));
+        assert!(position::<C>(&plugins) < position::<D>(&plugins));
+        assert_eq!(plugins.order.len(), 4);
+    }
+
+    #[test]
+    fn breaks_cycles() {
+        let mut plugins = Plugins::new();
+        register::<A>(&mut plugins).after::<C>();
+        register::<B>(&mut plugins).after::<A>();
+        register::<C>(&mut plugins).after::<B>();
+        register::<D>(&mut plugins).after::<C>();
+
+        // every plugin still runs exactly once
+        let mut order = plugins.order.clone();
+        order.sort();
+        order.dedup();
+        assert_eq!(order.len(), 4);
+        assert!(position::<C>(&plugins) < position::<D>(&plugins));
+    }
+}
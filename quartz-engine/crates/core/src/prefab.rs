@@ -0,0 +1,819 @@
+use crate::component::*;
+use crate::node::*;
+use crate::plugin::*;
+use crate::reflect::serde::ComponentDeserializer;
+use crate::reflect::Value;
+use crate::transform::*;
+use crate::tree::*;
+use linked_hash_map::LinkedHashMap;
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Links an instantiated node back to the prefab node it was created from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrefabLink {
+    /// Name of the source prefab, see [`Prefab::name`].
+    pub prefab: String,
+    /// Id of the node in the prefab, see [`PrefabNode::id`].
+    pub node: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrefabNode {
+    /// Identifies the node across edits of the prefab, the root is always `0`.
+    pub id: u64,
+    pub name: String,
+    pub transform: Transform,
+    /// Id of the parent, `None` for the root.
+    pub parent: Option<u64>,
+    /// Serialized components by long name.
+    pub components: LinkedHashMap<String, Value>,
+}
+
+/// A node subtree saved as an asset.
+///
+/// Nodes are stored depth first, so the root is always the first node and parents always
+/// come before their children. Components are kept in serialized form and created with
+/// [`ComponentDeserializer`] when instantiated.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Prefab {
+    /// Path of the prefab relative to the project root, used to link instances.
+    #[serde(skip)]
+    pub name: String,
+    pub nodes: Vec<PrefabNode>,
+    /// The id given to the next node added, ids of removed nodes aren't reused.
+    pub next_id: u64,
+}
+
+impl PrefabNode {
+    /// Serializes a single node without its children, `id` is left as `0` and `parent` as
+    /// `None`.
+    ///
+    /// Components still waiting to be created by [`Tree::init_prefab_components`] are
+    /// included.
+    pub fn from_node(tree: &Tree, node_id: impl Into<NodeId>) -> ron::Result<Option<Self>> {
+        let node_id = node_id.into();
+
+        let node = match tree.get_node(node_id) {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+
+        let mut components = LinkedHashMap::new();
+
+        for component in node.components.components() {
+            components.insert(
+                component.long_name().to_string(),
+                component_value(&**component)?,
+            );
+        }
+
+        for (id, name, value) in &tree.prefab_components {
+            if *id == node_id {
+                components.insert(name.clone(), value.clone());
+            }
+        }
+
+        Ok(Some(Self {
+            id: 0,
+            name: node.name.clone(),
+            transform: node.transform.clone(),
+            parent: None,
+            components,
+        }))
+    }
+}
+
+impl Prefab {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            nodes: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Creates a prefab from `root` and all of its descendants.
+    ///
+    /// Nodes linked to the prefab keep their ids, and other nodes take the id of a node
+    /// with the same name and parent in the version of the prefab the tree was last updated
+    /// to, so instances can still be matched up after the prefab is edited.
+    pub fn from_tree(
+        tree: &Tree,
+        root: impl Into<NodeId>,
+        name: impl Into<String>,
+    ) -> ron::Result<Self> {
+        let mut prefab = Self::new(name);
+        let mut node_ids = Vec::new();
+        let mut parents = Vec::new();
+        prefab.add_node(tree, root.into(), None, &mut node_ids, &mut parents)?;
+        prefab.assign_ids(tree, &node_ids, &parents);
+
+        Ok(prefab)
+    }
+
+    fn add_node(
+        &mut self,
+        tree: &Tree,
+        node_id: NodeId,
+        parent: Option<usize>,
+        node_ids: &mut Vec<NodeId>,
+        parents: &mut Vec<Option<usize>>,
+    ) -> ron::Result<()> {
+        let prefab_node = match PrefabNode::from_node(tree, node_id)? {
+            Some(prefab_node) => prefab_node,
+            None => return Ok(()),
+        };
+
+        let index = self.nodes.len();
+        self.nodes.push(prefab_node);
+        node_ids.push(node_id);
+        parents.push(parent);
+
+        for child in tree.get_children(node_id).clone() {
+            self.add_node(tree, child, Some(index), node_ids, parents)?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_ids(&mut self, tree: &Tree, node_ids: &[NodeId], parents: &[Option<usize>]) {
+        let previous = tree.prefabs.get(&self.name);
+
+        if let Some(previous) = previous {
+            self.next_id = previous.next_id;
+        }
+
+        let mut ids = vec![None; self.nodes.len()];
+        let mut used = HashSet::new();
+
+        // linked nodes first, so a new node can't take the id of a linked one
+        for (index, node_id) in node_ids.iter().enumerate() {
+            let link = tree.get_node(node_id).and_then(|node| node.prefab.clone());
+
+            let id = match link {
+                _ if index == 0 => 0,
+                Some(link) if link.prefab == self.name && link.node != 0 => link.node,
+                _ => continue,
+            };
+
+            if used.insert(id) {
+                ids[index] = Some(id);
+            }
+        }
+
+        for index in 0..self.nodes.len() {
+            if ids[index].is_some() {
+                continue;
+            }
+
+            let parent = parents[index].and_then(|parent| ids[parent]);
+            let name = &self.nodes[index].name;
+
+            let matched = previous.and_then(|previous| {
+                previous
+                    .nodes
+                    .iter()
+                    .find(|node| {
+                        node.parent == parent && node.name == *name && !used.contains(&node.id)
+                    })
+                    .map(|node| node.id)
+            });
+
+            let id = match matched {
+                Some(id) => id,
+                None => {
+                    self.next_id += 1;
+                    self.next_id - 1
+                }
+            };
+
+            used.insert(id);
+            ids[index] = Some(id);
+        }
+
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            node.id = ids[index].unwrap();
+            node.parent = parents[index].and_then(|parent| ids[parent]);
+        }
+
+        self.next_id = used
+            .into_iter()
+            .map(|id| id + 1)
+            .fold(self.next_id, u64::max);
+    }
+
+    pub fn node(&self, id: u64) -> Option<&PrefabNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Loads the prefab at `name` relative to `root`.
+    pub fn load(root: impl AsRef<Path>, name: impl Into<String>) -> ron::Result<Self> {
+        let name = name.into();
+        let data = std::fs::read_to_string(root.as_ref().join(&name))?;
+
+        // read through a `Value` so components keep their struct and variant names
+        let mut prefab = Prefab::deserialize(Value::from_ron(&data)?)?;
+        prefab.name = name;
+
+        Ok(prefab)
+    }
+
+    /// Saves the prefab to [`Prefab::name`] relative to `root`.
+    pub fn save(&self, root: impl AsRef<Path>) -> ron::Result<()> {
+        let mut data = Vec::new();
+        let config = ron::ser::PrettyConfig::default();
+        let mut serializer = ron::Serializer::new(&mut data, Some(config), true)?;
+        self.serialize(&mut serializer)?;
+        std::fs::write(root.as_ref().join(&self.name), data)?;
+
+        Ok(())
+    }
+}
+
+fn component_value(component: &dyn ComponentPod) -> ron::Result<Value> {
+    Value::from_serialize(component.as_serialize())
+}
+
+/// Loads `value` into `component`, keeping the component as it was if it can't be read.
+fn reflect_value(name: &str, component: &mut dyn ComponentPod, value: Value) {
+    let mut deserializer = <dyn erased_serde::Deserializer>::erase(value);
+
+    if let Err(err) = component.try_reflect(&mut deserializer) {
+        log::warn!("failed to load prefab component {}: {}", name, err);
+    }
+}
+
+/// Merges `new` into `current`, keeping the values in `current` that differ from `old`.
+///
+/// Structs and tuples are merged field by field, other values, like an enum whose variant
+/// changed, are replaced as a whole.
+fn merge(old: Option<&Value>, current: &Value, new: &Value) -> Value {
+    match (old, current, new) {
+        (
+            Some(Value::Named(old_name, old)),
+            Value::Named(name, current),
+            Value::Named(new_name, new),
+        ) if old_name == name && name == new_name => {
+            Value::Named(name.clone(), Box::new(merge(Some(old), current, new)))
+        }
+        (Some(Value::Map(old)), Value::Map(current), Value::Map(new)) => {
+            let get = |map: &'_ [(Value, Value)], key: &Value| {
+                map.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+            };
+
+            let mut merged = current.clone();
+
+            for (key, new) in new {
+                let value = match get(current, key) {
+                    Some(current) => merge(get(old, key).as_ref(), &current, new),
+                    None => new.clone(),
+                };
+
+                match merged.iter_mut().find(|(k, _)| k == key) {
+                    Some((_, field)) => *field = value,
+                    None => merged.push((key.clone(), value)),
+                }
+            }
+
+            Value::Map(merged)
+        }
+        (Some(Value::Tuple(old)), Value::Tuple(current), Value::Tuple(new))
+            if old.len() == current.len() && current.len() == new.len() =>
+        {
+            let merged = old
+                .iter()
+                .zip(current)
+                .zip(new)
+                .map(|((old, current), new)| merge(Some(old), current, new))
+                .collect();
+
+            Value::Tuple(merged)
+        }
+        (Some(old), current, new) if old == current => new.clone(),
+        _ => current.clone(),
+    }
+}
+
+impl Tree {
+    /// Spawns the nodes of `prefab` with fresh ids under `parent`, and returns the id of
+    /// the root.
+    ///
+    /// Components are created at the start of the next update.
+    pub fn instantiate(
+        &mut self,
+        prefab: &Prefab,
+        parent: impl Into<Option<NodeId>>,
+    ) -> Option<NodeId> {
+        let parent = parent.into();
+        let mut ids = HashMap::new();
+
+        // bring existing instances up to date first, so they all share one version
+        self.sync_prefab(prefab);
+
+        for prefab_node in &prefab.nodes {
+            let node_parent = match prefab_node.parent {
+                Some(prefab_parent) => ids.get(&prefab_parent).cloned(),
+                None => parent,
+            };
+
+            let node_id = self.spawn_prefab_node(&prefab.name, prefab_node, node_parent);
+            ids.insert(prefab_node.id, node_id);
+        }
+
+        ids.get(&0).cloned()
+    }
+
+    fn spawn_prefab_node(
+        &mut self,
+        prefab: &str,
+        prefab_node: &PrefabNode,
+        parent: Option<NodeId>,
+    ) -> NodeId {
+        let node_id = self.spawn();
+
+        let mut node = self.get_node(node_id).unwrap();
+        node.name = prefab_node.name.clone();
+        node.transform = prefab_node.transform.clone();
+        node.prefab = Some(PrefabLink {
+            prefab: prefab.to_string(),
+            node: prefab_node.id,
+        });
+        drop(node);
+
+        if parent.is_some() {
+            self.set_parent(node_id, parent);
+        }
+
+        for (name, value) in &prefab_node.components {
+            self.prefab_components
+                .push((node_id, name.clone(), value.clone()));
+        }
+
+        node_id
+    }
+
+    /// Returns the root nodes of every instance of the prefab named `prefab`.
+    pub fn prefab_instances(&self, prefab: &str) -> Vec<NodeId> {
+        self.nodes()
+            .into_iter()
+            .filter(|node_id| {
+                self.get_node(node_id).map_or(false, |node| {
+                    node.prefab
+                        .as_ref()
+                        .map_or(false, |link| link.prefab == prefab && link.node == 0)
+                })
+            })
+            .collect()
+    }
+
+    fn collect_instance(&self, prefab: &str, node_id: NodeId, nodes: &mut HashMap<u64, NodeId>) {
+        if let Some(node) = self.get_node(node_id) {
+            if let Some(link) = &node.prefab {
+                if link.prefab == prefab {
+                    nodes.insert(link.node, node_id);
+                }
+            }
+        }
+
+        for child in self.get_children(node_id).clone() {
+            self.collect_instance(prefab, child, nodes);
+        }
+    }
+
+    /// Links `root` and its descendants to `prefab`, which should have been created from
+    /// them with [`Prefab::from_tree`].
+    pub fn link_prefab(&mut self, root: impl Into<NodeId>, prefab: &Prefab) {
+        let mut index = 0;
+        self.link_prefab_node(root.into(), prefab, &mut index);
+
+        // the linked nodes match this version now
+        self.prefabs.insert(prefab.name.clone(), prefab.clone());
+    }
+
+    fn link_prefab_node(&self, node_id: NodeId, prefab: &Prefab, index: &mut usize) {
+        match self.get_node(node_id) {
+            Some(mut node) => {
+                // nodes of nested prefab instances stay linked to their own prefab
+                let nested = node
+                    .prefab
+                    .as_ref()
+                    .map_or(false, |link| link.prefab != prefab.name);
+
+                if let (false, Some(prefab_node)) = (nested, prefab.nodes.get(*index)) {
+                    node.prefab = Some(PrefabLink {
+                        prefab: prefab.name.clone(),
+                        node: prefab_node.id,
+                    });
+                }
+            }
+            None => return,
+        }
+
+        *index += 1;
+
+        for child in self.get_children(node_id).clone() {
+            self.link_prefab_node(child, prefab, index);
+        }
+    }
+
+    /// Updates every instance of `new` to match it, while keeping per-instance overrides.
+    ///
+    /// A property is overridden if it differs from `old`, the version of the prefab the
+    /// instances were last updated to.
+    pub fn update_prefab_instances(&mut self, old: &Prefab, new: &Prefab) {
+        self.prefabs.insert(new.name.clone(), new.clone());
+
+        for root in self.prefab_instances(&new.name) {
+            let mut instance = HashMap::new();
+            self.collect_instance(&new.name, root, &mut instance);
+
+            for new_node in &new.nodes {
+                match (instance.get(&new_node.id).cloned(), old.node(new_node.id)) {
+                    (Some(node_id), Some(old_node)) => {
+                        self.update_prefab_node(node_id, old_node, new_node);
+
+                        // move the node if it was moved in the prefab but not in the instance
+                        let old_parent = old_node.parent.and_then(|p| instance.get(&p).cloned());
+                        let new_parent = new_node.parent.and_then(|p| instance.get(&p).cloned());
+
+                        if old_parent != new_parent && self.get_parent(node_id) == old_parent {
+                            if let Some(new_parent) = new_parent {
+                                self.set_parent(node_id, new_parent);
+                            }
+                        }
+                    }
+                    (None, None) => {
+                        let parent = new_node
+                            .parent
+                            .and_then(|parent| instance.get(&parent).cloned());
+
+                        let node_id = self.spawn_prefab_node(&new.name, new_node, parent);
+                        instance.insert(new_node.id, node_id);
+                    }
+                    // the node was removed from this instance
+                    _ => {}
+                }
+            }
+
+            for old_node in &old.nodes {
+                if new.node(old_node.id).is_none() {
+                    if let Some(node_id) = instance.get(&old_node.id) {
+                        self.despawn(*node_id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Updates the instances of `prefab` from the version they were last updated to.
+    fn sync_prefab(&mut self, prefab: &Prefab) {
+        match self.prefabs.get(&prefab.name).cloned() {
+            Some(old) if old != *prefab => self.update_prefab_instances(&old, prefab),
+            Some(_) => {}
+            None => {
+                self.prefabs.insert(prefab.name.clone(), prefab.clone());
+            }
+        }
+    }
+
+    /// Reloads every prefab with instances in the tree from `root`, and updates the
+    /// instances to match while keeping their overrides, see
+    /// [`Tree::update_prefab_instances`].
+    ///
+    /// Should be called after loading a scene, since prefabs may have been edited since it
+    /// was saved.
+    pub fn sync_prefabs(&mut self, root: impl AsRef<Path>) {
+        let mut names = Vec::new();
+
+        for node_id in self.nodes() {
+            if let Some(node) = self.get_node(node_id) {
+                if let Some(link) = &node.prefab {
+                    if !names.contains(&link.prefab) {
+                        names.push(link.prefab.clone());
+                    }
+                }
+            }
+        }
+
+        for name in names {
+            match Prefab::load(root.as_ref(), &name) {
+                Ok(prefab) => self.sync_prefab(&prefab),
+                Err(err) => log::warn!("failed to load prefab {}: {}", name, err),
+            }
+        }
+    }
+
+    fn update_prefab_node(&mut self, node_id: NodeId, old: &PrefabNode, new: &PrefabNode) {
+        let mut node = match self.get_node(node_id) {
+            Some(node) => node,
+            None => return,
+        };
+        let node = &mut *node;
+
+        if node.name == old.name {
+            node.name = new.name.clone();
+        }
+
+        if node.transform.translation == old.transform.translation {
+            node.transform.translation = new.transform.translation;
+        }
+
+        if node.transform.rotation == old.transform.rotation {
+            node.transform.rotation = new.transform.rotation;
+        }
+
+        if node.transform.scale == old.transform.scale {
+            node.transform.scale = new.transform.scale;
+        }
+
+        for (name, new_value) in &new.components {
+            match (
+                node.components.components.get(name),
+                old.components.get(name),
+            ) {
+                (Some(component), old_value) => {
+                    let mut component = component.write().unwrap();
+
+                    let current = match component_value(&**component) {
+                        Ok(current) => current,
+                        Err(err) => {
+                            log::warn!("failed to serialize component {}: {}", name, err);
+                            continue;
+                        }
+                    };
+
+                    let merged = merge(old_value, &current, new_value);
+
+                    if merged != current {
+                        reflect_value(name, &mut **component, merged);
+                    }
+                }
+                (None, None) => {
+                    self.prefab_components
+                        .push((node_id, name.clone(), new_value.clone()));
+                }
+                // the component was removed from this instance
+                (None, Some(_)) => {}
+            }
+        }
+
+        for name in old.components.keys() {
+            if !new.components.contains_key(name) {
+                node.components.remove(name);
+            }
+        }
+    }
+
+    /// Creates the components queued by [`Tree::instantiate`].
+    pub fn init_prefab_components(&mut self, components: &Components, plugins: &Plugins) {
+        let queued = std::mem::take(&mut self.prefab_components);
+
+        for (node_id, name, value) in queued {
+            if !components.inits_long_name.contains_key(name.as_str()) {
+                log::warn!("prefab component not registered: {}", name);
+                continue;
+            }
+
+            if let Some(mut node) = self.get_node(node_id) {
+                let deserializer = ComponentDeserializer {
+                    name: &name,
+                    plugins,
+                    components,
+                };
+
+                match deserializer.deserialize(value) {
+                    Ok(component) => node.components.add_direct(component),
+                    Err(err) => log::warn!("failed to load prefab component {}: {}", name, err),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quartz_engine;
+    use crate::reflect::Reflect;
+
+    #[derive(Reflect, Default, Debug, Clone, PartialEq)]
+    struct Stats {
+        health: f32,
+        speed: f32,
+    }
+
+    impl Component for Stats {
+        type Plugins = ();
+    }
+
+    #[derive(Reflect, Default, Debug, Clone, PartialEq)]
+    enum Team {
+        #[default]
+        None,
+        Red,
+        Named(String),
+    }
+
+    impl Component for Team {
+        type Plugins = ();
+    }
+
+    fn components() -> Components {
+        let mut components = Components::new();
+        components.register_component::<Stats>();
+        components.register_component::<Team>();
+
+        components
+    }
+
+    fn spawn(tree: &mut Tree, name: &str, parent: Option<NodeId>) -> NodeId {
+        let id = match parent {
+            Some(parent) => tree.spawn_child(parent).unwrap(),
+            None => tree.spawn(),
+        };
+        tree.get_node(id).unwrap().name = String::from(name);
+
+        id
+    }
+
+    fn child(tree: &Tree, parent: NodeId, name: &str) -> Option<NodeId> {
+        tree.get_children(parent)
+            .iter()
+            .copied()
+            .find(|child| tree.get_node(child).unwrap().name == name)
+    }
+
+    fn get_stats(tree: &Tree, node_id: NodeId) -> Stats {
+        let node = tree.get_node(node_id).unwrap();
+        let stats = node.get_component::<Stats>().unwrap().as_ref().clone();
+
+        stats
+    }
+
+    #[test]
+    fn updates_instances_of_edited_prefab() {
+        let components = components();
+        let plugins = Plugins::new();
+
+        let mut tree = Tree::new();
+        let root = spawn(&mut tree, "Player", None);
+        let arm = spawn(&mut tree, "Arm", Some(root));
+        let leg = spawn(&mut tree, "Leg", Some(root));
+        tree.get_node(root)
+            .unwrap()
+            .components
+            .add_direct(Team::Red);
+        let stats = Stats {
+            health: 100.0,
+            speed: 1.0,
+        };
+        tree.get_node(leg).unwrap().components.add_direct(stats);
+
+        let v1 = Prefab::from_tree(&tree, root, "player.prefab").unwrap();
+        tree.link_prefab(root, &v1);
+
+        let instance = tree.instantiate(&v1, None).unwrap();
+        tree.init_prefab_components(&components, &plugins);
+        let instance_arm = child(&tree, instance, "Arm").unwrap();
+        let instance_leg = child(&tree, instance, "Leg").unwrap();
+        let node = tree.get_node(instance_leg).unwrap();
+        node.get_component_mut::<Stats>().unwrap().speed = 5.0;
+        drop(node);
+
+        // remove the arm, which moves the leg up, and add a tail after it
+        tree.remove_recursive(arm);
+        let tail = spawn(&mut tree, "Tail", Some(root));
+        tree.get_node(tail)
+            .unwrap()
+            .components
+            .add_direct(Stats::default());
+        *tree
+            .get_node(root)
+            .unwrap()
+            .get_component_mut::<Team>()
+            .unwrap() = Box::new(Team::Named(String::from("Blue")));
+        tree.get_node(leg)
+            .unwrap()
+            .get_component_mut::<Stats>()
+            .unwrap()
+            .health = 150.0;
+
+        let v2 = Prefab::from_tree(&tree, root, "player.prefab").unwrap();
+        tree.link_prefab(root, &v2);
+
+        let id = |prefab: &Prefab, name: &str| {
+            prefab
+                .nodes
+                .iter()
+                .find(|node| node.name == name)
+                .unwrap()
+                .id
+        };
+        assert_eq!(id(&v1, "Leg"), id(&v2, "Leg"));
+        // the id of the removed arm isn't reused
+        assert!(v2.node(id(&v1, "Arm")).is_none());
+        assert_eq!(v2.next_id, 4);
+
+        tree.update_prefab_instances(&v1, &v2);
+        tree.init_prefab_components(&components, &plugins);
+
+        assert!(tree.despawn_queue().contains(&instance_arm));
+        assert_eq!(child(&tree, instance, "Leg"), Some(instance_leg));
+        let expected = Stats {
+            health: 150.0,
+            speed: 5.0,
+        };
+        assert_eq!(get_stats(&tree, instance_leg), expected);
+
+        let node = tree.get_node(instance).unwrap();
+        let team = node.get_component::<Team>().unwrap();
+        assert_eq!(**team, Team::Named(String::from("Blue")));
+        drop(team);
+        drop(node);
+
+        let instance_tail = child(&tree, instance, "Tail").unwrap();
+        let link = tree
+            .get_node(instance_tail)
+            .unwrap()
+            .prefab
+            .clone()
+            .unwrap();
+        assert_eq!(link.node, id(&v2, "Tail"));
+        assert_eq!(get_stats(&tree, instance_tail), Stats::default());
+    }
+
+    #[test]
+    fn matches_unlinked_nodes_by_name() {
+        let mut tree = Tree::new();
+        let root = spawn(&mut tree, "Player", None);
+        spawn(&mut tree, "Arm", Some(root));
+
+        let v1 = Prefab::from_tree(&tree, root, "player.prefab").unwrap();
+        tree.prefabs.insert(v1.name.clone(), v1.clone());
+
+        // never linked, eg. nodes of a nested prefab
+        let v2 = Prefab::from_tree(&tree, root, "player.prefab").unwrap();
+        assert_eq!(v1, v2);
+    }
+
+    #[test]
+    fn keeps_components_that_fail_to_load() {
+        let mut tree = Tree::new();
+        let root = spawn(&mut tree, "Player", None);
+        let stats = Stats {
+            health: 1.0,
+            speed: 2.0,
+        };
+        tree.get_node(root)
+            .unwrap()
+            .components
+            .add_direct(stats.clone());
+
+        let old = PrefabNode::from_node(&tree, root).unwrap().unwrap();
+        let mut new = old.clone();
+        let data = Value::from_ron("Stats(health: \"full\", speed: 3)").unwrap();
+        new.components
+            .insert(String::from(Stats::long_name_const()), data);
+
+        tree.update_prefab_node(root, &old, &new);
+        assert_eq!(get_stats(&tree, root), stats);
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let mut tree = Tree::new();
+        let root = spawn(&mut tree, "Player", None);
+        let arm = spawn(&mut tree, "Arm", Some(root));
+        let team = Team::Named(String::from("Blue"));
+        tree.get_node(root).unwrap().components.add_direct(team);
+        tree.get_node(arm)
+            .unwrap()
+            .components
+            .add_direct(Stats::default());
+
+        let dir = std::env::temp_dir();
+        let prefab = Prefab::from_tree(&tree, root, "quartz-test-player.prefab").unwrap();
+        prefab.save(&dir).unwrap();
+        let loaded = Prefab::load(&dir, &prefab.name).unwrap();
+        std::fs::remove_file(dir.join(&prefab.name)).unwrap();
+
+        assert_eq!(loaded, prefab);
+    }
+
+    #[test]
+    fn merges_enums() {
+        let value = |team: Team| Value::from_serialize(&team).unwrap();
+        let old = value(Team::Red);
+        let new = value(Team::Named(String::from("Blue")));
+
+        assert_eq!(merge(Some(&old), &old, &new), new);
+        let current = value(Team::None);
+        assert_eq!(merge(Some(&old), &current, &new), current);
+
+        let old = value(Team::Named(String::from("Red")));
+        let current = value(Team::Named(String::from("Green")));
+        assert_eq!(merge(Some(&old), &current, &new), current);
+    }
+}
@@ -0,0 +1,131 @@
+use super::Reflect;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use std::cell::Cell;
+
+/// A reflected field, see [`Reflect::fields`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldInfo {
+    pub name: String,
+    pub type_name: &'static str,
+}
+
+impl FieldInfo {
+    pub fn new(name: impl Into<String>, type_name: &'static str) -> Self {
+        Self {
+            name: name.into(),
+            type_name,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReflectError {
+    NoField(String),
+    Ron(ron::Error),
+    /// The value couldn't be read as the field's type.
+    Deserialize(erased_serde::Error),
+}
+
+impl std::fmt::Display for ReflectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoField(path) => write!(f, "no reflected field at '{}'", path),
+            Self::Ron(err) => write!(f, "{}", err),
+            Self::Deserialize(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ReflectError {}
+
+impl From<ron::Error> for ReflectError {
+    fn from(err: ron::Error) -> Self {
+        Self::Ron(err)
+    }
+}
+
+impl From<erased_serde::Error> for ReflectError {
+    fn from(err: erased_serde::Error) -> Self {
+        Self::Deserialize(err)
+    }
+}
+
+// Used by `#[derive(Reflect)]` to get fields as `dyn Reflect` when their type implements
+// `Reflect`, and `None` otherwise. Method resolution picks `AsReflect` when it applies, since
+// it doesn't need the extra reference `AsReflectFallback` is implemented on.
+
+#[doc(hidden)]
+pub struct FieldRef<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub struct FieldMut<'a, T>(pub Cell<Option<&'a mut T>>);
+
+impl<'a, T> FieldMut<'a, T> {
+    pub fn new(field: &'a mut T) -> Self {
+        Self(Cell::new(Some(field)))
+    }
+}
+
+#[doc(hidden)]
+pub trait AsReflect<'a> {
+    fn as_reflect(&self) -> Option<&'a dyn Reflect>;
+}
+
+impl<'a, T: Reflect> AsReflect<'a> for FieldRef<'a, T> {
+    fn as_reflect(&self) -> Option<&'a dyn Reflect> {
+        Some(self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait AsReflectFallback<'a> {
+    fn as_reflect(&self) -> Option<&'a dyn Reflect> {
+        None
+    }
+}
+
+impl<'a, T> AsReflectFallback<'a> for &FieldRef<'a, T> {}
+
+#[doc(hidden)]
+pub trait AsReflectMut<'a> {
+    fn as_reflect_mut(&self) -> Option<&'a mut dyn Reflect>;
+}
+
+impl<'a, T: Reflect> AsReflectMut<'a> for FieldMut<'a, T> {
+    fn as_reflect_mut(&self) -> Option<&'a mut dyn Reflect> {
+        self.0.take().map(|field| field as &mut dyn Reflect)
+    }
+}
+
+#[doc(hidden)]
+pub trait AsReflectMutFallback<'a> {
+    fn as_reflect_mut(&self) -> Option<&'a mut dyn Reflect> {
+        None
+    }
+}
+
+impl<'a, T> AsReflectMutFallback<'a> for &FieldMut<'a, T> {}
+
+/// A struct field or enum variant name, read by `#[derive(Reflect)]`.
+#[doc(hidden)]
+pub struct Identifier(pub String);
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdentifierVisitor;
+
+        impl<'de> Visitor<'de> for IdentifierVisitor {
+            type Value = Identifier;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an identifier")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Identifier, E> {
+                Ok(Identifier(value.to_string()))
+            }
+        }
+
+        deserializer.deserialize_identifier(IdentifierVisitor)
+    }
+}
@@ -0,0 +1,229 @@
+use super::{FieldInfo, Reflect};
+use crate::asset::Handle;
+use crate::node::NodeId;
+use crate::transform::Transform;
+use quartz_render::prelude::*;
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use serde::Serialize;
+use std::any::type_name;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+crate::reflect_value!(
+    bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, String, NodeId, Color,
+);
+
+crate::reflect_value!(Vec2, Vec3, Vec4, IVec2, IVec3, IVec4, UVec2, UVec3, UVec4, Quat, Mat3, Mat4);
+
+impl Reflect for Transform {
+    fn reflect(&mut self, deserializer: &mut dyn erased_serde::Deserializer) {
+        self.try_reflect(deserializer).unwrap();
+    }
+
+    fn try_reflect(
+        &mut self,
+        deserializer: &mut dyn erased_serde::Deserializer,
+    ) -> Result<(), erased_serde::Error> {
+        *self = erased_serde::deserialize(deserializer)?;
+
+        Ok(())
+    }
+
+    fn as_serialize(&self) -> &dyn erased_serde::Serialize {
+        self
+    }
+
+    fn short_name_const() -> &'static str {
+        "Transform"
+    }
+
+    fn fields(&self) -> Vec<FieldInfo> {
+        vec![
+            FieldInfo::new("translation", type_name::<Vec3>()),
+            FieldInfo::new("rotation", type_name::<Quat>()),
+            FieldInfo::new("scale", type_name::<Vec3>()),
+        ]
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        match name {
+            "translation" => Some(&self.translation),
+            "rotation" => Some(&self.rotation),
+            "scale" => Some(&self.scale),
+            _ => None,
+        }
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        match name {
+            "translation" => Some(&mut self.translation),
+            "rotation" => Some(&mut self.rotation),
+            "scale" => Some(&mut self.scale),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Reflect for Handle<T> {
+    fn reflect(&mut self, deserializer: &mut dyn erased_serde::Deserializer) {
+        self.try_reflect(deserializer).unwrap();
+    }
+
+    fn try_reflect(
+        &mut self,
+        deserializer: &mut dyn erased_serde::Deserializer,
+    ) -> Result<(), erased_serde::Error> {
+        *self = erased_serde::deserialize(deserializer)?;
+
+        Ok(())
+    }
+
+    fn as_serialize(&self) -> &dyn erased_serde::Serialize {
+        self
+    }
+
+    fn short_name_const() -> &'static str {
+        "Handle"
+    }
+}
+
+/// Fields of the value if there is one.
+impl<T: Reflect + Serialize + DeserializeOwned> Reflect for Option<T> {
+    fn reflect(&mut self, deserializer: &mut dyn erased_serde::Deserializer) {
+        self.try_reflect(deserializer).unwrap();
+    }
+
+    fn try_reflect(
+        &mut self,
+        deserializer: &mut dyn erased_serde::Deserializer,
+    ) -> Result<(), erased_serde::Error> {
+        *self = erased_serde::deserialize(deserializer)?;
+
+        Ok(())
+    }
+
+    fn as_serialize(&self) -> &dyn erased_serde::Serialize {
+        self
+    }
+
+    fn short_name_const() -> &'static str {
+        "Option"
+    }
+
+    fn fields(&self) -> Vec<FieldInfo> {
+        self.as_ref().map_or_else(Vec::new, |value| value.fields())
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        self.as_ref()?.field(name)
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        self.as_mut()?.field_mut(name)
+    }
+}
+
+impl<T: Reflect + Serialize + DeserializeOwned> Reflect for Vec<T> {
+    fn reflect(&mut self, deserializer: &mut dyn erased_serde::Deserializer) {
+        self.try_reflect(deserializer).unwrap();
+    }
+
+    fn try_reflect(
+        &mut self,
+        deserializer: &mut dyn erased_serde::Deserializer,
+    ) -> Result<(), erased_serde::Error> {
+        *self = erased_serde::deserialize(deserializer)?;
+
+        Ok(())
+    }
+
+    fn as_serialize(&self) -> &dyn erased_serde::Serialize {
+        self
+    }
+
+    fn short_name_const() -> &'static str {
+        "Vec"
+    }
+
+    fn fields(&self) -> Vec<FieldInfo> {
+        (0..self.len())
+            .map(|index| FieldInfo::new(index.to_string(), type_name::<T>()))
+            .collect()
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        let element = self.get(name.parse::<usize>().ok()?)?;
+        Some(element)
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        let element = self.get_mut(name.parse::<usize>().ok()?)?;
+        Some(element)
+    }
+}
+
+/// Names a map entry by its key written as ron, without the quotes around strings.
+fn key_name(key: &impl Serialize) -> Option<String> {
+    let name = ron::ser::to_string(key).ok()?;
+
+    match name
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+    {
+        Some(name) => Some(name.to_string()),
+        None => Some(name),
+    }
+}
+
+/// Reads a key named by [`key_name`], or the same name with the quotes around strings.
+fn parse_key<K: DeserializeOwned>(name: &str) -> Option<K> {
+    ron::de::from_str(name).ok().or_else(|| {
+        let name: StrDeserializer<'_, ValueError> = name.into_deserializer();
+        K::deserialize(name).ok()
+    })
+}
+
+impl<K, V> Reflect for HashMap<K, V>
+where
+    K: Serialize + DeserializeOwned + Eq + Hash,
+    V: Reflect + Serialize + DeserializeOwned,
+{
+    fn reflect(&mut self, deserializer: &mut dyn erased_serde::Deserializer) {
+        self.try_reflect(deserializer).unwrap();
+    }
+
+    fn try_reflect(
+        &mut self,
+        deserializer: &mut dyn erased_serde::Deserializer,
+    ) -> Result<(), erased_serde::Error> {
+        *self = erased_serde::deserialize(deserializer)?;
+
+        Ok(())
+    }
+
+    fn as_serialize(&self) -> &dyn erased_serde::Serialize {
+        self
+    }
+
+    fn short_name_const() -> &'static str {
+        "HashMap"
+    }
+
+    fn fields(&self) -> Vec<FieldInfo> {
+        self.keys()
+            .filter_map(key_name)
+            .map(|name| FieldInfo::new(name, type_name::<V>()))
+            .collect()
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        let value = self.get(&parse_key::<K>(name)?)?;
+        Some(value)
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        let value = self.get_mut(&parse_key::<K>(name)?)?;
+        Some(value)
+    }
+}
@@ -1,11 +1,28 @@
+pub mod field;
+mod impls;
 pub(crate) mod serde;
+pub mod value;
+pub use self::field::{FieldInfo, ReflectError};
 pub use self::serde::ReflectDeserializer;
+pub use self::value::Value;
 pub use quartz_engine_derive::Reflect;
 
+#[allow(unused_variables)]
 pub trait Reflect: erased_serde::Serialize {
     fn reflect<'de>(&mut self, deserializer: &mut dyn erased_serde::Deserializer<'de>);
     fn as_serialize(&self) -> &dyn erased_serde::Serialize;
 
+    /// Like [`Reflect::reflect`] but returns an error instead of panicking when the data can't
+    /// be read. `self` may be partially overwritten when it fails.
+    fn try_reflect<'de>(
+        &mut self,
+        deserializer: &mut dyn erased_serde::Deserializer<'de>,
+    ) -> Result<(), erased_serde::Error> {
+        self.reflect(deserializer);
+
+        Ok(())
+    }
+
     fn short_name_const() -> &'static str
     where
         Self: Sized;
@@ -15,6 +32,102 @@ pub trait Reflect: erased_serde::Serialize {
     {
         std::any::type_name::<Self>()
     }
+
+    /// Version of the serialized layout, set with `#[reflect(version = 1)]`.
+    ///
+    /// Scenes store the version of every component, data saved with an older version is
+    /// migrated with the migrations registered through
+    /// [`ComponentRegistration`](crate::component::ComponentRegistration).
+    fn version_const() -> u32
+    where
+        Self: Sized,
+    {
+        0
+    }
+
+    /// The fields of a struct or the elements of a collection, empty for plain values.
+    fn fields(&self) -> Vec<FieldInfo> {
+        Vec::new()
+    }
+
+    /// Gets a field by name, [`None`] if there's no such field or its type isn't [`Reflect`].
+    ///
+    /// Elements of a [`Vec`] are named by their index, and entries of a
+    /// [`HashMap`](std::collections::HashMap) by their key written as ron, string keys can be
+    /// written with or without quotes.
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        None
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        None
+    }
+
+    /// Gets a nested field by a dotted path, eg. `"settings.scale"` or `"lods.2"`.
+    ///
+    /// Names containing dots can be quoted or put in brackets, eg. `"materials.\"a.b\".color"`
+    /// or `"materials[a.b].color"`.
+    fn path(&self, path: &str) -> Option<&dyn Reflect> {
+        match split_path(path)? {
+            (name, Some(rest)) => self.field(name)?.path(rest),
+            (name, None) => self.field(name),
+        }
+    }
+
+    fn path_mut(&mut self, path: &str) -> Option<&mut dyn Reflect> {
+        match split_path(path)? {
+            (name, Some(rest)) => self.field_mut(name)?.path_mut(rest),
+            (name, None) => self.field_mut(name),
+        }
+    }
+
+    /// Overwrites the field at `path` with `value`, like [`Reflect::try_reflect`] does.
+    fn set_field(
+        &mut self,
+        path: &str,
+        value: &dyn erased_serde::Serialize,
+    ) -> Result<(), ReflectError> {
+        let field = self
+            .path_mut(path)
+            .ok_or_else(|| ReflectError::NoField(path.to_string()))?;
+
+        let value = Value::from_serialize(value)?;
+        field.try_reflect(&mut <dyn erased_serde::Deserializer>::erase(value))?;
+
+        Ok(())
+    }
+}
+
+/// Splits the first name off a path, see [`Reflect::path`], [`None`] if a quote or bracket
+/// isn't closed.
+///
+/// Quotes are kept, so quoted map keys are read as ron strings.
+fn split_path(path: &str) -> Option<(&str, Option<&str>)> {
+    let (name, rest) = if let Some(quoted) = path.strip_prefix('"') {
+        let mut escaped = false;
+        let end = quoted.find(|c| {
+            let end = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        })?;
+
+        path.split_at(end + 2)
+    } else if let Some(path) = path.strip_prefix('[') {
+        let end = path.find(']')?;
+
+        (&path[..end], &path[end + 1..])
+    } else {
+        let end = path.find(['.', '[']).unwrap_or(path.len());
+
+        path.split_at(end)
+    };
+
+    match rest.strip_prefix('.') {
+        Some(rest) => Some((name, Some(rest))),
+        None if rest.is_empty() => Some((name, None)),
+        None if rest.starts_with('[') => Some((name, Some(rest))),
+        None => None,
+    }
 }
 
 pub trait ReflectName {
@@ -31,3 +144,260 @@ impl<T: Reflect> ReflectName for T {
         Self::short_name_const()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quartz_engine;
+    use ::serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    /// Has no `Default`, so variants holding it can only be read from data.
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Id(u32);
+
+    #[derive(Reflect, Default, Debug, Clone, PartialEq)]
+    struct Color(f32, f32, f32);
+
+    #[derive(Reflect, Default, Debug, Clone, PartialEq)]
+    struct Meters(f32);
+
+    #[derive(Reflect, Default, Debug, Clone, PartialEq)]
+    enum Shape {
+        #[default]
+        Empty,
+        Mesh(Id),
+        Sphere(f32, Id),
+        Box {
+            size: f32,
+            id: Id,
+            #[reflect(default)]
+            hollow: bool,
+        },
+        Tinted(#[reflect(reflect)] Color),
+        Named {
+            #[reflect(reflect)]
+            color: Color,
+            #[reflect(ignore)]
+            cached: Option<Id>,
+        },
+    }
+
+    fn read_ron<T: Reflect>(text: &str, target: &mut T) -> Result<(), erased_serde::Error> {
+        let mut deserializer = ron::Deserializer::from_str(text).unwrap();
+        target.try_reflect(&mut <dyn erased_serde::Deserializer>::erase(&mut deserializer))
+    }
+
+    fn round_trip<T: Reflect + Serialize>(value: &T, target: &mut T) {
+        let text = ron::to_string(value).unwrap();
+        read_ron(&text, target).unwrap();
+    }
+
+    #[test]
+    fn round_trips_enums() {
+        let shapes = vec![
+            Shape::Empty,
+            Shape::Mesh(Id(1)),
+            Shape::Sphere(2.0, Id(2)),
+            Shape::Box {
+                size: 3.0,
+                id: Id(3),
+                hollow: true,
+            },
+            Shape::Tinted(Color(1.0, 0.5, 0.0)),
+            Shape::Named {
+                color: Color(0.0, 1.0, 0.0),
+                cached: None,
+            },
+        ];
+
+        // into the same variant in place, and into every other variant
+        for shape in &shapes {
+            for target in &shapes {
+                let mut target = target.clone();
+                round_trip(shape, &mut target);
+                assert_eq!(target, *shape);
+            }
+        }
+    }
+
+    #[test]
+    fn reads_new_variants() {
+        let mut shape = Shape::Empty;
+        read_ron("Box(size: 1.5, id: (4))", &mut shape).unwrap();
+        let expected = Shape::Box {
+            size: 1.5,
+            id: Id(4),
+            hollow: false,
+        };
+        assert_eq!(shape, expected);
+
+        read_ron("Sphere(0.5, Id(5))", &mut shape).unwrap();
+        assert_eq!(shape, Shape::Sphere(0.5, Id(5)));
+
+        // fields without a default have to be in the data
+        assert!(read_ron("Box(size: 1.5)", &mut Shape::Empty).is_err());
+        assert!(read_ron("Sphere(0.5)", &mut Shape::Empty).is_err());
+        assert!(read_ron("Cone(1.0)", &mut Shape::Empty).is_err());
+    }
+
+    #[test]
+    fn keeps_ignored_fields() {
+        let mut shape = Shape::Named {
+            color: Color(1.0, 1.0, 1.0),
+            cached: Some(Id(7)),
+        };
+        let data = Shape::Named {
+            color: Color(0.0, 0.0, 0.0),
+            cached: None,
+        };
+        round_trip(&data, &mut shape);
+
+        let expected = Shape::Named {
+            color: Color(0.0, 0.0, 0.0),
+            cached: Some(Id(7)),
+        };
+        assert_eq!(shape, expected);
+    }
+
+    #[test]
+    fn round_trips_tuple_structs() {
+        let mut color = Color::default();
+        round_trip(&Color(0.25, 0.5, 1.0), &mut color);
+        assert_eq!(color, Color(0.25, 0.5, 1.0));
+
+        read_ron("Color(1.0, 0.0, 0.5)", &mut color).unwrap();
+        assert_eq!(color, Color(1.0, 0.0, 0.5));
+        assert!(read_ron("(1.0, 0.0)", &mut color).is_err());
+
+        let mut meters = Meters::default();
+        round_trip(&Meters(2.5), &mut meters);
+        assert_eq!(meters, Meters(2.5));
+    }
+
+    #[derive(Reflect, Default)]
+    struct Settings {
+        scale: f32,
+        #[reflect(reflect)]
+        tint: Color,
+        lods: Vec<f32>,
+        curves: HashMap<String, Vec<f32>>,
+        layers: HashMap<u32, String>,
+        offsets: Option<Vec<f32>>,
+    }
+
+    fn settings() -> Settings {
+        let mut curves = HashMap::new();
+        curves.insert(String::from("fade"), vec![0.0, 1.0]);
+        curves.insert(String::from("fade.in"), vec![0.5]);
+
+        let mut layers = HashMap::new();
+        layers.insert(3, String::from("ui"));
+
+        Settings {
+            scale: 2.0,
+            tint: Color(1.0, 0.5, 0.0),
+            lods: vec![10.0, 20.0, 40.0],
+            curves,
+            layers,
+            offsets: Some(vec![0.25]),
+        }
+    }
+
+    fn value(field: Option<&dyn Reflect>) -> Option<Value> {
+        Some(Value::from_serialize(field?.as_serialize()).unwrap())
+    }
+
+    fn float(value: f32) -> Option<Value> {
+        Some(Value::from_serialize(&value).unwrap())
+    }
+
+    #[test]
+    fn gets_fields_by_path() {
+        let settings = settings();
+
+        assert_eq!(value(settings.field("scale")), float(2.0));
+        assert_eq!(value(settings.path("tint.1")), float(0.5));
+        assert_eq!(value(settings.path("lods.2")), float(40.0));
+        assert_eq!(value(settings.path("lods[1]")), float(20.0));
+        assert_eq!(value(settings.path("curves.fade.1")), float(1.0));
+        assert_eq!(value(settings.path("curves.\"fade\".0")), float(0.0));
+        assert_eq!(value(settings.path("curves.\"fade.in\".0")), float(0.5));
+        assert_eq!(value(settings.path("curves[fade.in][0]")), float(0.5));
+        assert_eq!(value(settings.path("curves[\"fade.in\"].0")), float(0.5));
+        assert_eq!(value(settings.path("offsets.0")), float(0.25));
+
+        let layer = value(settings.path("layers.3")).unwrap();
+        assert_eq!(layer.as_str(), Some("ui"));
+
+        let names = settings
+            .curves
+            .fields()
+            .into_iter()
+            .map(|field| field.name)
+            .collect::<Vec<_>>();
+        assert!(names.contains(&String::from("fade.in")));
+    }
+
+    #[test]
+    fn misses_invalid_paths() {
+        let settings = settings();
+
+        assert!(settings.path("missing").is_none());
+        assert!(settings.path("scale.x").is_none());
+        assert!(settings.path("lods.3").is_none());
+        assert!(settings.path("lods.x").is_none());
+        assert!(settings.path("curves.fade.in.0").is_none());
+        assert!(settings.path("curves[fade.in").is_none());
+        assert!(settings.path("curves.\"fade.in").is_none());
+        assert!(settings.path("curves[fade]x").is_none());
+        assert!(settings.path("layers.ui").is_none());
+        assert!(settings.path("").is_none());
+
+        let empty = Settings::default();
+        assert!(empty.path("offsets.0").is_none());
+    }
+
+    #[test]
+    fn sets_fields_by_path() {
+        let mut settings = settings();
+
+        settings.set_field("scale", &3.0f32).unwrap();
+        settings.set_field("tint.2", &1.0f32).unwrap();
+        settings.set_field("lods.0", &5.0f32).unwrap();
+        settings.set_field("curves[fade.in]", &vec![0.75f32]).unwrap();
+        settings.set_field("layers.3", &"world").unwrap();
+        settings.set_field("offsets.0", &0.5f32).unwrap();
+
+        assert_eq!(settings.scale, 3.0);
+        assert_eq!(settings.tint, Color(1.0, 0.5, 1.0));
+        assert_eq!(settings.lods, vec![5.0, 20.0, 40.0]);
+        assert_eq!(settings.curves["fade.in"], vec![0.75]);
+        assert_eq!(settings.layers[&3], "world");
+        assert_eq!(settings.offsets, Some(vec![0.5]));
+
+        settings.path_mut("lods").unwrap().set_field("1", &15.0f32).unwrap();
+        assert_eq!(settings.lods[1], 15.0);
+
+        settings.set_field("offsets", &None::<Vec<f32>>).unwrap();
+        assert_eq!(settings.offsets, None);
+    }
+
+    #[test]
+    fn reports_set_field_errors() {
+        let mut settings = settings();
+
+        match settings.set_field("lods.5", &1.0f32) {
+            Err(ReflectError::NoField(path)) => assert_eq!(path, "lods.5"),
+            result => panic!("expected a missing field, got {:?}", result),
+        }
+
+        match settings.set_field("scale", &"large") {
+            Err(ReflectError::Deserialize(_)) => (),
+            result => panic!("expected a deserialize error, got {:?}", result),
+        }
+
+        assert!(settings.set_field("tint", &(1.0f32, 0.0f32)).is_err());
+        assert_eq!(settings.scale, 2.0);
+    }
+}
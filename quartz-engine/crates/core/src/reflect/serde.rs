@@ -1,9 +1,11 @@
 use crate::component::*;
 use crate::node::*;
 use crate::plugin::*;
+use crate::prefab::{Prefab, PrefabLink};
 use crate::scene::*;
 use crate::transform::*;
 use crate::tree::*;
+use super::value::ValueSeed;
 use super::Reflect;
 use linked_hash_map::LinkedHashMap;
 use serde::{
@@ -11,7 +13,7 @@ use serde::{
     ser::{SerializeStruct, Serializer},
     Deserialize, Serialize,
 };
-use std::sync::RwLock;
+use std::collections::HashMap;
 
 impl<'a> Serialize for Scene<'a> {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
@@ -44,11 +46,23 @@ impl Serialize for dyn Plugin {
 
 impl Serialize for Node {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("Node", 3)?;
+        // versions are only stored for components that have one, and have to come before
+        // the components since they're needed to load them
+        let versions = self
+            .components
+            .components()
+            .filter(|component| component.version() > 0)
+            .map(|component| (component.long_name().to_string(), component.version()))
+            .collect::<LinkedHashMap<_, _>>();
+
+        let mut state = serializer.serialize_struct("Node", 6)?;
 
         state.serialize_field("name", &self.name)?;
         state.serialize_field("transform", &self.transform)?;
+        state.serialize_field("versions", &versions)?;
         state.serialize_field("component", &self.components)?;
+        state.serialize_field("opaque", &self.components.opaque)?;
+        state.serialize_field("prefab", &self.prefab)?;
 
         state.end()
     }
@@ -73,13 +87,15 @@ impl Serialize for NodeContainer {
 
 impl Serialize for Tree {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("Tree", 4)?;
+        let mut state = serializer.serialize_struct("Tree", 5)?;
 
         state.serialize_field("nodes", &self.nodes)?;
         state.serialize_field("children", &self.children)?;
         state.serialize_field("parents", &self.parents)?;
         state.serialize_field("base", &self.base)?;
 
+        state.serialize_field("prefabs", &self.prefabs)?;
+
         state.end()
     }
 }
@@ -163,7 +179,7 @@ impl<'a, 'de> DeserializeSeed<'de> for SceneDeserializer<'a> {
                     }
                 }
 
-                let _ = plugins.ok_or_else(|| de::Error::missing_field("plugins"))?;
+                plugins.ok_or_else(|| de::Error::missing_field("plugins"))?;
                 let tree = tree.ok_or_else(|| de::Error::missing_field("tree"))?;
 
                 Ok(tree)
@@ -205,11 +221,17 @@ impl<'a, 'de> DeserializeSeed<'de> for PluginsDeserializer<'a> {
 
             fn visit_map<V: MapAccess<'de>>(self, mut map: V) -> Result<Self::Value, V::Error> {
                 while let Some(key) = map.next_key()? {
-                    self.plugins
-                        .get_mut_dyn(key, |plugin| {
-                            map.next_value_seed(PluginDeserializer { plugin })
-                        })
-                        .unwrap()?;
+                    let loaded = self.plugins.get_mut_dyn(key, |plugin| {
+                        map.next_value_seed(PluginDeserializer { plugin })
+                    });
+
+                    match loaded {
+                        Ok(loaded) => loaded?,
+                        Err(()) => {
+                            log::warn!("plugin not registered, dropping its data: {}", key);
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
                 }
 
                 Ok(())
@@ -231,8 +253,8 @@ impl<'a, 'de> DeserializeSeed<'de> for PluginDeserializer<'a> {
 
     fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
         self.plugin
-            .reflect(&mut <dyn erased_serde::Deserializer>::erase(deserializer));
-        Ok(())
+            .try_reflect(&mut <dyn erased_serde::Deserializer>::erase(deserializer))
+            .map_err(de::Error::custom)
     }
 }
 
@@ -252,6 +274,7 @@ impl<'a, 'de> DeserializeSeed<'de> for TreeDeserializer<'a> {
             Parents,
             Children,
             Base,
+            Prefabs,
         }
 
         struct TreeVisitor<'a> {
@@ -282,7 +305,7 @@ impl<'a, 'de> DeserializeSeed<'de> for TreeDeserializer<'a> {
                     .map(|id| NodeId(id.0 + 1))
                     .unwrap_or(NodeId(0));
 
-                Ok(Tree {
+                let mut tree = Tree {
                     nodes,
                     children: seq
                         .next_element()?
@@ -296,7 +319,17 @@ impl<'a, 'de> DeserializeSeed<'de> for TreeDeserializer<'a> {
                     next_node_id,
                     despawn: Vec::new(),
                     added: Vec::new(),
-                })
+                    prefab_components: Vec::new(),
+                    prefabs: named_prefabs(
+                        seq.next_element()?
+                            .ok_or(de::Error::invalid_length(4, &self))?,
+                    ),
+                    component_index: ComponentIndex::default(),
+                };
+
+                tree.index_components();
+
+                Ok(tree)
             }
 
             fn visit_map<V: MapAccess<'de>>(self, mut map: V) -> Result<Tree, V::Error> {
@@ -304,6 +337,7 @@ impl<'a, 'de> DeserializeSeed<'de> for TreeDeserializer<'a> {
                 let mut parents = None;
                 let mut children = None;
                 let mut base = None;
+                let mut prefabs = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -338,6 +372,13 @@ impl<'a, 'de> DeserializeSeed<'de> for TreeDeserializer<'a> {
 
                             base = Some(map.next_value()?);
                         }
+                        Field::Prefabs => {
+                            if prefabs.is_some() {
+                                return Err(de::Error::duplicate_field("prefabs"));
+                            }
+
+                            prefabs = Some(map.next_value()?);
+                        }
                     }
                 }
 
@@ -352,7 +393,7 @@ impl<'a, 'de> DeserializeSeed<'de> for TreeDeserializer<'a> {
                     .map(|id| NodeId(id.0 + 1))
                     .unwrap_or(NodeId(0));
 
-                Ok(Tree {
+                let mut tree = Tree {
                     nodes,
                     parents,
                     children,
@@ -360,11 +401,18 @@ impl<'a, 'de> DeserializeSeed<'de> for TreeDeserializer<'a> {
                     next_node_id,
                     despawn: Vec::new(),
                     added: Vec::new(),
-                })
+                    prefab_components: Vec::new(),
+                    prefabs: named_prefabs(prefabs.unwrap_or_default()),
+                    component_index: ComponentIndex::default(),
+                };
+
+                tree.index_components();
+
+                Ok(tree)
             }
         }
 
-        const FIELDS: &[&str] = &["nodes", "parents", "children", "base"];
+        const FIELDS: &[&str] = &["nodes", "parents", "children", "base", "prefabs"];
         deserializer.deserialize_struct(
             "Tree",
             FIELDS,
@@ -376,6 +424,15 @@ impl<'a, 'de> DeserializeSeed<'de> for TreeDeserializer<'a> {
     }
 }
 
+// prefab names aren't serialized, they're the keys of the map
+fn named_prefabs(mut prefabs: LinkedHashMap<String, Prefab>) -> LinkedHashMap<String, Prefab> {
+    for (name, prefab) in prefabs.iter_mut() {
+        prefab.name = name.clone();
+    }
+
+    prefabs
+}
+
 pub(crate) struct NodesDeserializer<'a> {
     pub components: &'a Components,
     pub plugins: &'a Plugins,
@@ -455,7 +512,10 @@ impl<'a, 'de> DeserializeSeed<'de> for NodeDeserializer<'a> {
         enum Field {
             Name,
             Transform,
+            Versions,
             Component,
+            Opaque,
+            Prefab,
         }
 
         struct NodeVisitor<'a> {
@@ -473,30 +533,39 @@ impl<'a, 'de> DeserializeSeed<'de> for NodeDeserializer<'a> {
             }
 
             fn visit_seq<V: SeqAccess<'de>>(self, mut seq: V) -> Result<Self::Value, V::Error> {
-                Ok(Node {
-                    name: seq
-                        .next_element()?
-                        .ok_or(de::Error::invalid_length(0, &self))?,
-                    transform: seq
-                        .next_element()?
-                        .ok_or(de::Error::invalid_length(1, &self))?,
-                    global_transform: Transform::IDENTITY,
-                    components: NodeComponents {
-                        add: Default::default(),
-                        components: seq
-                            .next_element_seed(ComponentsDeserializer {
-                                plugins: self.plugins,
-                                components: self.components,
-                            })?
-                            .ok_or(de::Error::invalid_length(2, &self))?,
-                    },
-                })
+                let name = seq
+                    .next_element()?
+                    .ok_or(de::Error::invalid_length(0, &self))?;
+                let transform = seq
+                    .next_element()?
+                    .ok_or(de::Error::invalid_length(1, &self))?;
+                let versions: HashMap<String, u32> = seq
+                    .next_element()?
+                    .ok_or(de::Error::invalid_length(2, &self))?;
+                let components = seq
+                    .next_element_seed(ComponentsDeserializer {
+                        versions: &versions,
+                        plugins: self.plugins,
+                        components: self.components,
+                    })?
+                    .ok_or(de::Error::invalid_length(3, &self))?;
+                let opaque = seq
+                    .next_element()?
+                    .ok_or(de::Error::invalid_length(4, &self))?;
+                let prefab = seq
+                    .next_element()?
+                    .ok_or(de::Error::invalid_length(5, &self))?;
+
+                Ok(self.node(name, transform, components, opaque, prefab))
             }
 
             fn visit_map<V: MapAccess<'de>>(self, mut map: V) -> Result<Self::Value, V::Error> {
                 let mut name = None;
                 let mut transform = None;
+                let mut versions = HashMap::new();
                 let mut components = None;
+                let mut opaque: Option<LinkedHashMap<String, OpaqueComponent>> = None;
+                let mut prefab = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -514,16 +583,34 @@ impl<'a, 'de> DeserializeSeed<'de> for NodeDeserializer<'a> {
 
                             transform = Some(map.next_value()?);
                         }
+                        Field::Versions => {
+                            versions = map.next_value()?;
+                        }
                         Field::Component => {
                             if components.is_some() {
                                 return Err(de::Error::duplicate_field("component"));
                             }
 
                             components = Some(map.next_value_seed(ComponentsDeserializer {
+                                versions: &versions,
                                 components: self.components,
                                 plugins: self.plugins,
                             })?);
                         }
+                        Field::Opaque => {
+                            if opaque.is_some() {
+                                return Err(de::Error::duplicate_field("opaque"));
+                            }
+
+                            opaque = Some(map.next_value()?);
+                        }
+                        Field::Prefab => {
+                            if prefab.is_some() {
+                                return Err(de::Error::duplicate_field("prefab"));
+                            }
+
+                            prefab = Some(map.next_value()?);
+                        }
                     }
                 }
 
@@ -531,16 +618,57 @@ impl<'a, 'de> DeserializeSeed<'de> for NodeDeserializer<'a> {
                 let transform = transform.ok_or_else(|| de::Error::missing_field("transform"))?;
                 let components = components.ok_or_else(|| de::Error::missing_field("component"))?;
 
-                Ok(Node {
+                Ok(self.node(
+                    name,
+                    transform,
+                    components,
+                    opaque.unwrap_or_default(),
+                    prefab.unwrap_or(None),
+                ))
+            }
+        }
+
+        impl<'a> NodeVisitor<'a> {
+            fn node(
+                &self,
+                name: String,
+                transform: Transform,
+                mut components: NodeComponents,
+                opaque: LinkedHashMap<String, OpaqueComponent>,
+                prefab: Option<PrefabLink>,
+            ) -> Node {
+                // components that couldn't be loaded before might be now
+                for (component, opaque) in opaque {
+                    if components.components.contains_key(&component) {
+                        continue;
+                    }
+
+                    match load_component(&component, opaque, self.components, self.plugins) {
+                        Ok(loaded) => components.add_direct(loaded),
+                        Err(opaque) => {
+                            components.opaque.insert(component, opaque);
+                        }
+                    }
+                }
+
+                Node {
                     name,
                     transform,
                     global_transform: Transform::IDENTITY,
-                    components: NodeComponents { components, add: Default::default() },
-                })
+                    components,
+                    prefab,
+                }
             }
         }
 
-        const FIELDS: &[&str] = &["type", "component"];
+        const FIELDS: &[&str] = &[
+            "name",
+            "transform",
+            "versions",
+            "component",
+            "opaque",
+            "prefab",
+        ];
         deserializer.deserialize_struct(
             "Node",
             FIELDS,
@@ -553,31 +681,27 @@ impl<'a, 'de> DeserializeSeed<'de> for NodeDeserializer<'a> {
 }
 
 pub struct ComponentsDeserializer<'a> {
+    /// Versions the components were saved with, components not in it have version 0.
+    versions: &'a HashMap<String, u32>,
     plugins: &'a Plugins,
     components: &'a Components,
 }
 
 impl<'a, 'de> DeserializeSeed<'de> for ComponentsDeserializer<'a> {
-    type Value = LinkedHashMap<String, RwLock<Box<dyn ComponentPod>>>;
+    type Value = NodeComponents;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(field_identifier, rename_all = "lowercase")]
-        enum Field {
-            Type,
-            Component,
-        }
-
         struct ComponentsVisitor<'a> {
+            versions: &'a HashMap<String, u32>,
             plugins: &'a Plugins,
             components: &'a Components,
         }
 
         impl<'a, 'de> Visitor<'de> for ComponentsVisitor<'a> {
-            type Value = LinkedHashMap<String, RwLock<Box<dyn ComponentPod>>>;
+            type Value = NodeComponents;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
                 formatter.write_str("map of components")?;
@@ -586,16 +710,22 @@ impl<'a, 'de> DeserializeSeed<'de> for ComponentsDeserializer<'a> {
             }
 
             fn visit_map<V: MapAccess<'de>>(self, mut map: V) -> Result<Self::Value, V::Error> {
-                let mut components = LinkedHashMap::new();
-
-                while let Some(key) = map.next_key()? {
-                    let component = map.next_value_seed(ComponentDeserializer {
-                        name: key,
-                        plugins: self.plugins,
-                        components: self.components,
-                    })?;
-
-                    components.insert(key.to_string(), RwLock::new(component));
+                let mut components = NodeComponents::new();
+
+                while let Some(name) = map.next_key::<String>()? {
+                    // components are buffered before they're loaded, so a component that
+                    // fails to load is kept as is instead of failing the whole scene
+                    let opaque = OpaqueComponent {
+                        version: self.versions.get(&name).cloned().unwrap_or(0),
+                        data: map.next_value_seed(ValueSeed)?,
+                    };
+
+                    match load_component(&name, opaque, self.components, self.plugins) {
+                        Ok(component) => components.add_direct(component),
+                        Err(opaque) => {
+                            components.opaque.insert(name, opaque);
+                        }
+                    }
                 }
 
                 Ok(components)
@@ -603,16 +733,44 @@ impl<'a, 'de> DeserializeSeed<'de> for ComponentsDeserializer<'a> {
         }
 
         deserializer.deserialize_map(ComponentsVisitor {
+            versions: self.versions,
             plugins: self.plugins,
             components: self.components,
         })
     }
 }
 
+/// Migrates and loads a component from its serialized data, giving the data back if it isn't
+/// registered or can't be read.
+fn load_component(
+    name: &str,
+    opaque: OpaqueComponent,
+    components: &Components,
+    plugins: &Plugins,
+) -> Result<Box<dyn ComponentPod>, OpaqueComponent> {
+    let mut component = match components.init_long_name(name, plugins) {
+        Some(component) => component,
+        None => {
+            log::warn!("component not registered, keeping its data: {}", name);
+            return Err(opaque);
+        }
+    };
+
+    let data = components.migrate(name, opaque.version, opaque.data.clone());
+
+    match component.try_reflect(&mut <dyn erased_serde::Deserializer>::erase(data)) {
+        Ok(()) => Ok(component),
+        Err(err) => {
+            log::warn!("failed to load component {}, keeping its data: {}", name, err);
+            Err(opaque)
+        }
+    }
+}
+
 pub struct ComponentDeserializer<'a> {
-    name: &'a str,
-    plugins: &'a Plugins,
-    components: &'a Components,
+    pub name: &'a str,
+    pub plugins: &'a Plugins,
+    pub components: &'a Components,
 }
 
 impl<'a, 'de> DeserializeSeed<'de> for ComponentDeserializer<'a> {
@@ -626,27 +784,245 @@ impl<'a, 'de> DeserializeSeed<'de> for ComponentDeserializer<'a> {
         let mut component = self
             .components
             .init_long_name(self.name, self.plugins)
-            .unwrap();
+            .ok_or_else(|| {
+                de::Error::custom(format!("component not registered: {}", self.name))
+            })?;
 
-        component.reflect(&mut <dyn erased_serde::Deserializer>::erase(deserializer));
+        component
+            .try_reflect(&mut <dyn erased_serde::Deserializer>::erase(deserializer))
+            .map_err(de::Error::custom)?;
 
         Ok(component)
     }
 }
 
-pub struct ReflectDeserializer<'a> {
-    pub reflect: &'a mut dyn Reflect,
+pub struct ReflectDeserializer<'a, R: Reflect + ?Sized = dyn Reflect> {
+    pub reflect: &'a mut R,
 }
 
-impl<'a, 'de> DeserializeSeed<'de> for ReflectDeserializer<'a> {
+impl<'a, 'de, R: Reflect + ?Sized> DeserializeSeed<'de> for ReflectDeserializer<'a, R> {
     type Value = ();
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        self.reflect.reflect(&mut <dyn erased_serde::Deserializer>::erase(deserializer));
+        self.reflect
+            .try_reflect(&mut <dyn erased_serde::Deserializer>::erase(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quartz_engine;
+    use crate::reflect::Value;
+
+    #[derive(Reflect, Debug, PartialEq)]
+    #[reflect(version = 1)]
+    struct Light {
+        color: (f32, f32, f32),
+        #[reflect(rename = "strength")]
+        intensity: f32,
+        range: f32,
+        #[reflect(default)]
+        shadows: bool,
+    }
+
+    impl Default for Light {
+        fn default() -> Self {
+            Self {
+                color: (1.0, 1.0, 1.0),
+                intensity: 1.0,
+                range: 1.0,
+                shadows: true,
+            }
+        }
+    }
+
+    impl Component for Light {
+        type Plugins = ();
+    }
+
+    impl Light {
+        // version 0 stored the radius of the light instead of its range
+        fn migrate_v0(mut data: Value) -> Value {
+            if let Some(radius) = data.remove_field("radius").and_then(|r| r.as_f64()) {
+                data.insert_field("range", Value::F64(radius * 2.0));
+            }
+
+            data
+        }
+    }
 
-        Ok(())
+    /// [`Light`] as it was saved at version 0.
+    #[derive(Serialize, Deserialize, Default)]
+    #[serde(rename = "Light")]
+    struct LightV0 {
+        color: (f32, f32, f32),
+        strength: f32,
+        radius: f32,
     }
-}
\ No newline at end of file
+
+    impl Reflect for LightV0 {
+        fn reflect(&mut self, deserializer: &mut dyn erased_serde::Deserializer) {
+            self.try_reflect(deserializer).unwrap();
+        }
+
+        fn try_reflect(
+            &mut self,
+            deserializer: &mut dyn erased_serde::Deserializer,
+        ) -> Result<(), erased_serde::Error> {
+            *self = erased_serde::deserialize(deserializer)?;
+
+            Ok(())
+        }
+
+        fn as_serialize(&self) -> &dyn erased_serde::Serialize {
+            self
+        }
+
+        fn short_name_const() -> &'static str {
+            "Light"
+        }
+
+        fn long_name_const() -> &'static str {
+            Light::long_name_const()
+        }
+    }
+
+    impl Component for LightV0 {
+        type Plugins = ();
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    enum Mode {
+        #[default]
+        Off,
+        Blink(f32),
+        Pulse { from: f32, to: f32 },
+    }
+
+    impl Component for Mode {
+        type Plugins = ();
+    }
+
+    fn components() -> Components {
+        let mut components = Components::new();
+        components
+            .register_component::<Light>()
+            .migration(0, Light::migrate_v0);
+        components.register_component::<Mode>();
+
+        components
+    }
+
+    fn save(tree: &Tree, format: SceneFormat) -> Vec<u8> {
+        let plugins = Plugins::new();
+        let scene = Scene {
+            plugins: &plugins,
+            tree,
+        };
+
+        format.serialize(&scene).unwrap()
+    }
+
+    fn load(data: &[u8], format: SceneFormat, components: &Components) -> Tree {
+        let mut plugins = Plugins::new();
+        let seed = SceneDeserializer {
+            components,
+            plugins: &mut plugins,
+        };
+
+        format.deserialize_seed(data, seed).unwrap()
+    }
+
+    fn spawn(tree: &mut Tree, component: impl ToPod) -> NodeId {
+        let id = tree.spawn();
+        tree.get_node(id).unwrap().components.add_direct(component);
+
+        id
+    }
+
+    #[test]
+    fn migrates_from_v0() {
+        for &format in SceneFormat::ALL.iter() {
+            let mut tree = Tree::new();
+            let id = spawn(
+                &mut tree,
+                LightV0 {
+                    color: (1.0, 0.5, 0.0),
+                    strength: 3.0,
+                    radius: 2.5,
+                },
+            );
+
+            let components = components();
+            let tree = load(&save(&tree, format), format, &components);
+
+            let expected = Light {
+                color: (1.0, 0.5, 0.0),
+                // read from its old name
+                intensity: 3.0,
+                range: 5.0,
+                // missing, so set to its default rather than kept
+                shadows: false,
+            };
+            let node = tree.get_node(id).unwrap();
+            assert_eq!(**node.get_component::<Light>().unwrap(), expected);
+            drop(node);
+
+            // saved at version 1 now, so it isn't migrated again
+            let tree = load(&save(&tree, format), format, &components);
+            let node = tree.get_node(id).unwrap();
+            assert_eq!(**node.get_component::<Light>().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_components() {
+        for &format in SceneFormat::ALL.iter() {
+            // a single node, so the saved tree layout doesn't depend on hash order
+            let mut tree = Tree::new();
+            let id = spawn(&mut tree, Light::default());
+            let mode = Mode::Pulse { from: 0.5, to: 1.0 };
+            tree.get_node(id).unwrap().components.add_direct(mode);
+            let original = save(&tree, format);
+
+            // neither is registered, so both are kept as they were saved
+            let empty = Components::new();
+            let loaded = load(&original, format, &empty);
+            assert_eq!(loaded.get_node(id).unwrap().components.opaque().count(), 2);
+
+            let saved = save(&loaded, format);
+            let resaved = save(&load(&saved, format, &empty), format);
+            assert_eq!(saved, resaved);
+
+            // and load once they're registered
+            let tree = load(&resaved, format, &components());
+            let node = tree.get_node(id).unwrap();
+            assert_eq!(**node.get_component::<Light>().unwrap(), Light::default());
+            let expected = Mode::Pulse { from: 0.5, to: 1.0 };
+            assert_eq!(**node.get_component::<Mode>().unwrap(), expected);
+            assert_eq!(node.components.opaque().count(), 0);
+        }
+    }
+
+    #[test]
+    fn keeps_components_that_fail_to_load() {
+        let mut tree = Tree::new();
+        let id = spawn(&mut tree, Mode::Blink(0.25));
+        let text = String::from_utf8(save(&tree, SceneFormat::Ron)).unwrap();
+        let text = text.replace("Blink(0.25)", "Blink(\"fast\")");
+
+        let components = components();
+        let tree = load(text.as_bytes(), SceneFormat::Ron, &components);
+        let node = tree.get_node(id).unwrap();
+        assert!(node.get_component::<Mode>().is_none());
+
+        let (name, opaque) = node.components.opaque().next().unwrap();
+        assert_eq!(name, Mode::long_name_const());
+        assert_eq!(opaque.data, Value::from_ron("Blink(\"fast\")").unwrap());
+    }
+}
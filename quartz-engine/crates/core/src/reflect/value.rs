@@ -0,0 +1,1542 @@
+use ron::{
+    error::{ErrorCode, Position},
+    Error,
+};
+use serde::{
+    de::{
+        self, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+        VariantAccess, Visitor,
+    },
+    ser::{
+        self, Serialize, SerializeStructVariant, SerializeTuple, SerializeTupleVariant, Serializer,
+    },
+    Deserialize,
+};
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// Serialized data read without knowing its type, used for components that can't be loaded,
+/// prefab overrides and migrations.
+///
+/// Unlike [`ron::Value`] it keeps the names of structs and enum variants, so enums can be read
+/// back from it. Binary formats can't tell structs and enum variants apart, so it's stored as
+/// ron text in them.
+///
+/// Names are only kept when reading from a [`Value`], ron text has to be parsed with
+/// [`Value::from_ron`] rather than read through a ron deserializer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    Option(Option<Box<Value>>),
+    /// `[a, b]` in ron.
+    Seq(Vec<Value>),
+    /// `(a, b)` in ron, a tuple, tuple struct or the contents of a newtype.
+    Tuple(Vec<Value>),
+    /// `{ key: value }` in ron, or the fields of a struct.
+    Map(Vec<(Value, Value)>),
+    /// A named struct or enum variant, `Name`, `Name(a)` or `Name(field: a)` in ron.
+    Named(String, Box<Value>),
+}
+
+impl Value {
+    /// Parses ron text.
+    pub fn from_ron(text: &str) -> Result<Self, Error> {
+        let mut parser = RonParser { text, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace()?;
+
+        if parser.pos < text.len() {
+            return Err(parser.error(ErrorCode::TrailingCharacters));
+        }
+
+        Ok(value)
+    }
+
+    /// Converts `value` into a [`Value`], the same one parsing it from ron would give.
+    pub fn from_serialize<T: Serialize + ?Sized>(value: &T) -> Result<Self, Error> {
+        value.serialize(ValueSerializer)
+    }
+
+    /// Fields of a struct or entries of a map, [`None`] for other values.
+    pub fn fields(&self) -> Option<&Vec<(Value, Value)>> {
+        match self {
+            Self::Named(_, value) => value.fields(),
+            Self::Map(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    pub fn fields_mut(&mut self) -> Option<&mut Vec<(Value, Value)>> {
+        match self {
+            Self::Named(_, value) => value.fields_mut(),
+            Self::Map(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.fields()?
+            .iter()
+            .find(|(key, _)| key.as_str() == Some(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.fields_mut()?
+            .iter_mut()
+            .find(|(key, _)| key.as_str() == Some(name))
+            .map(|(_, value)| value)
+    }
+
+    /// Removes a field from a struct, eg. in a migration.
+    pub fn remove_field(&mut self, name: &str) -> Option<Value> {
+        let fields = self.fields_mut()?;
+        let index = fields
+            .iter()
+            .position(|(key, _)| key.as_str() == Some(name))?;
+
+        Some(fields.remove(index).1)
+    }
+
+    /// Sets a field of a struct, adding it if it doesn't exist yet.
+    pub fn insert_field(&mut self, name: &str, value: Value) {
+        if let Some(field) = self.field_mut(name) {
+            *field = value;
+        } else if let Some(fields) = self.fields_mut() {
+            fields.push((Value::String(String::from(name)), value));
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Any number as an `f64`, ron writes whole floats without a fraction so they're read
+    /// as integers.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::I64(value) => Some(value as f64),
+            Self::U64(value) => Some(value as f64),
+            Self::F64(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn unnamed(self) -> Self {
+        match self {
+            Self::Named(_, value) => *value,
+            value => value,
+        }
+    }
+
+    fn unexpected(&self) -> de::Unexpected<'_> {
+        match self {
+            Self::Unit => de::Unexpected::Unit,
+            Self::Bool(value) => de::Unexpected::Bool(*value),
+            Self::I64(value) => de::Unexpected::Signed(*value),
+            Self::U64(value) => de::Unexpected::Unsigned(*value),
+            Self::F64(value) => de::Unexpected::Float(*value),
+            Self::Char(value) => de::Unexpected::Char(*value),
+            Self::String(value) => de::Unexpected::Str(value),
+            Self::Bytes(value) => de::Unexpected::Bytes(value),
+            Self::Option(_) => de::Unexpected::Option,
+            Self::Seq(_) | Self::Tuple(_) => de::Unexpected::Seq,
+            Self::Map(_) => de::Unexpected::Map,
+            Self::Named(_, _) => de::Unexpected::Other("named value"),
+        }
+    }
+}
+
+/// Serializers take names as `&'static str`, so the names of values are leaked, once per
+/// distinct name.
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let mut names = NAMES.lock().unwrap();
+
+    if let Some(name) = names.get(name) {
+        return name;
+    }
+
+    let name = Box::leak(String::from(name).into_boxed_str());
+    names.insert(name);
+
+    name
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            let text = ron::to_string(self).map_err(ser::Error::custom)?;
+            return serializer.serialize_str(&text);
+        }
+
+        match self {
+            Self::Unit => serializer.serialize_unit(),
+            Self::Bool(value) => serializer.serialize_bool(*value),
+            Self::I64(value) => serializer.serialize_i64(*value),
+            Self::U64(value) => serializer.serialize_u64(*value),
+            Self::F64(value) => serializer.serialize_f64(*value),
+            Self::Char(value) => serializer.serialize_char(*value),
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Bytes(value) => serializer.serialize_bytes(value),
+            Self::Option(None) => serializer.serialize_none(),
+            Self::Option(Some(value)) => serializer.serialize_some(value.as_ref()),
+            Self::Seq(values) => serializer.collect_seq(values),
+            Self::Tuple(values) => {
+                let mut state = serializer.serialize_tuple(values.len())?;
+
+                for value in values {
+                    state.serialize_element(value)?;
+                }
+
+                state.end()
+            }
+            Self::Map(map) => serializer.collect_map(map.iter().map(|(key, value)| (key, value))),
+            // structs are written as enum variants, which formats that keep names write the
+            // same way
+            Self::Named(name, value) => {
+                let name = intern(name);
+
+                match value.as_ref() {
+                    Self::Unit => serializer.serialize_unit_variant("", 0, name),
+                    Self::Tuple(values) if values.len() == 1 => {
+                        serializer.serialize_newtype_variant("", 0, name, &values[0])
+                    }
+                    Self::Tuple(values) => {
+                        let mut state =
+                            serializer.serialize_tuple_variant("", 0, name, values.len())?;
+
+                        for value in values {
+                            state.serialize_field(value)?;
+                        }
+
+                        state.end()
+                    }
+                    Self::Map(fields) if fields.iter().all(|(key, _)| key.as_str().is_some()) => {
+                        let mut state =
+                            serializer.serialize_struct_variant("", 0, name, fields.len())?;
+
+                        for (key, value) in fields {
+                            state.serialize_field(intern(key.as_str().unwrap()), value)?;
+                        }
+
+                        state.end()
+                    }
+                    value => serializer.serialize_newtype_variant("", 0, name, value),
+                }
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            ValueSeed.deserialize(deserializer)
+        } else {
+            let text = String::deserialize(deserializer)?;
+            Value::from_ron(&text).map_err(de::Error::custom)
+        }
+    }
+}
+
+/// Name of the newtype a [`Value`] deserializer recognizes, to hand itself over as it is
+/// instead of through `deserialize_any`, which loses names and tuples.
+const TOKEN: &str = "$quartz::private::Value";
+
+/// Reads a [`Value`] from any format, names are only kept when reading from a [`Value`].
+pub(crate) struct ValueSeed;
+
+impl<'de> DeserializeSeed<'de> for ValueSeed {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_newtype_struct(TOKEN, ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::I64(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::U64(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::F64(v))
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<Value, E> {
+        Ok(Value::Char(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(String::from(v)))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(v))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Option(None))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        let value = ValueSeed.deserialize(deserializer)?;
+
+        Ok(Value::Option(Some(Box::new(value))))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    // formats other than `Value` don't know the token
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+        while let Some(value) = seq.next_element_seed(ValueSeed)? {
+            values.push(value);
+        }
+
+        Ok(Value::Seq(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+
+        while let Some(entry) = map.next_entry_seed(ValueSeed, ValueSeed)? {
+            entries.push(entry);
+        }
+
+        Ok(Value::Map(entries))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Value, A::Error> {
+        let (kind, variant) = data.variant::<String>()?;
+
+        variant.tuple_variant(
+            0,
+            ChannelVisitor {
+                named: kind == "Named",
+            },
+        )
+    }
+}
+
+/// Hands the values `deserialize_any` can't describe over to [`ValueVisitor`], as an enum.
+enum Channel {
+    Named(String, Value),
+    Tuple(Vec<Value>),
+}
+
+impl<'de> EnumAccess<'de> for Channel {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let kind = match self {
+            Self::Named(_, _) => "Named",
+            Self::Tuple(_) => "Tuple",
+        };
+
+        let kind: de::value::StrDeserializer<Error> = kind.into_deserializer();
+
+        Ok((seed.deserialize(kind)?, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Channel {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, _seed: T) -> Result<T::Value, Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        let values = match self {
+            Self::Named(name, value) => vec![Value::String(name), value],
+            Self::Tuple(values) => values,
+        };
+
+        let mut seq = de::value::SeqDeserializer::new(values.into_iter());
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+
+        Ok(value)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::StructVariant,
+            &"tuple variant",
+        ))
+    }
+}
+
+struct ChannelVisitor {
+    named: bool,
+}
+
+impl<'de> Visitor<'de> for ChannelVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("named value or tuple")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        if self.named {
+            let name = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+            let value = seq
+                .next_element_seed(ValueSeed)?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+            Ok(Value::Named(name, Box::new(value)))
+        } else {
+            let mut values = Vec::new();
+
+            while let Some(value) = seq.next_element_seed(ValueSeed)? {
+                values.push(value);
+            }
+
+            Ok(Value::Tuple(values))
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Self::Unit => visitor.visit_unit(),
+            Self::Bool(value) => visitor.visit_bool(value),
+            Self::I64(value) => visitor.visit_i64(value),
+            Self::U64(value) => visitor.visit_u64(value),
+            Self::F64(value) => visitor.visit_f64(value),
+            Self::Char(value) => visitor.visit_char(value),
+            Self::String(value) => visitor.visit_string(value),
+            Self::Bytes(value) => visitor.visit_byte_buf(value),
+            Self::Option(None) => visitor.visit_none(),
+            Self::Option(Some(value)) => visitor.visit_some(*value),
+            Self::Seq(values) | Self::Tuple(values) => {
+                let mut seq = de::value::SeqDeserializer::new(values.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+
+                Ok(value)
+            }
+            Self::Map(map) => {
+                let mut map = de::value::MapDeserializer::new(map.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+
+                Ok(value)
+            }
+            Self::Named(_, value) => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Self::Option(None) | Self::Unit => visitor.visit_none(),
+            Self::Option(Some(value)) => visitor.visit_some(*value),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if name == TOKEN {
+            return match self {
+                Self::Named(name, value) => visitor.visit_enum(Channel::Named(name, *value)),
+                Self::Tuple(values) => visitor.visit_enum(Channel::Tuple(values)),
+                value => value.deserialize_any(visitor),
+            };
+        }
+
+        match self.unnamed() {
+            Self::Tuple(mut values) if values.len() == 1 => {
+                visitor.visit_newtype_struct(values.pop().unwrap())
+            }
+            value => visitor.visit_newtype_struct(value),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.unnamed() {
+            // `Name()` is read as a unit
+            Self::Unit => Self::Map(Vec::new()).deserialize_any(visitor),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (variant, value) = match self {
+            Self::Named(variant, value) => (variant, Some(*value)),
+            Self::String(variant) => (variant, None),
+            Self::Map(mut map) if map.len() == 1 => match map.pop().unwrap() {
+                (Self::String(variant), value) => (variant, Some(value)),
+                (key, _) => return Err(de::Error::invalid_type(key.unexpected(), &"variant")),
+            },
+            value => return Err(de::Error::invalid_type(value.unexpected(), &"enum")),
+        };
+
+        visitor.visit_enum(EnumDeserializer { variant, value })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<Value>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer), Error> {
+        let variant: de::value::StringDeserializer<Error> = self.variant.into_deserializer();
+        let variant = seed.deserialize(variant)?;
+
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<Value>,
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None | Some(Value::Unit) => Ok(()),
+            Some(value) => Err(de::Error::invalid_type(value.unexpected(), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        match self.value {
+            Some(Value::Tuple(mut values)) if values.len() == 1 => {
+                seed.deserialize(values.pop().unwrap())
+            }
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Some(value) => value.deserialize_any(visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            Some(value) => value.deserialize_struct("", fields, visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+/// Serializes into a [`Value`], keeping the names of structs like ron with `struct_names` does.
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(Value::I64(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(Value::I64(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(Value::I64(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(Value::I64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(Value::I64(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(Value::I64(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(Value::I64(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(number(&v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        Ok(number(&v.to_string()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(number(&v.to_string()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::String(String::from(v)))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Option(None))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        Ok(Value::Option(Some(Box::new(value.serialize(self)?))))
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Named(String::from(name), Box::new(Value::Unit)))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        self.serialize_unit_struct(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        let value = Value::Tuple(vec![value.serialize(self)?]);
+
+        Ok(Value::Named(String::from(name), Box::new(value)))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        self.serialize_newtype_struct(variant, value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(None, false, len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(None, true, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(Some(name), true, len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(Some(variant), true, len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer::new(None, len.unwrap_or(0)))
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer::new(Some(name), len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer::new(Some(variant), len))
+    }
+}
+
+/// Reads a number the way [`RonParser`] reads what ron writes for it, so eg. `1.0` is an
+/// integer and `0.1f32` stays `0.1`.
+fn number(text: &str) -> Value {
+    if let Ok(value) = text.parse() {
+        Value::I64(value)
+    } else if let Ok(value) = text.parse() {
+        Value::U64(value)
+    } else {
+        Value::F64(text.parse().unwrap_or(f64::NAN))
+    }
+}
+
+fn named(name: Option<&'static str>, value: Value) -> Value {
+    match name {
+        Some(name) => Value::Named(String::from(name), Box::new(value)),
+        None => value,
+    }
+}
+
+struct SeqSerializer {
+    name: Option<&'static str>,
+    tuple: bool,
+    values: Vec<Value>,
+}
+
+impl SeqSerializer {
+    fn new(name: Option<&'static str>, tuple: bool, len: usize) -> Self {
+        Self {
+            name,
+            tuple,
+            values: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.values.push(value.serialize(ValueSerializer)?);
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        let value = if self.tuple {
+            Value::Tuple(self.values)
+        } else {
+            Value::Seq(self.values)
+        };
+
+        Ok(named(self.name, value))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+struct MapSerializer {
+    name: Option<&'static str>,
+    entries: Vec<(Value, Value)>,
+    key: Option<Value>,
+}
+
+impl MapSerializer {
+    fn new(name: Option<&'static str>, len: usize) -> Self {
+        Self {
+            name,
+            entries: Vec::with_capacity(len),
+            key: None,
+        }
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        let value = value.serialize(ValueSerializer)?;
+        self.entries.push((Value::String(String::from(key)), value));
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        Ok(named(self.name, Value::Map(self.entries)))
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(ValueSerializer)?);
+
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| <Error as ser::Error>::custom("map value without a key"))?;
+        self.entries.push((key, value.serialize(ValueSerializer)?));
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+/// Parses ron into a [`Value`], strings, chars and numbers are read by ron itself.
+struct RonParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> RonParser<'a> {
+    fn error(&self, code: ErrorCode) -> Error {
+        let before = self.text.get(..self.pos).unwrap_or(self.text);
+        let line = before.matches('\n').count() + 1;
+        let col = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+
+        Error {
+            code,
+            position: Position { line, col },
+        }
+    }
+
+    fn message(&self, message: &str) -> Error {
+        self.error(ErrorCode::Message(String::from(message)))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).cloned()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.text.as_bytes().get(self.pos + offset).cloned()
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), Error> {
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some(c), _) if c.is_ascii_whitespace() => self.pos += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while !matches!(self.peek(), Some(b'\n') | None) {
+                        self.pos += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    let mut depth = 0;
+
+                    loop {
+                        match (self.peek(), self.peek_at(1)) {
+                            (Some(b'/'), Some(b'*')) => {
+                                depth += 1;
+                                self.pos += 2;
+                            }
+                            (Some(b'*'), Some(b'/')) => {
+                                depth -= 1;
+                                self.pos += 2;
+
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            (Some(_), _) => self.pos += 1,
+                            (None, _) => return Err(self.error(ErrorCode::UnclosedBlockComment)),
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Skips whitespace, then consumes `c` if it's next.
+    fn eat(&mut self, c: u8) -> Result<bool, Error> {
+        self.skip_whitespace()?;
+
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), Error> {
+        if self.eat(c)? {
+            Ok(())
+        } else if self.peek().is_none() {
+            Err(self.error(ErrorCode::Eof))
+        } else {
+            Err(self.message(&format!("expected '{}'", c as char)))
+        }
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let bytes = self.text.as_bytes();
+        let start = self.pos;
+        let mut end = start;
+
+        if bytes[start..].starts_with(b"r#") {
+            end += 2;
+        }
+
+        if !matches!(bytes.get(end), Some(c) if c.is_ascii_alphabetic() || *c == b'_') {
+            return None;
+        }
+
+        while matches!(bytes.get(end), Some(c) if c.is_ascii_alphanumeric() || *c == b'_') {
+            end += 1;
+        }
+
+        self.pos = end;
+        Some(self.text[start..end].trim_start_matches("r#"))
+    }
+
+    /// Length of the string or char literal starting at `pos`, quoted with `quote`.
+    fn quoted_len(&self, quote: u8) -> Result<usize, Error> {
+        let bytes = &self.text.as_bytes()[self.pos..];
+        let mut i = 1;
+
+        loop {
+            match bytes.get(i) {
+                Some(b'\\') => i += 2,
+                Some(c) if *c == quote => return Ok(i + 1),
+                Some(_) => i += 1,
+                None => return Err(self.error(ErrorCode::ExpectedStringEnd)),
+            }
+        }
+    }
+
+    /// Length of the raw string starting at `pos`, eg. `r#"text"#`.
+    fn raw_string_len(&self) -> Option<usize> {
+        let bytes = &self.text.as_bytes()[self.pos..];
+        let hashes = bytes[1..].iter().take_while(|c| **c == b'#').count();
+
+        if bytes.get(hashes + 1) != Some(&b'"') {
+            return None;
+        }
+
+        let end = format!("\"{}", "#".repeat(hashes));
+
+        let body = hashes + 2;
+        bytes[body..]
+            .windows(end.len())
+            .position(|window| window == end.as_bytes())
+            .map(|i| body + i + end.len())
+    }
+
+    fn literal<T: serde::de::DeserializeOwned>(&mut self, len: usize) -> Result<T, Error> {
+        let literal = &self.text[self.pos..self.pos + len];
+        let value = ron::from_str(literal).map_err(|err| self.error(err.code))?;
+        self.pos += len;
+
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Value, Error> {
+        let len = self.text[self.pos..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '+' | '-')))
+            .unwrap_or(self.text.len() - self.pos);
+        let literal = &self.text[self.pos..self.pos + len];
+
+        let value = if literal == "-inf" {
+            Value::F64(f64::NEG_INFINITY)
+        } else if let Ok(value) = ron::from_str(literal) {
+            Value::I64(value)
+        } else if let Ok(value) = ron::from_str(literal) {
+            Value::U64(value)
+        } else {
+            Value::F64(ron::from_str(literal).map_err(|err| self.error(err.code))?)
+        };
+
+        self.pos += len;
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        self.skip_whitespace()?;
+
+        match self.peek() {
+            Some(b'"') => Ok(Value::String(self.literal(self.quoted_len(b'"')?)?)),
+            Some(b'\'') => Ok(Value::Char(self.literal(self.quoted_len(b'\'')?)?)),
+            Some(b'r') if self.raw_string_len().is_some() => {
+                Ok(Value::String(self.literal(self.raw_string_len().unwrap())?))
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+
+                while !self.eat(b']')? {
+                    values.push(self.value()?);
+
+                    if !self.eat(b',')? {
+                        self.expect(b']')?;
+                        break;
+                    }
+                }
+
+                Ok(Value::Seq(values))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut map = Vec::new();
+
+                while !self.eat(b'}')? {
+                    let key = self.value()?;
+                    self.expect(b':')?;
+                    map.push((key, self.value()?));
+
+                    if !self.eat(b',')? {
+                        self.expect(b'}')?;
+                        break;
+                    }
+                }
+
+                Ok(Value::Map(map))
+            }
+            Some(b'(') => {
+                self.pos += 1;
+                self.parenthesized()
+            }
+            Some(c) if c.is_ascii_digit() || matches!(c, b'+' | b'-' | b'.') => self.number(),
+            Some(c) => {
+                let identifier = self
+                    .identifier()
+                    .ok_or_else(|| self.error(ErrorCode::UnexpectedByte(c as char)))?;
+
+                match identifier {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "inf" => Ok(Value::F64(f64::INFINITY)),
+                    "NaN" => Ok(Value::F64(f64::NAN)),
+                    "None" => Ok(Value::Option(None)),
+                    "Some" => {
+                        self.expect(b'(')?;
+                        let value = self.value()?;
+                        self.eat(b',')?;
+                        self.expect(b')')?;
+
+                        Ok(Value::Option(Some(Box::new(value))))
+                    }
+                    name => {
+                        let value = if self.eat(b'(')? {
+                            self.parenthesized()?
+                        } else {
+                            Value::Unit
+                        };
+
+                        Ok(Value::Named(String::from(name), Box::new(value)))
+                    }
+                }
+            }
+            None => Err(self.error(ErrorCode::Eof)),
+        }
+    }
+
+    /// Parses struct fields or tuple elements, after the opening parenthesis.
+    fn parenthesized(&mut self) -> Result<Value, Error> {
+        if self.eat(b')')? {
+            return Ok(Value::Unit);
+        }
+
+        let start = self.pos;
+        let is_struct = self.identifier().is_some() && self.eat(b':')? && self.peek() != Some(b':');
+        self.pos = start;
+
+        let mut fields = Vec::new();
+        let mut values = Vec::new();
+
+        while !self.eat(b')')? {
+            if is_struct {
+                self.skip_whitespace()?;
+                let name = self
+                    .identifier()
+                    .ok_or_else(|| self.error(ErrorCode::ExpectedIdentifier))?;
+                self.expect(b':')?;
+                fields.push((Value::String(String::from(name)), self.value()?));
+            } else {
+                values.push(self.value()?);
+            }
+
+            if !self.eat(b',')? {
+                self.expect(b')')?;
+                break;
+            }
+        }
+
+        if is_struct {
+            Ok(Value::Map(fields))
+        } else {
+            Ok(Value::Tuple(values))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    fn string(value: &str) -> Value {
+        Value::String(String::from(value))
+    }
+
+    fn named(name: &str, value: Value) -> Value {
+        Value::Named(String::from(name), Box::new(value))
+    }
+
+    fn fields(fields: &[(&str, Value)]) -> Value {
+        Value::Map(
+            fields
+                .iter()
+                .map(|(name, value)| (string(name), value.clone()))
+                .collect(),
+        )
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f32),
+        Rect(f32, f32),
+        Polygon { name: String, sides: u32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Pair(i32, String);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Marker;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Id(u64);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Data {
+        shapes: Vec<Shape>,
+        pair: Pair,
+        marker: Marker,
+        id: Id,
+        text: String,
+        letter: char,
+        maybe: Option<Shape>,
+        nothing: Option<u8>,
+        map: BTreeMap<String, (u8, i64)>,
+        nested: Vec<Vec<f64>>,
+        scale: f32,
+    }
+
+    fn data() -> Data {
+        let mut map = BTreeMap::new();
+        map.insert(String::from("a.b"), (1, -2));
+        map.insert(String::from("ü"), (255, i64::MIN));
+
+        Data {
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(0.5),
+                Shape::Rect(1.0, -2.25),
+                Shape::Polygon {
+                    name: String::from("hex (\"six\")"),
+                    sides: 6,
+                },
+            ],
+            pair: Pair(-7, String::from("tab\there")),
+            marker: Marker,
+            id: Id(u64::MAX),
+            text: String::from("ends with ) and \\ and 🌍"),
+            letter: '\'',
+            maybe: Some(Shape::Circle(1.0)),
+            nothing: None,
+            map,
+            nested: vec![vec![], vec![0.1, 1e20, f64::INFINITY, f64::NEG_INFINITY]],
+            scale: 0.1,
+        }
+    }
+
+    #[test]
+    fn parses_nested_structs() {
+        let value = Value::from_ron("Outer(inner: Inner(x: 1, y: -2.5), list: [1, 2,],)").unwrap();
+
+        let inner = named(
+            "Inner",
+            fields(&[("x", Value::I64(1)), ("y", Value::F64(-2.5))]),
+        );
+        let list = Value::Seq(vec![Value::I64(1), Value::I64(2)]);
+        assert_eq!(
+            value,
+            named("Outer", fields(&[("inner", inner), ("list", list)]))
+        );
+    }
+
+    #[test]
+    fn parses_enums_and_tuples() {
+        let value = Value::from_ron("[A, B(1), C(1, \"c\"), D(d: true), (1, 2), ()]").unwrap();
+
+        assert_eq!(
+            value,
+            Value::Seq(vec![
+                named("A", Value::Unit),
+                named("B", Value::Tuple(vec![Value::I64(1)])),
+                named("C", Value::Tuple(vec![Value::I64(1), string("c")])),
+                named("D", fields(&[("d", Value::Bool(true))])),
+                Value::Tuple(vec![Value::I64(1), Value::I64(2)]),
+                Value::Unit,
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_options_and_maps() {
+        let value = Value::from_ron("{ \"a\": Some(1), 2: None, 'c': r#type }").unwrap();
+
+        assert_eq!(
+            value,
+            Value::Map(vec![
+                (string("a"), Value::Option(Some(Box::new(Value::I64(1))))),
+                (Value::I64(2), Value::Option(None)),
+                (Value::Char('c'), named("type", Value::Unit)),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_strings() {
+        let text =
+            r###"["a \"quoted\" ) \\ \n", '\'', 'é', "\u{e9}", "héllo 🌍", r##"raw "# )"##]"###;
+        let value = Value::from_ron(text).unwrap();
+
+        assert_eq!(
+            value,
+            Value::Seq(vec![
+                string("a \"quoted\" ) \\ \n"),
+                Value::Char('\''),
+                Value::Char('é'),
+                string("é"),
+                string("héllo 🌍"),
+                string("raw \"# )"),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_numbers() {
+        let value = Value::from_ron("[1, -1, 18446744073709551615, 1.5, 1e3, inf, -inf]").unwrap();
+
+        assert_eq!(
+            value,
+            Value::Seq(vec![
+                Value::I64(1),
+                Value::I64(-1),
+                Value::U64(u64::MAX),
+                Value::F64(1.5),
+                Value::F64(1000.0),
+                Value::F64(f64::INFINITY),
+                Value::F64(f64::NEG_INFINITY),
+            ])
+        );
+
+        match Value::from_ron("NaN").unwrap() {
+            Value::F64(value) => assert!(value.is_nan()),
+            value => panic!("expected NaN, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn skips_comments() {
+        let text = "// leading\n( /* nested /* ) */ */ a: 1, // trailing )\n b: 2 )";
+        let value = Value::from_ron(text).unwrap();
+
+        assert_eq!(value, fields(&[("a", Value::I64(1)), ("b", Value::I64(2))]));
+    }
+
+    #[test]
+    fn reports_errors() {
+        let err = Value::from_ron("(\n  a: \"unclosed)").unwrap_err();
+        assert_eq!(err.code, ErrorCode::ExpectedStringEnd);
+        assert_eq!(err.position, Position { line: 2, col: 6 });
+
+        let err = Value::from_ron("(a: 1) x").unwrap_err();
+        assert_eq!(err.code, ErrorCode::TrailingCharacters);
+
+        let err = Value::from_ron("[1, 2").unwrap_err();
+        assert_eq!(err.code, ErrorCode::Eof);
+
+        let err = Value::from_ron("/* open").unwrap_err();
+        assert_eq!(err.code, ErrorCode::UnclosedBlockComment);
+
+        assert!(Value::from_ron("(a: 1 b: 2)").is_err());
+        assert!(Value::from_ron("#").is_err());
+    }
+
+    #[test]
+    fn round_trips_ron() {
+        let data = data();
+
+        let config = ron::ser::PrettyConfig::default();
+        let mut text = Vec::new();
+        let mut serializer = ron::Serializer::new(&mut text, Some(config.clone()), true).unwrap();
+        data.serialize(&mut serializer).unwrap();
+        let text = String::from_utf8(text).unwrap();
+
+        let value = Value::from_ron(&text).unwrap();
+        assert_eq!(Value::from_serialize(&data).unwrap(), value);
+
+        // writing the value gives back the same text
+        let mut written = Vec::new();
+        let mut serializer = ron::Serializer::new(&mut written, Some(config), true).unwrap();
+        value.serialize(&mut serializer).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), text);
+
+        assert_eq!(Data::deserialize(value.clone()).unwrap(), data);
+
+        // and reading it as a value keeps the names
+        assert_eq!(Value::deserialize(value.clone()).unwrap(), value);
+    }
+
+    #[test]
+    fn round_trips_cbor() {
+        let data = data();
+        let value = Value::from_serialize(&data).unwrap();
+
+        // binary formats store values as ron text
+        let bytes = serde_cbor::to_vec(&value).unwrap();
+        let read: Value = serde_cbor::from_slice(&bytes).unwrap();
+        assert_eq!(Data::deserialize(read).unwrap(), data);
+
+        // and values read from them lose names, but can still be read back
+        let bytes = serde_cbor::to_vec(&data).unwrap();
+        let mut deserializer = serde_cbor::Deserializer::from_slice(&bytes);
+        let read = ValueSeed.deserialize(&mut deserializer).unwrap();
+        assert_eq!(Data::deserialize(read).unwrap(), data);
+    }
+
+    #[test]
+    fn edits_fields() {
+        let mut value = Value::from_ron("Light(color: (1, 1, 1), area: 2)").unwrap();
+
+        assert_eq!(value.remove_field("area"), Some(Value::I64(2)));
+        value.insert_field("intensity", Value::F64(0.5));
+        value.insert_field("color", Value::Unit);
+
+        assert_eq!(
+            value,
+            named(
+                "Light",
+                fields(&[("color", Value::Unit), ("intensity", Value::F64(0.5))])
+            )
+        );
+        assert_eq!(value.field("intensity"), Some(&Value::F64(0.5)));
+        assert_eq!(Value::I64(1).field("intensity"), None);
+    }
+}
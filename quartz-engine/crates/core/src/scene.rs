@@ -1,7 +1,262 @@
 use crate::plugin::*;
+use crate::reflect::Value;
 use crate::tree::*;
+use ::serde::{de::DeserializeSeed, Serialize};
+use std::path::Path;
+
+/// Tag written at the start of binary scenes, see [`SceneFormat::detect`].
+const CBOR_HEADER: [u8; 3] = [0xd9, 0xd9, 0xf7];
 
 pub struct Scene<'a> {
     pub plugins: &'a Plugins,
     pub tree: &'a Tree,
 }
+
+#[derive(Debug)]
+pub enum SceneError {
+    Ron(ron::Error),
+    Cbor(serde_cbor::Error),
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ron(err) => write!(f, "{}", err),
+            Self::Cbor(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<ron::Error> for SceneError {
+    fn from(err: ron::Error) -> Self {
+        Self::Ron(err)
+    }
+}
+
+impl From<serde_cbor::Error> for SceneError {
+    fn from(err: serde_cbor::Error) -> Self {
+        Self::Cbor(err)
+    }
+}
+
+/// The format a scene is stored in.
+///
+/// Ron is readable and diffs well, Cbor is smaller and faster to load, and is meant for
+/// shipped builds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SceneFormat {
+    Ron,
+    Cbor,
+}
+
+impl Default for SceneFormat {
+    fn default() -> Self {
+        Self::Ron
+    }
+}
+
+impl SceneFormat {
+    pub const ALL: [SceneFormat; 2] = [SceneFormat::Ron, SceneFormat::Cbor];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ron => "RON",
+            Self::Cbor => "CBOR",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Ron => "scn",
+            Self::Cbor => "scnb",
+        }
+    }
+
+    pub fn from_extension(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?;
+
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| extension == format.extension())
+    }
+
+    /// Detects the format of `data`, binary scenes are recognized by their header, and
+    /// otherwise the extension of `path` is used, falling back to [`SceneFormat::Ron`].
+    pub fn detect(path: impl AsRef<Path>, data: &[u8]) -> Self {
+        if data.starts_with(&CBOR_HEADER) {
+            Self::Cbor
+        } else {
+            Self::from_extension(path).unwrap_or_default()
+        }
+    }
+
+    pub fn serialize(self, value: &impl Serialize) -> Result<Vec<u8>, SceneError> {
+        let mut data = Vec::new();
+
+        match self {
+            Self::Ron => {
+                let config = ron::ser::PrettyConfig::default();
+                let mut serializer = ron::Serializer::new(&mut data, Some(config), true)?;
+                value.serialize(&mut serializer)?;
+            }
+            Self::Cbor => {
+                let mut serializer = serde_cbor::Serializer::new(&mut data);
+                serializer.self_describe()?;
+                value.serialize(&mut serializer)?;
+            }
+        }
+
+        Ok(data)
+    }
+
+    pub fn deserialize_seed<'de, T: DeserializeSeed<'de>>(
+        self,
+        data: &'de [u8],
+        seed: T,
+    ) -> Result<T::Value, SceneError> {
+        match self {
+            // ron can't read enums without knowing their type, so the text is parsed into a
+            // `Value` first, which lets components be buffered before they're loaded
+            Self::Ron => {
+                let text = std::str::from_utf8(data).map_err(|err| ron::Error {
+                    code: ron::error::ErrorCode::Utf8Error(err),
+                    position: ron::error::Position { line: 0, col: 0 },
+                })?;
+
+                Ok(seed.deserialize(Value::from_ron(text)?)?)
+            }
+            Self::Cbor => {
+                // the header is only there for `detect`, and serde_cbor reads enums behind a
+                // tag as unit variants
+                let data = data.strip_prefix(&CBOR_HEADER).unwrap_or(data);
+                let mut deserializer = serde_cbor::Deserializer::from_slice(data);
+                let value = seed.deserialize(&mut deserializer)?;
+                deserializer.end()?;
+
+                Ok(value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::*;
+    use crate::quartz_engine;
+    use crate::reflect::serde::SceneDeserializer;
+    use crate::reflect::Reflect;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    enum Mode {
+        #[default]
+        Off,
+        Blink(f32),
+        Pulse {
+            from: f32,
+            to: f32,
+        },
+    }
+
+    impl Component for Mode {
+        type Plugins = ();
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    struct Label {
+        text: String,
+    }
+
+    impl Component for Label {
+        type Plugins = ();
+    }
+
+    fn save(tree: &Tree, format: SceneFormat) -> Vec<u8> {
+        let plugins = Plugins::new();
+        let scene = Scene {
+            plugins: &plugins,
+            tree,
+        };
+
+        format.serialize(&scene).unwrap()
+    }
+
+    fn load(data: &[u8], format: SceneFormat, components: &Components) -> Tree {
+        let mut plugins = Plugins::new();
+        let seed = SceneDeserializer {
+            components,
+            plugins: &mut plugins,
+        };
+
+        format.deserialize_seed(data, seed).unwrap()
+    }
+
+    #[test]
+    fn detects_format() {
+        let tree = Tree::new();
+        let ron = save(&tree, SceneFormat::Ron);
+        let cbor = save(&tree, SceneFormat::Cbor);
+
+        // the cbor header wins over the extension
+        assert_eq!(SceneFormat::detect("scene.scn", &cbor), SceneFormat::Cbor);
+        assert_eq!(SceneFormat::detect("scene", &cbor), SceneFormat::Cbor);
+        assert_eq!(SceneFormat::detect("scene.scn", &ron), SceneFormat::Ron);
+        assert_eq!(SceneFormat::detect("scene", &ron), SceneFormat::Ron);
+        assert_eq!(SceneFormat::detect("scene.scnb", &[]), SceneFormat::Cbor);
+        assert_eq!(SceneFormat::detect("scene.txt", &[]), SceneFormat::Ron);
+    }
+
+    #[test]
+    fn converts_ron_to_cbor_and_back() {
+        let mut tree = Tree::new();
+        let id = tree.spawn();
+        {
+            let mut node = tree.get_node(id).unwrap();
+            node.name = String::from("lamp");
+            node.components
+                .add_direct(Mode::Pulse { from: 0.5, to: 1.0 });
+            node.components.add_direct(Label {
+                text: String::from("\"quoted\" (and) ünïcode"),
+            });
+        }
+
+        // `Label` isn't registered, so it's carried through as an opaque component
+        let mut components = Components::new();
+        components.register_component::<Mode>();
+
+        let ron = save(&tree, SceneFormat::Ron);
+        let ron = save(&load(&ron, SceneFormat::Ron, &components), SceneFormat::Ron);
+        let cbor = save(
+            &load(&ron, SceneFormat::Ron, &components),
+            SceneFormat::Cbor,
+        );
+        assert!(cbor.starts_with(&CBOR_HEADER));
+
+        let tree = load(&cbor, SceneFormat::Cbor, &components);
+        assert_eq!(save(&tree, SceneFormat::Ron), ron);
+
+        let node = tree.get_node(id).unwrap();
+        assert_eq!(node.name, "lamp");
+        let expected = Mode::Pulse { from: 0.5, to: 1.0 };
+        assert_eq!(**node.get_component::<Mode>().unwrap(), expected);
+        assert_eq!(node.components.opaque().count(), 1);
+    }
+
+    #[test]
+    fn rejects_trailing_data() {
+        let mut cbor = save(&Tree::new(), SceneFormat::Cbor);
+        cbor.push(0);
+
+        let mut plugins = Plugins::new();
+        let seed = SceneDeserializer {
+            components: &Components::new(),
+            plugins: &mut plugins,
+        };
+
+        let result = SceneFormat::Cbor.deserialize_seed(&cbor, seed);
+        assert!(matches!(result, Err(SceneError::Cbor(_))));
+    }
+}
@@ -0,0 +1,152 @@
+#[derive(Clone, Debug)]
+pub struct Time {
+    delta: f32,
+    elapsed: f64,
+    frame: u64,
+    /// Length of a fixed step in seconds.
+    pub fixed_timestep: f32,
+    /// Upper bound on fixed steps per frame, to avoid spiraling after long frames.
+    pub max_fixed_steps: u32,
+    fixed_accumulator: f32,
+    fixed_steps: u32,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Time {
+    pub fn new() -> Self {
+        Self {
+            delta: 0.0,
+            elapsed: 0.0,
+            frame: 0,
+            fixed_timestep: 1.0 / 60.0,
+            max_fixed_steps: 8,
+            fixed_accumulator: 0.0,
+            fixed_steps: 0,
+        }
+    }
+
+    pub fn with_fixed_timestep(mut self, fixed_timestep: f32) -> Self {
+        self.fixed_timestep = fixed_timestep;
+        self
+    }
+
+    /// Advances time by `delta` seconds, called once per frame.
+    pub fn advance(&mut self, delta: f32) {
+        self.delta = delta;
+        self.elapsed += delta as f64;
+        self.frame += 1;
+
+        if self.fixed_timestep > 0.0 {
+            self.fixed_accumulator += delta;
+
+            let steps = (self.fixed_accumulator / self.fixed_timestep).floor() as u32;
+            self.fixed_accumulator -= steps as f32 * self.fixed_timestep;
+
+            if steps > self.max_fixed_steps {
+                self.fixed_steps = self.max_fixed_steps;
+                self.fixed_accumulator = 0.0;
+            } else {
+                self.fixed_steps = steps;
+            }
+        } else {
+            self.fixed_steps = 0;
+        }
+    }
+
+    /// Seconds since the last frame.
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// Seconds since the first frame, as an `f64` so it stays precise in long sessions.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Index of the current frame, starting at 1 after the first [`Time::advance`].
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Number of fixed steps that run this frame, each one runs
+    /// [`Stage::FixedUpdate`](crate::plugin::Stage::FixedUpdate).
+    pub fn fixed_steps(&self) -> u32 {
+        self.fixed_steps
+    }
+
+    /// How far into the next fixed step we are, in the range 0..1, useful for interpolation.
+    pub fn fixed_alpha(&self) -> f32 {
+        if self.fixed_timestep > 0.0 {
+            self.fixed_accumulator / self.fixed_timestep
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulates_fixed_steps() {
+        let mut time = Time::new().with_fixed_timestep(0.25);
+
+        time.advance(0.1);
+        assert_eq!(time.fixed_steps(), 0);
+        assert!((time.fixed_alpha() - 0.4).abs() < 1e-5);
+
+        // the leftover from the last frame carries over
+        time.advance(0.2);
+        assert_eq!(time.fixed_steps(), 1);
+        assert!((time.fixed_alpha() - 0.2).abs() < 1e-5);
+
+        time.advance(0.5);
+        assert_eq!(time.fixed_steps(), 2);
+        assert!((time.fixed_alpha() - 0.2).abs() < 1e-5);
+
+        assert_eq!(time.frame(), 3);
+        assert_eq!(time.delta(), 0.5);
+        assert!((time.elapsed() - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clamps_fixed_steps() {
+        let mut time = Time::new().with_fixed_timestep(0.1);
+        time.max_fixed_steps = 3;
+
+        time.advance(1.05);
+        assert_eq!(time.fixed_steps(), 3);
+
+        // time past the clamp is dropped instead of running later
+        assert_eq!(time.fixed_alpha(), 0.0);
+        time.advance(0.05);
+        assert_eq!(time.fixed_steps(), 0);
+    }
+
+    #[test]
+    fn disables_fixed_steps() {
+        let mut time = Time::new().with_fixed_timestep(0.0);
+
+        time.advance(1.0);
+        assert_eq!(time.fixed_steps(), 0);
+        assert_eq!(time.fixed_alpha(), 0.0);
+    }
+
+    #[test]
+    fn keeps_elapsed_precise() {
+        let mut time = Time::new();
+
+        // a day at 60 frames per second
+        for _ in 0..60 * 60 * 60 * 24 {
+            time.advance(1.0 / 60.0);
+        }
+
+        assert!((time.elapsed() - 86400.0).abs() < 0.01);
+    }
+}
@@ -1,10 +1,14 @@
 use crate::component::*;
+use crate::input::*;
 use crate::node::*;
 use crate::plugin::*;
+use crate::prefab::Prefab;
+use crate::reflect::Value;
+use crate::time::*;
 use crate::transform::*;
 use linked_hash_map::LinkedHashMap;
 use quartz_render::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{Arc, Mutex};
 
 pub struct Tree {
@@ -15,6 +19,11 @@ pub struct Tree {
     pub(crate) next_node_id: NodeId,
     pub(crate) despawn: Vec<NodeId>,
     pub(crate) added: Vec<NodeId>,
+    pub(crate) prefab_components: Vec<(NodeId, String, Value)>,
+    /// The version of each prefab its instances were last updated to, see
+    /// [`Tree::sync_prefabs`].
+    pub(crate) prefabs: LinkedHashMap<String, Prefab>,
+    pub(crate) component_index: ComponentIndex,
 }
 
 impl Tree {
@@ -27,6 +36,9 @@ impl Tree {
             next_node_id: NodeId(0),
             despawn: Vec::new(),
             added: Vec::new(),
+            prefab_components: Vec::new(),
+            prefabs: LinkedHashMap::new(),
+            component_index: ComponentIndex::default(),
         }
     }
 
@@ -46,7 +58,8 @@ impl Tree {
 
     pub fn spawn(&mut self) -> NodeId {
         let id = self.generate_id();
-        let node = Node::new();
+        let mut node = Node::new();
+        node.components.set_index(id, self.component_index.clone());
         self.nodes.insert(id, NodeContainer::new(node));
         self.base.insert(id);
         self.children.insert(id, Vec::new());
@@ -55,6 +68,28 @@ impl Tree {
         id
     }
 
+    /// Spawns a node with a specific id, used when restoring nodes that were removed.
+    ///
+    /// Does nothing if `id` is already in use.
+    pub fn spawn_with_id(&mut self, id: impl Into<NodeId>) {
+        let id = id.into();
+
+        if self.nodes.contains_key(&id) {
+            return;
+        }
+
+        if id.0 >= self.next_node_id.0 {
+            self.next_node_id = NodeId(id.0 + 1);
+        }
+
+        let mut node = Node::new();
+        node.components.set_index(id, self.component_index.clone());
+        self.nodes.insert(id, NodeContainer::new(node));
+        self.base.insert(id);
+        self.children.insert(id, Vec::new());
+        self.added.push(id);
+    }
+
     pub fn spawn_child(&mut self, parent_id: impl Into<NodeId>) -> Option<NodeId> {
         let parent_id = parent_id.into();
 
@@ -72,9 +107,15 @@ impl Tree {
         self.despawn.push(node.into());
     }
 
+    /// Nodes queued for despawning at the end of the update.
+    pub fn despawn_queue(&self) -> &Vec<NodeId> {
+        &self.despawn
+    }
+
     pub(crate) fn remove_recursive(&mut self, node: impl Into<NodeId>) {
         let node = node.into();
         self.nodes.remove(&node);
+        self.component_index.remove_node(node);
 
         if let Some(parent) = self.parents.remove(&node) {
             if let Some(children) = self.children.get_mut(&parent) {
@@ -133,6 +174,144 @@ impl Tree {
             None
         }
     }
+
+    /// Adds the components of every node to the component index, used after deserializing.
+    pub(crate) fn index_components(&mut self) {
+        for (node_id, container) in self.nodes.iter() {
+            if let Some(node) = container.node.lock().unwrap().as_mut() {
+                node.components
+                    .set_index(*node_id, self.component_index.clone());
+            }
+        }
+    }
+
+    /// Nodes with a `C` component, in the order they were spawned.
+    pub fn query<C: ComponentPod>(&self) -> Vec<NodeId> {
+        let mut nodes = self.component_index.get(C::long_name_const());
+        nodes.sort_by_key(|node_id| node_id.0);
+        nodes
+    }
+
+    /// The first node named `name`.
+    ///
+    /// Nodes that are currently borrowed, like the node being updated, are skipped.
+    pub fn find_by_name(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .keys()
+            .find(|node_id| self.node_name_is(**node_id, name))
+            .cloned()
+    }
+
+    /// Finds a node by the names of the nodes on the way to it from the root, separated by
+    /// `/`, eg. `"Player/Camera"`.
+    pub fn find_by_path(&self, path: &str) -> Option<NodeId> {
+        let mut names = path.split('/').filter(|name| !name.is_empty());
+
+        let first = names.next()?;
+        let mut node_id = self
+            .nodes
+            .keys()
+            .find(|node_id| self.base.contains(node_id) && self.node_name_is(**node_id, first))
+            .cloned()?;
+
+        for name in names {
+            node_id = self
+                .children
+                .get(&node_id)?
+                .iter()
+                .find(|child| self.node_name_is(**child, name))
+                .cloned()?;
+        }
+
+        Some(node_id)
+    }
+
+    fn node_name_is(&self, node_id: NodeId, name: &str) -> bool {
+        self.get_node(node_id)
+            .map_or(false, |node| node.name == name)
+    }
+
+    /// Iterates the descendants of `node_id` depth first, parents before their children.
+    pub fn descendants(&self, node_id: impl Into<NodeId>) -> DepthFirst<'_> {
+        let mut stack = self.children_of(node_id.into());
+        stack.reverse();
+
+        DepthFirst { tree: self, stack }
+    }
+
+    /// Iterates the descendants of `node_id` breadth first, closest descendants first.
+    pub fn descendants_breadth_first(&self, node_id: impl Into<NodeId>) -> BreadthFirst<'_> {
+        BreadthFirst {
+            tree: self,
+            queue: self.children_of(node_id.into()).into(),
+        }
+    }
+
+    /// Iterates the parent of `node_id`, its parent and so on up to the root.
+    pub fn ancestors(&self, node_id: impl Into<NodeId>) -> Ancestors<'_> {
+        Ancestors {
+            tree: self,
+            node_id: self.get_parent(node_id),
+        }
+    }
+
+    fn children_of(&self, node_id: NodeId) -> Vec<NodeId> {
+        self.children.get(&node_id).cloned().unwrap_or_default()
+    }
+}
+
+pub struct DepthFirst<'a> {
+    tree: &'a Tree,
+    stack: Vec<NodeId>,
+}
+
+impl Iterator for DepthFirst<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node_id = self.stack.pop()?;
+
+        if let Some(children) = self.tree.children.get(&node_id) {
+            self.stack.extend(children.iter().rev());
+        }
+
+        Some(node_id)
+    }
+}
+
+pub struct BreadthFirst<'a> {
+    tree: &'a Tree,
+    queue: VecDeque<NodeId>,
+}
+
+impl Iterator for BreadthFirst<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node_id = self.queue.pop_front()?;
+
+        if let Some(children) = self.tree.children.get(&node_id) {
+            self.queue.extend(children.iter());
+        }
+
+        Some(node_id)
+    }
+}
+
+pub struct Ancestors<'a> {
+    tree: &'a Tree,
+    node_id: Option<NodeId>,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node_id = self.node_id?;
+        self.node_id = self.tree.get_parent(node_id);
+
+        Some(node_id)
+    }
 }
 
 #[cfg(feature = "editor_bridge")]
@@ -141,47 +320,89 @@ impl Tree {
         &mut self,
         node_id: &NodeId,
         plugins: &Plugins,
+        time: &Time,
+        input: &InputState,
         instance: &Instance,
     ) {
         if let Some(mut node) = self.get_node(node_id) {
             self.base.remove(node_id);
 
-            node.despawn(plugins, node_id, self, instance);
+            node.despawn(plugins, node_id, self, time, input, instance);
 
             for child in self.get_children(*node_id).clone() {
-                self.despawn_recursive(&child, plugins, instance);
+                self.despawn_recursive(&child, plugins, time, input, instance);
             }
         }
     }
 
-    pub fn start(&mut self, plugins: &Plugins, instance: &Instance) {
+    pub fn start(
+        &mut self,
+        plugins: &Plugins,
+        time: &Time,
+        input: &InputState,
+        instance: &Instance,
+    ) {
         for node_id in self.nodes() {
             if let Some(mut node) = self.get_node(&node_id) {
-                node.start(plugins, &node_id, self, instance);
+                node.start(plugins, &node_id, self, time, input, instance);
             }
         }
     }
 
-    pub fn editor_start(&mut self, plugins: &Plugins, instance: &Instance) {
+    pub fn editor_start(
+        &mut self,
+        plugins: &Plugins,
+        time: &Time,
+        input: &InputState,
+        instance: &Instance,
+    ) {
         for node_id in self.nodes() {
             if let Some(mut node) = self.get_node(&node_id) {
-                node.editor_start(plugins, &node_id, self, instance);
+                node.editor_start(plugins, &node_id, self, time, input, instance);
             }
         }
     }
 
-    pub fn update(&mut self, plugins: &Plugins, instance: &Instance) {
+    /// Runs `stage` for every component, ordered by [`Component::priority`].
+    pub fn update(
+        &mut self,
+        stage: Stage,
+        plugins: &Plugins,
+        time: &Time,
+        input: &InputState,
+        instance: &Instance,
+    ) {
+        let mut components = Vec::new();
+
         for node_id in self.nodes() {
+            if let Some(node) = self.get_node(&node_id) {
+                for (name, component) in &node.components.components {
+                    let priority = component.read().unwrap().priority();
+                    components.push((priority, node_id, name.clone()));
+                }
+            }
+        }
+
+        // stable, so components with the same priority keep the tree order
+        components.sort_by_key(|(priority, _, _)| *priority);
+
+        for (_, node_id, name) in components {
             if let Some(mut node) = self.get_node(&node_id) {
-                node.update(plugins, &node_id, self, instance);
+                node.update(stage, &name, plugins, &node_id, self, time, input, instance);
             }
         }
     }
 
-    pub fn editor_update(&mut self, plugins: &Plugins, instance: &Instance) {
+    pub fn editor_update(
+        &mut self,
+        plugins: &Plugins,
+        time: &Time,
+        input: &InputState,
+        instance: &Instance,
+    ) {
         for node_id in self.nodes() {
             if let Some(mut node) = self.get_node(&node_id) {
-                node.editor_update(plugins, &node_id, self, instance);
+                node.editor_update(plugins, &node_id, self, time, input, instance);
             }
         }
     }
@@ -305,3 +526,219 @@ impl Drop for NodeGuard<'_> {
         *self.container.lock().unwrap() = self.node.take();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quartz_engine;
+    use crate::reflect::serde::SceneDeserializer;
+    use crate::reflect::Reflect;
+    use crate::scene::{Scene, SceneFormat};
+
+    #[derive(Reflect, Default, Debug, Clone, PartialEq)]
+    struct Health {
+        value: f32,
+    }
+
+    impl Component for Health {
+        type Plugins = ();
+    }
+
+    fn spawn(tree: &mut Tree, name: &str, parent: Option<NodeId>) -> NodeId {
+        let id = match parent {
+            Some(parent) => tree.spawn_child(parent).unwrap(),
+            None => tree.spawn(),
+        };
+        tree.get_node(id).unwrap().name = String::from(name);
+
+        id
+    }
+
+    fn add_health(tree: &Tree, node_id: NodeId) {
+        let health = Health { value: 1.0 };
+        tree.get_node(node_id)
+            .unwrap()
+            .components
+            .add_direct(health);
+    }
+
+    struct Example {
+        tree: Tree,
+        player: NodeId,
+        body: NodeId,
+        arm: NodeId,
+        leg: NodeId,
+        camera: NodeId,
+        enemy: NodeId,
+        enemy_body: NodeId,
+    }
+
+    /// ```text
+    /// Player
+    ///     Body
+    ///         Arm
+    ///         Leg
+    ///     Camera
+    /// Enemy
+    ///     Body
+    /// ```
+    fn example() -> Example {
+        let mut tree = Tree::new();
+        let player = spawn(&mut tree, "Player", None);
+        let body = spawn(&mut tree, "Body", Some(player));
+        let arm = spawn(&mut tree, "Arm", Some(body));
+        let leg = spawn(&mut tree, "Leg", Some(body));
+        let camera = spawn(&mut tree, "Camera", Some(player));
+        let enemy = spawn(&mut tree, "Enemy", None);
+        let enemy_body = spawn(&mut tree, "Body", Some(enemy));
+
+        Example {
+            tree,
+            player,
+            body,
+            arm,
+            leg,
+            camera,
+            enemy,
+            enemy_body,
+        }
+    }
+
+    #[test]
+    fn finds_by_name() {
+        let example = example();
+        let tree = &example.tree;
+
+        assert_eq!(tree.find_by_name("Leg"), Some(example.leg));
+        // the first one spawned
+        assert_eq!(tree.find_by_name("Body"), Some(example.body));
+        assert_eq!(tree.find_by_name("Tail"), None);
+
+        // borrowed nodes are skipped
+        let leg = tree.get_node(example.leg).unwrap();
+        assert_eq!(tree.find_by_name("Leg"), None);
+        drop(leg);
+    }
+
+    #[test]
+    fn finds_by_path() {
+        let example = example();
+        let tree = &example.tree;
+
+        assert_eq!(tree.find_by_path("Player"), Some(example.player));
+        assert_eq!(tree.find_by_path("Player/Body/Leg"), Some(example.leg));
+        assert_eq!(tree.find_by_path("Enemy/Body"), Some(example.enemy_body));
+        assert_eq!(tree.find_by_path("/Player//Camera/"), Some(example.camera));
+
+        // missing segments
+        assert_eq!(tree.find_by_path("Player/Leg"), None);
+        assert_eq!(tree.find_by_path("Player/Body/Tail"), None);
+        assert_eq!(tree.find_by_path("Player/Body/Leg/Foot"), None);
+        // only roots match the first segment
+        assert_eq!(tree.find_by_path("Body"), None);
+        assert_eq!(tree.find_by_path(""), None);
+    }
+
+    #[test]
+    fn iterates_in_order() {
+        let example = example();
+        let tree = &example.tree;
+
+        let depth_first: Vec<_> = tree.descendants(example.player).collect();
+        let expected = vec![example.body, example.arm, example.leg, example.camera];
+        assert_eq!(depth_first, expected);
+
+        let breadth_first: Vec<_> = tree.descendants_breadth_first(example.player).collect();
+        let expected = vec![example.body, example.camera, example.arm, example.leg];
+        assert_eq!(breadth_first, expected);
+
+        let ancestors: Vec<_> = tree.ancestors(example.leg).collect();
+        assert_eq!(ancestors, vec![example.body, example.player]);
+
+        assert_eq!(tree.descendants(example.leg).count(), 0);
+        assert_eq!(tree.ancestors(example.enemy).count(), 0);
+    }
+
+    #[test]
+    fn keeps_index_up_to_date() {
+        let mut example = example();
+        let tree = &mut example.tree;
+
+        add_health(tree, example.leg);
+        add_health(tree, example.arm);
+        add_health(tree, example.enemy);
+        assert_eq!(
+            tree.query::<Health>(),
+            vec![example.arm, example.leg, example.enemy]
+        );
+
+        // queued components are indexed once they're added
+        let mut camera = tree.get_node(example.camera).unwrap();
+        camera.add_component(Health::default());
+        camera.components.update();
+        drop(camera);
+        assert!(tree.query::<Health>().contains(&example.camera));
+
+        let mut enemy = tree.get_node(example.enemy).unwrap();
+        enemy.components.remove(Health::long_name_const());
+        drop(enemy);
+        assert!(!tree.query::<Health>().contains(&example.enemy));
+
+        // despawned nodes are removed at the end of the update, with their descendants
+        tree.despawn(example.body);
+        assert!(tree.query::<Health>().contains(&example.arm));
+        tree.remove_recursive(example.body);
+        assert_eq!(tree.query::<Health>(), vec![example.camera]);
+    }
+
+    #[test]
+    fn indexes_restored_components() {
+        let mut components = Components::new();
+        components.register_component::<Health>();
+        let plugins = Plugins::new();
+
+        let mut example = example();
+        add_health(&example.tree, example.leg);
+
+        // deserialized scenes
+        for &format in SceneFormat::ALL.iter() {
+            let scene = Scene {
+                plugins: &plugins,
+                tree: &example.tree,
+            };
+            let data = format.serialize(&scene).unwrap();
+
+            let mut loaded_plugins = Plugins::new();
+            let seed = SceneDeserializer {
+                components: &components,
+                plugins: &mut loaded_plugins,
+            };
+            let loaded = format.deserialize_seed(&data, seed).unwrap();
+            assert_eq!(loaded.query::<Health>(), vec![example.leg]);
+        }
+
+        let tree = &mut example.tree;
+
+        // instantiated prefabs
+        let prefab = Prefab::from_tree(tree, example.body, "body.prefab").unwrap();
+        let instance = tree.instantiate(&prefab, None).unwrap();
+        tree.init_prefab_components(&components, &plugins);
+        let instance_leg = tree.get_children(instance)[1];
+        assert_eq!(tree.query::<Health>(), vec![example.leg, instance_leg]);
+
+        // undone edits
+        let snapshot = tree
+            .get_node(example.leg)
+            .unwrap()
+            .snapshot_components(SceneFormat::Ron)
+            .unwrap();
+        tree.remove_recursive(example.leg);
+        assert_eq!(tree.query::<Health>(), vec![instance_leg]);
+
+        tree.spawn_with_id(example.leg);
+        let mut leg = tree.get_node(example.leg).unwrap();
+        leg.restore_components(&snapshot, &components, &plugins);
+        drop(leg);
+        assert_eq!(tree.query::<Health>(), vec![example.leg, instance_leg]);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::asset::*;
 use crate::component::*;
+use crate::event::*;
 use crate::plugin::*;
 use crate::reflect::*;
 use quartz_render::{instance::Instance, texture_format::TargetFormat};
@@ -20,14 +22,26 @@ impl<'a> Types<'a> {
         }
     }
 
-    pub fn register_component<C: InitComponent + Reflect>(&mut self) {
+    /// Registers a component, use the returned [`ComponentRegistration`] to add migrations
+    /// for data saved with older versions of it.
+    pub fn register_component<C: InitComponent + Reflect>(&mut self) -> ComponentRegistration<'_> {
         self.components.register_component::<C>()
     }
 
-    pub fn register_plugin<P: Plugin>(&mut self) {
+    pub fn register_event<T: Event>(&mut self) {
+        self.plugins.events.register_event::<T>();
+    }
+
+    pub fn register_asset_loader<L: AssetLoader>(&mut self, loader: L) {
+        self.plugins.assets.register_loader(loader);
+    }
+
+    /// Registers a plugin, use the returned [`PluginRegistration`] to order it relative to
+    /// other plugins, eg. `types.register_plugin::<Camera>().after::<Physics>()`.
+    pub fn register_plugin<P: Plugin>(&mut self) -> PluginRegistration<'_> {
         self.plugins.register_plugin::<P>(PluginInitCtx {
             instance: &self.instance,
             target_format: self.target_format,
-        });
+        })
     }
 }
@@ -1,10 +1,10 @@
 use proc_macro2::{Span, TokenStream};
 use proc_macro_crate::FoundCrate;
-use quote::{quote, quote_spanned};
+use quote::{format_ident, quote, quote_spanned};
 use syn::spanned::Spanned;
 use syn::{
-    parse::ParseStream, parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields,
-    GenericParam, Generics, Ident, Lit, LitStr, Meta, MetaNameValue,
+    parse::ParseStream, parse_macro_input, parse_quote, Attribute, Data, DataEnum, DeriveInput,
+    Field, Fields, GenericParam, Generics, Ident, Index, Lit, LitStr, Meta, MetaNameValue,
 };
 
 #[derive(Default)]
@@ -69,7 +69,7 @@ pub fn derive_inspect(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     let generics = add_trait_bounds(input.generics);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let inspect = inspect(&input.data);
+    let inspect = inspect(&name, &input.data);
 
     let expanded = quote! {
         impl #impl_generics quartz_engine::core::inspect::Inspect for #name #ty_generics #where_clause {
@@ -94,63 +94,105 @@ fn add_trait_bounds(mut generics: Generics) -> Generics {
     generics
 }
 
-fn inspect(data: &Data) -> TokenStream {
+/// Inspects a single field, `place` is a `&mut` to it.
+fn inspect_field(f: &Field, name: &str, place: TokenStream) -> Option<TokenStream> {
+    let attributes = InspectFieldAttributes::parse(&f.attrs);
+
+    if attributes.ignore {
+        return None;
+    }
+
+    let tooltip = if attributes.doc_comments.len() > 0 {
+        let mut tooltip = String::new();
+
+        for doc_comment in &attributes.doc_comments[..attributes.doc_comments.len() - 1] {
+            tooltip.push_str(&doc_comment);
+            tooltip.push_str("\n");
+        }
+
+        tooltip.push_str(attributes.doc_comments.last().unwrap());
+
+        Some(quote!(
+            quartz_engine::core::egui::popup::show_tooltip_text(
+                ui.ctx(),
+                quartz_engine::core::egui::Id::new(stringify!(#name)),
+                #tooltip
+            );
+        ))
+    } else {
+        None
+    };
+
+    if attributes.collapsing {
+        Some(quote_spanned! {f.ty.span()=>
+            let response = ui.collapsing(#name, |ui| {
+                mutated |= (#place).inspect(ui);
+            });
+
+            if response.header_response.hovered() {
+                #tooltip
+            }
+        })
+    } else {
+        Some(quote_spanned! {f.ty.span()=>
+            let response = ui.label(#name);
+            ui.indent(#name, |ui| {
+                mutated |= (#place).inspect(ui);
+            });
+
+            if response.hovered() {
+                #tooltip
+            }
+        })
+    }
+}
+
+fn field_binding(index: usize, field: &Field) -> Ident {
+    field
+        .ident
+        .clone()
+        .unwrap_or_else(|| format_ident!("__field{}", index))
+}
+
+fn inspect(ident: &Ident, data: &Data) -> TokenStream {
     match data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => {
                 let fields = fields.named.iter().filter_map(|f| {
-                    let attributes = InspectFieldAttributes::parse(&f.attrs);
-
-                    if attributes.ignore {
-                        None
-                    } else {
-                        let ident = f.ident.as_ref().unwrap();
-                        let name = ident.to_string();
-
-                        let tooltip = if attributes.doc_comments.len() > 0 {
-                            let mut tooltip = String::new();
-
-                            for doc_comment in &attributes.doc_comments[..attributes.doc_comments.len() - 1] {
-                                tooltip.push_str(&doc_comment);
-                                tooltip.push_str("\n");
-                            }
-
-                            tooltip.push_str(attributes.doc_comments.last().unwrap());
-
-                            Some(quote!(
-                                quartz_engine::core::egui::popup::show_tooltip_text(
-                                    ui.ctx(), 
-                                    quartz_engine::core::egui::Id::new(stringify!(#name)), 
-                                    #tooltip
-                                );
-                            ))
-                        } else {
-                            None
-                        };
-
-                        if attributes.collapsing {
-                            Some(quote_spanned! {f.ty.span()=>
-                                let response = ui.collapsing(#name, |ui| {
-                                    mutated |= self.#ident.inspect(ui);
-                                });
-
-                                if response.header_response.hovered() {
-                                    #tooltip
-                                }
-                            })
-                        } else {
-                            Some(quote_spanned! {f.ty.span()=>
-                                let response = ui.label(#name);
-                                ui.indent(#name, |ui| {
-                                    mutated |= self.#ident.inspect(ui);
-                                });
-
-                                if response.hovered() {
-                                    #tooltip
-                                }
-                            })
-                        }
-                    }
+                    let ident = f.ident.as_ref().unwrap();
+
+                    inspect_field(f, &ident.to_string(), quote!(&mut self.#ident))
+                });
+
+                quote! {
+                    ui.vertical(|ui| {
+                        let mut mutated = false;
+
+                        #(
+                            #fields
+                        )*
+
+                        mutated
+                    }).inner
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let inspected = fields
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, f)| !InspectFieldAttributes::parse(&f.attrs).ignore)
+                    .collect::<Vec<_>>();
+
+                // a newtype is inspected as its only field
+                if fields.unnamed.len() == 1 && inspected.len() == 1 {
+                    return quote!(self.0.inspect(ui));
+                }
+
+                let fields = inspected.into_iter().filter_map(|(i, f)| {
+                    let index = Index::from(i);
+
+                    inspect_field(f, &i.to_string(), quote!(&mut self.#index))
                 });
 
                 quote! {
@@ -165,8 +207,116 @@ fn inspect(data: &Data) -> TokenStream {
                     }).inner
                 }
             }
-            _ => unimplemented!(),
+            Fields::Unit => quote!(false),
         },
+        Data::Enum(data) => inspect_enum(ident, data),
         _ => unimplemented!(),
     }
 }
+
+/// Shows a combo box selecting the variant followed by the fields of the current variant.
+/// Selecting another variant replaces `self` with it, with every field set to its default.
+fn inspect_enum(ident: &Ident, data: &DataEnum) -> TokenStream {
+    let name = ident.to_string();
+
+    let variant_names = data
+        .variants
+        .iter()
+        .map(|v| v.ident.to_string())
+        .collect::<Vec<_>>();
+
+    let mut current = Vec::new();
+    let mut defaults = Vec::new();
+    let mut inspect_variants = Vec::new();
+
+    for (index, variant) in data.variants.iter().enumerate() {
+        let variant_ident = &variant.ident;
+
+        let fields = variant
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f, field_binding(i, f)))
+            .collect::<Vec<_>>();
+
+        let bindings = fields.iter().map(|(_, binding)| binding);
+        let inspect_fields = fields.iter().filter_map(|(f, binding)| {
+            let name = match &f.ident {
+                Some(ident) => ident.to_string(),
+                None => binding.to_string().trim_start_matches("__field").to_string(),
+            };
+
+            inspect_field(f, &name, quote!(#binding))
+        });
+
+        let default_fields = fields
+            .iter()
+            .map(|_| quote!(std::default::Default::default()));
+
+        let (pattern, any_pattern, default) = match &variant.fields {
+            Fields::Named(_) => {
+                let idents = fields.iter().map(|(_, binding)| binding);
+
+                (
+                    quote!(#ident::#variant_ident { #(#bindings),* }),
+                    quote!(#ident::#variant_ident { .. }),
+                    quote!(#ident::#variant_ident { #(#idents: #default_fields),* }),
+                )
+            }
+            Fields::Unnamed(_) => (
+                quote!(#ident::#variant_ident(#(#bindings),*)),
+                quote!(#ident::#variant_ident(..)),
+                quote!(#ident::#variant_ident(#(#default_fields),*)),
+            ),
+            Fields::Unit => (
+                quote!(#ident::#variant_ident),
+                quote!(#ident::#variant_ident),
+                quote!(#ident::#variant_ident),
+            ),
+        };
+
+        current.push(quote!(#any_pattern => #index,));
+        defaults.push(quote!(#index => #default,));
+        inspect_variants.push(quote! {
+            #[allow(unused_variables)]
+            #pattern => {
+                #(#inspect_fields)*
+            }
+        });
+    }
+
+    quote! {
+        const VARIANTS: &[&str] = &[#(#variant_names),*];
+
+        ui.vertical(|ui| {
+            let mut mutated = false;
+
+            let mut variant = match self {
+                #(#current)*
+            };
+
+            quartz_engine::core::egui::ComboBox::from_id_source(ui.make_persistent_id(#name))
+                .selected_text(VARIANTS[variant])
+                .show_ui(ui, |ui| {
+                    for (index, name) in VARIANTS.iter().enumerate() {
+                        ui.selectable_value(&mut variant, index, *name);
+                    }
+                });
+
+            if variant != match self { #(#current)* } {
+                *self = match variant {
+                    #(#defaults)*
+                    _ => unreachable!(),
+                };
+
+                mutated = true;
+            }
+
+            match self {
+                #(#inspect_variants)*
+            }
+
+            mutated
+        }).inner
+    }
+}
@@ -1,15 +1,19 @@
 use proc_macro2::{Span, TokenStream};
-use quote::{quote, quote_spanned};
+use quote::{format_ident, quote, quote_spanned};
 use syn::spanned::Spanned;
 use syn::{
-    parse::ParseStream, parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields,
-    GenericParam, Generics, Ident,
+    parse::ParseStream, parse_macro_input, parse_quote, Attribute, Data, DataEnum, DeriveInput,
+    Field, Fields, GenericParam, Generics, Ident, Index, LitInt, LitStr, Token, Variant,
 };
 
 #[derive(Default)]
 struct ReflectFieldAttributes {
     pub ignore: bool,
     pub reflect: bool,
+    /// Set the field to its default when it's missing, instead of keeping its value.
+    pub default: bool,
+    /// Name the field had before being renamed, still accepted when reading.
+    pub rename: Option<String>,
 }
 
 impl ReflectFieldAttributes {
@@ -20,15 +24,25 @@ impl ReflectFieldAttributes {
             .map_or_else(Self::default, |a| {
                 syn::custom_keyword!(ignore);
                 syn::custom_keyword!(reflect);
+                syn::custom_keyword!(default);
+                syn::custom_keyword!(rename);
 
                 let mut attributes = Self::default();
                 a.parse_args_with(|input: ParseStream| {
-                    if input.parse::<Option<ignore>>()?.is_some() {
-                        attributes.ignore = true;
-                    }
+                    while !input.is_empty() {
+                        if input.parse::<Option<ignore>>()?.is_some() {
+                            attributes.ignore = true;
+                        } else if input.parse::<Option<reflect>>()?.is_some() {
+                            attributes.reflect = true;
+                        } else if input.parse::<Option<default>>()?.is_some() {
+                            attributes.default = true;
+                        } else {
+                            input.parse::<rename>()?;
+                            input.parse::<Token![=]>()?;
+                            attributes.rename = Some(input.parse::<LitStr>()?.value());
+                        }
 
-                    if input.parse::<Option<reflect>>()?.is_some() {
-                        attributes.reflect = true;
+                        input.parse::<Option<Token![,]>>()?;
                     }
 
                     Ok(())
@@ -40,6 +54,34 @@ impl ReflectFieldAttributes {
     }
 }
 
+#[derive(Default)]
+struct ReflectAttributes {
+    pub version: Option<LitInt>,
+}
+
+impl ReflectAttributes {
+    fn parse(attributes: &Vec<Attribute>) -> Self {
+        attributes
+            .iter()
+            .find(|a| *a.path.get_ident().as_ref().unwrap() == REFLECT_ATTRIBUTE_NAME)
+            .map_or_else(Self::default, |a| {
+                syn::custom_keyword!(version);
+
+                let mut attributes = Self::default();
+                a.parse_args_with(|input: ParseStream| {
+                    input.parse::<version>()?;
+                    input.parse::<Token![=]>()?;
+                    attributes.version = Some(input.parse()?);
+
+                    Ok(())
+                })
+                .expect("Invalid 'reflect' attribute format.");
+
+                attributes
+            })
+    }
+}
+
 const REFLECT_ATTRIBUTE_NAME: &str = "reflect";
 
 pub fn derive_reflect(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -47,10 +89,20 @@ pub fn derive_reflect(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 
     let name = input.ident;
 
+    let attributes = ReflectAttributes::parse(&input.attrs);
     let generics = add_trait_bounds(input.generics);
 
     let reflect = reflect(&name, &generics, &input.data);
     let serialize = serialize(&name, &input.data);
+    let fields = fields(&name, &input.data);
+
+    let version = attributes.version.map(|version| {
+        quote! {
+            fn version_const() -> u32 {
+                #version
+            }
+        }
+    });
 
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
@@ -63,6 +115,13 @@ pub fn derive_reflect(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 
         impl #impl_generics quartz_engine::core::reflect::Reflect for #name #ty_generics #where_clause {
             fn reflect(&mut self, deserializer: &mut dyn quartz_engine::core::erased_serde::Deserializer) {
+                self.try_reflect(deserializer).unwrap();
+            }
+
+            fn try_reflect(
+                &mut self,
+                deserializer: &mut dyn quartz_engine::core::erased_serde::Deserializer,
+            ) -> Result<(), quartz_engine::core::erased_serde::Error> {
                 #reflect
             }
 
@@ -73,6 +132,10 @@ pub fn derive_reflect(input: proc_macro::TokenStream) -> proc_macro::TokenStream
             fn short_name_const() -> &'static str {
                 stringify!(#name)
             }
+
+            #version
+
+            #fields
         }
     };
 
@@ -141,25 +204,44 @@ fn reflect(ident: &Ident, generics: &Generics, data: &Data) -> TokenStream {
 
                     quote! {
                         #[allow(non_camel_case_types)]
-                        enum Field { #( #idents ),* }
+                        enum Field { #( #idents, )* __ignore }
                     }
                 };
 
+                // fields are also read by their old name, and unknown fields are skipped so
+                // removing a field doesn't break saved data
                 let field_match = {
-                    let names = names.clone();
+                    let patterns = fields.iter().map(|f| {
+                        let name = f.ident.as_ref().unwrap().to_string();
+
+                        match ReflectFieldAttributes::parse(&f.attrs).rename {
+                            Some(old) => quote!(#name | #old),
+                            None => quote!(#name),
+                        }
+                    });
                     let idents = idents.clone();
 
                     quote! {
                         match value {
                             #(
-                                #names => Ok(Field::#idents),
+                                #patterns => Ok(Field::#idents),
                             )*
-                            _ => Err(quartz_engine::core::serde::de::Error::unknown_field(value, FIELDS)),
+                            _ => Ok(Field::__ignore),
                         }
                     }
                 };
 
-                
+                let defaults = fields
+                    .iter()
+                    .filter(|f| ReflectFieldAttributes::parse(&f.attrs).default)
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect::<Vec<_>>();
+                let seen = defaults
+                    .iter()
+                    .map(|ident| format_ident!("__seen_{}", ident))
+                    .collect::<Vec<_>>();
+
+
                 let visit_seq = {
                     let mut index = 0usize;
 
@@ -168,8 +250,18 @@ fn reflect(ident: &Ident, generics: &Generics, data: &Data) -> TokenStream {
 
                         let attrs = ReflectFieldAttributes::parse(&f.attrs);
                         let ident = f.ident.as_ref().unwrap();
-    
-                        if attrs.reflect {
+
+                        if attrs.reflect && attrs.default {
+                            quote! {
+                                if seq.next_element_seed(
+                                    quartz_engine::core::reflect::ReflectDeserializer {
+                                        reflect: &mut self.#ident,
+                                    }
+                                )?.is_none() {
+                                    self.#ident = std::default::Default::default();
+                                }
+                            }
+                        } else if attrs.reflect {
                             quote! {
                                 seq.next_element_seed(
                                     quartz_engine::core::reflect::ReflectDeserializer {
@@ -177,6 +269,10 @@ fn reflect(ident: &Ident, generics: &Generics, data: &Data) -> TokenStream {
                                     }
                                 )?.ok_or(Error::invalid_length(#index, &self))?;
                             }
+                        } else if attrs.default {
+                            quote! {
+                                self.#ident = seq.next_element()?.unwrap_or_default();
+                            }
                         } else {
                             quote! {
                                 self.#ident = seq.next_element()?.ok_or(Error::invalid_length(#index, &self))?;
@@ -204,9 +300,17 @@ fn reflect(ident: &Ident, generics: &Generics, data: &Data) -> TokenStream {
                     let attrs = ReflectFieldAttributes::parse(&f.attrs);
                     let ident = f.ident.as_ref().unwrap();
 
+                    let seen = if attrs.default {
+                        let seen = format_ident!("__seen_{}", ident);
+                        Some(quote!(#seen = true;))
+                    } else {
+                        None
+                    };
+
                     if attrs.reflect {
                         quote!(
                             Field::#ident => {
+                                #seen
                                 map.next_value_seed(
                                     quartz_engine::core::reflect::ReflectDeserializer {
                                         reflect: &mut self.#ident,
@@ -217,6 +321,7 @@ fn reflect(ident: &Ident, generics: &Generics, data: &Data) -> TokenStream {
                     } else {
                         quote!(
                             Field::#ident => {
+                                #seen
                                 self.#ident = map.next_value()?;
                             }
                         )
@@ -284,20 +389,35 @@ fn reflect(ident: &Ident, generics: &Generics, data: &Data) -> TokenStream {
                             {
                                 use quartz_engine::core::serde::de::MapAccess;
 
+                                #(
+                                    let mut #seen = false;
+                                )*
+
                                 while let Some(key) = map.next_key::<Field>()? {
                                     match key {
                                         #(
                                             #fields_map
                                         ),*
+                                        Field::__ignore => {
+                                            map.next_value::<quartz_engine::core::serde::de::IgnoredAny>()?;
+                                        }
                                     }
                                 }
 
+                                // fields missing from the data keep their value, unless they
+                                // have a default
+                                #(
+                                    if !#seen {
+                                        self.#defaults = std::default::Default::default();
+                                    }
+                                )*
+
                                 Ok(())
                             }
                         }
 
                         const FIELDS: &[&str] = &[#(#names),*];
-                        deserializer.deserialize_struct(#name, FIELDS, self).unwrap();
+                        deserializer.deserialize_struct(#name, FIELDS, self)
                     }
                 } else {
                     quote! {
@@ -305,16 +425,768 @@ fn reflect(ident: &Ident, generics: &Generics, data: &Data) -> TokenStream {
                         deserializer.deserialize_unit_struct(
                             #name,
                             quartz_engine::core::serde::de::IgnoredAny
-                        ).unwrap();
+                        )?;
+
+                        Ok(())
                     }
                 }
             }
-            _ => unimplemented!(),
+            Fields::Unnamed(_) => reflect_tuple_struct(ident, generics, &data.fields),
+            Fields::Unit => quote! {
+                use quartz_engine::core::serde::Deserializer;
+                deserializer.deserialize_unit_struct(
+                    #name,
+                    quartz_engine::core::serde::de::IgnoredAny
+                )?;
+
+                Ok(())
+            },
         },
+        Data::Enum(data) => reflect_enum(ident, generics, data),
         _ => unimplemented!(),
     }
 }
 
+fn is_ignored(field: &Field) -> bool {
+    ReflectFieldAttributes::parse(&field.attrs).ignore
+}
+
+fn is_reflect(field: &Field) -> bool {
+    ReflectFieldAttributes::parse(&field.attrs).reflect
+}
+
+/// Name of a field in paths and serialized structs, the index for tuple fields.
+fn field_name(index: usize, field: &Field) -> String {
+    field
+        .ident
+        .as_ref()
+        .map_or_else(|| index.to_string(), |ident| ident.to_string())
+}
+
+/// Name a field is bound to when matching a variant.
+fn field_binding(index: usize, field: &Field) -> Ident {
+    field
+        .ident
+        .clone()
+        .unwrap_or_else(|| format_ident!("__field{}", index))
+}
+
+/// `self.field` access of every reflected field of a struct, along with their names.
+fn struct_members(fields: &Fields) -> Vec<(&Field, String, TokenStream)> {
+    fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| !is_ignored(f))
+        .map(|(i, f)| {
+            let member = match &f.ident {
+                Some(ident) => quote!(#ident),
+                None => {
+                    let index = Index::from(i);
+                    quote!(#index)
+                }
+            };
+
+            (f, field_name(i, f), member)
+        })
+        .collect()
+}
+
+/// Reflected fields of a variant, along with their names and the names they're bound to.
+fn variant_fields(variant: &Variant) -> Vec<(&Field, String, Ident)> {
+    variant
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| !is_ignored(f))
+        .map(|(i, f)| (f, field_name(i, f), field_binding(i, f)))
+        .collect()
+}
+
+/// Pattern matching `variant`, binding every reflected field.
+fn variant_pattern(ident: &Ident, variant: &Variant) -> TokenStream {
+    let variant_ident = &variant.ident;
+
+    match &variant.fields {
+        Fields::Named(fields) => {
+            let bindings = fields.named.iter().filter(|f| !is_ignored(f)).map(|f| &f.ident);
+
+            quote!(#ident::#variant_ident { #(#bindings,)* .. })
+        }
+        Fields::Unnamed(fields) => {
+            let bindings = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                if is_ignored(f) {
+                    quote!(_)
+                } else {
+                    let binding = field_binding(i, f);
+                    quote!(#binding)
+                }
+            });
+
+            quote!(#ident::#variant_ident(#(#bindings),*))
+        }
+        Fields::Unit => quote!(#ident::#variant_ident),
+    }
+}
+
+/// Pattern matching `variant` without binding anything.
+fn variant_any_pattern(ident: &Ident, variant: &Variant) -> TokenStream {
+    let variant_ident = &variant.ident;
+
+    match &variant.fields {
+        Fields::Named(_) => quote!(#ident::#variant_ident { .. }),
+        Fields::Unnamed(_) => quote!(#ident::#variant_ident(..)),
+        Fields::Unit => quote!(#ident::#variant_ident),
+    }
+}
+
+/// `variant` built from the bindings of [`variant_fields`], ignored fields are set to their
+/// default.
+fn construct_variant(ident: &Ident, variant: &Variant) -> TokenStream {
+    let variant_ident = &variant.ident;
+
+    let values = variant.fields.iter().enumerate().map(|(i, f)| {
+        if is_ignored(f) {
+            quote!(std::default::Default::default())
+        } else {
+            let binding = field_binding(i, f);
+            quote!(#binding)
+        }
+    });
+
+    match &variant.fields {
+        Fields::Named(fields) => {
+            let idents = fields.named.iter().map(|f| &f.ident);
+
+            quote!(#ident::#variant_ident { #(#idents: #values,)* })
+        }
+        Fields::Unnamed(_) => quote!(#ident::#variant_ident(#(#values),*)),
+        Fields::Unit => quote!(#ident::#variant_ident),
+    }
+}
+
+/// Reads the next element of `seq` as a new value of `field`. Fields read through
+/// [`Reflect`] start out as their default.
+fn new_element(field: &Field, index: usize) -> TokenStream {
+    let ty = &field.ty;
+
+    if is_reflect(field) {
+        quote! {{
+            let mut value: #ty = std::default::Default::default();
+            seq.next_element_seed(
+                quartz_engine::core::reflect::ReflectDeserializer { reflect: &mut value }
+            )?.ok_or_else(|| V::Error::invalid_length(#index, &"more elements"))?;
+            value
+        }}
+    } else {
+        quote! {
+            seq.next_element::<#ty>()?
+                .ok_or_else(|| V::Error::invalid_length(#index, &"more elements"))?
+        }
+    }
+}
+
+/// Reads the next value of `map` as a new value of `field`.
+fn new_value(field: &Field) -> TokenStream {
+    let ty = &field.ty;
+
+    if is_reflect(field) {
+        quote! {{
+            let mut value: #ty = std::default::Default::default();
+            map.next_value_seed(
+                quartz_engine::core::reflect::ReflectDeserializer { reflect: &mut value }
+            )?;
+            value
+        }}
+    } else {
+        quote!(map.next_value::<#ty>()?)
+    }
+}
+
+/// Reads the next element of `seq` into `place`, a `&mut` to the field.
+fn read_element(field: &Field, place: TokenStream, index: usize) -> TokenStream {
+    if is_reflect(field) {
+        quote! {
+            seq.next_element_seed(
+                quartz_engine::core::reflect::ReflectDeserializer { reflect: #place }
+            )?.ok_or_else(|| V::Error::invalid_length(#index, &"more elements"))?;
+        }
+    } else {
+        quote! {
+            *#place = seq
+                .next_element()?
+                .ok_or_else(|| V::Error::invalid_length(#index, &"more elements"))?;
+        }
+    }
+}
+
+/// Reads the next value of `map` into `place`.
+fn read_value(field: &Field, place: TokenStream) -> TokenStream {
+    if is_reflect(field) {
+        quote! {
+            map.next_value_seed(
+                quartz_engine::core::reflect::ReflectDeserializer { reflect: #place }
+            )?;
+        }
+    } else {
+        quote! {
+            *#place = map.next_value()?;
+        }
+    }
+}
+
+fn reflect_tuple_struct(ident: &Ident, generics: &Generics, fields: &Fields) -> TokenStream {
+    let name = ident.to_string();
+
+    let visitor_generics = add_de_lifetime(generics.clone());
+    let (impl_generics, _, where_clause) = visitor_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let members = struct_members(fields);
+    let len = members.len();
+
+    let elements = members
+        .iter()
+        .enumerate()
+        .map(|(i, (f, _, member))| read_element(f, quote!(&mut self.#member), i));
+
+    // a single field is a newtype struct
+    if fields.len() == 1 && len == 1 {
+        let (field, _, member) = &members[0];
+
+        let read = if is_reflect(field) {
+            quote! {
+                quartz_engine::core::serde::de::DeserializeSeed::deserialize(
+                    quartz_engine::core::reflect::ReflectDeserializer { reflect: &mut self.#member },
+                    deserializer,
+                )?;
+            }
+        } else {
+            quote! {
+                self.#member = quartz_engine::core::serde::Deserialize::deserialize(deserializer)?;
+            }
+        };
+
+        quote! {
+            use quartz_engine::core::serde::de::{Deserializer, Error, SeqAccess, Visitor};
+
+            impl #impl_generics Visitor<'de> for &mut #ident #ty_generics #where_clause {
+                type Value = ();
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    formatter.write_str(concat!("tuple struct ", #name))
+                }
+
+                fn visit_newtype_struct<D>(self, deserializer: D) -> Result<(), D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    #read
+
+                    Ok(())
+                }
+
+                fn visit_seq<V>(self, mut seq: V) -> Result<(), V::Error>
+                where
+                    V: SeqAccess<'de>,
+                {
+                    #(#elements)*
+
+                    Ok(())
+                }
+            }
+
+            deserializer.deserialize_newtype_struct(#name, self)
+        }
+    } else {
+        quote! {
+            use quartz_engine::core::serde::de::{Deserializer, Error, SeqAccess, Visitor};
+
+            impl #impl_generics Visitor<'de> for &mut #ident #ty_generics #where_clause {
+                type Value = ();
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    formatter.write_str(concat!("tuple struct ", #name))
+                }
+
+                #[allow(unused_mut)]
+                fn visit_seq<V>(self, mut seq: V) -> Result<(), V::Error>
+                where
+                    V: SeqAccess<'de>,
+                {
+                    #(#elements)*
+
+                    Ok(())
+                }
+            }
+
+            deserializer.deserialize_tuple_struct(#name, #len, self)
+        }
+    }
+}
+
+/// Reads an externally tagged enum. The variant is read in place if it matches, so fields not
+/// in the data keep their values, otherwise a new variant is read from the data.
+fn reflect_enum(ident: &Ident, generics: &Generics, data: &DataEnum) -> TokenStream {
+    let name = ident.to_string();
+
+    let visitor_generics = add_de_lifetime(generics.clone());
+    let (impl_generics, _, where_clause) = visitor_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let mut variant_generics = visitor_generics.clone();
+    variant_generics.params.insert(0, parse_quote!('a));
+    let (variant_impl_generics, _, _) = variant_generics.split_for_impl();
+
+    let variant_names = data
+        .variants
+        .iter()
+        .map(|v| v.ident.to_string())
+        .collect::<Vec<_>>();
+
+    let mut seq_arms = Vec::new();
+    let mut map_arms = Vec::new();
+    let mut new_seq_arms = Vec::new();
+    let mut new_map_arms = Vec::new();
+
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let variant_name = variant.ident.to_string();
+            let pattern = variant_pattern(ident, variant);
+            let any_pattern = variant_any_pattern(ident, variant);
+            let construct = construct_variant(ident, variant);
+            let fields = variant_fields(variant);
+            let len = fields.len();
+
+            match &variant.fields {
+                Fields::Unit => quote! {
+                    #variant_name => {
+                        access.unit_variant()?;
+                        *self = #construct;
+                    }
+                },
+                Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 && len == 1 => {
+                    let (field, _, binding) = &fields[0];
+
+                    let ty = &field.ty;
+
+                    let (read, new) = if is_reflect(field) {
+                        let read = quote! {
+                            access.newtype_variant_seed(
+                                quartz_engine::core::reflect::ReflectDeserializer {
+                                    reflect: #binding,
+                                }
+                            )?;
+                        };
+                        let new = quote! {{
+                            let mut value: #ty = std::default::Default::default();
+                            access.newtype_variant_seed(
+                                quartz_engine::core::reflect::ReflectDeserializer {
+                                    reflect: &mut value,
+                                }
+                            )?;
+                            value
+                        }};
+
+                        (read, new)
+                    } else {
+                        let read = quote! {
+                            *#binding = access.newtype_variant()?;
+                        };
+                        let new = quote!(access.newtype_variant::<#ty>()?);
+
+                        (read, new)
+                    };
+
+                    quote! {
+                        #variant_name => {
+                            if let #pattern = self {
+                                #read
+                            } else {
+                                let #binding = #new;
+                                *self = #construct;
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    let elements = fields
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (f, _, binding))| read_element(f, quote!(#binding), i));
+
+                    seq_arms.push(quote! {
+                        #pattern => {
+                            #(#elements)*
+                        }
+                    });
+
+                    let bindings = fields.iter().map(|(_, _, binding)| binding).collect::<Vec<_>>();
+                    let new_elements = fields
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (f, _, _))| new_element(f, i));
+
+                    new_seq_arms.push(quote! {
+                        #variant_name => {
+                            #(
+                                let #bindings = #new_elements;
+                            )*
+
+                            Ok(#construct)
+                        }
+                    });
+
+                    // read in place if the variant matches
+                    let read_variant = |method: TokenStream, arg: TokenStream| {
+                        quote! {
+                            if matches!(self, #any_pattern) {
+                                access.#method(#arg, VariantVisitor(self))?;
+                            } else {
+                                *self = access.#method(#arg, NewVariantVisitor(
+                                    #variant_name,
+                                    std::marker::PhantomData,
+                                ))?;
+                            }
+                        }
+                    };
+
+                    if let Fields::Named(_) = &variant.fields {
+                        let names = fields.iter().map(|(_, name, _)| name).collect::<Vec<_>>();
+                        let patterns = fields.iter().map(|(f, name, _)| {
+                            match ReflectFieldAttributes::parse(&f.attrs).rename {
+                                Some(old) => quote!(#name | #old),
+                                None => quote!(#name),
+                            }
+                        });
+                        let patterns = patterns.collect::<Vec<_>>();
+                        let values = fields
+                            .iter()
+                            .map(|(f, _, binding)| read_value(f, quote!(#binding)));
+
+                        map_arms.push(quote! {
+                            #pattern => {
+                                while let Some(Identifier(key)) = map.next_key()? {
+                                    match key.as_str() {
+                                        #(
+                                            #patterns => {
+                                                #values
+                                            }
+                                        )*
+                                        _ => {
+                                            map.next_value::<IgnoredAny>()?;
+                                        }
+                                    }
+                                }
+                            }
+                        });
+
+                        let new_values = fields.iter().map(|(f, _, _)| new_value(f));
+                        let missing = fields.iter().map(|(f, name, _)| {
+                            if ReflectFieldAttributes::parse(&f.attrs).default {
+                                quote!(std::default::Default::default())
+                            } else {
+                                quote!(return Err(V::Error::missing_field(#name)))
+                            }
+                        });
+
+                        new_map_arms.push(quote! {
+                            #variant_name => {
+                                #(
+                                    let mut #bindings = None;
+                                )*
+
+                                while let Some(Identifier(key)) = map.next_key()? {
+                                    match key.as_str() {
+                                        #(
+                                            #patterns => {
+                                                #bindings = Some(#new_values);
+                                            }
+                                        )*
+                                        _ => {
+                                            map.next_value::<IgnoredAny>()?;
+                                        }
+                                    }
+                                }
+
+                                #(
+                                    let #bindings = match #bindings {
+                                        Some(value) => value,
+                                        None => #missing,
+                                    };
+                                )*
+
+                                Ok(#construct)
+                            }
+                        });
+
+                        let read = read_variant(quote!(struct_variant), quote!(&[#(#names),*]));
+
+                        quote! {
+                            #variant_name => {
+                                #read
+                            }
+                        }
+                    } else {
+                        let read = read_variant(quote!(tuple_variant), quote!(#len));
+
+                        quote! {
+                            #variant_name => {
+                                #read
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let variant_visitor = if seq_arms.len() > 0 {
+        quote! {
+            struct VariantVisitor<'a, E>(&'a mut E);
+
+            impl #variant_impl_generics Visitor<'de> for VariantVisitor<'a, #ident #ty_generics>
+                #where_clause
+            {
+                type Value = ();
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    formatter.write_str(concat!("variant of enum ", #name))
+                }
+
+                #[allow(unused_mut, unreachable_patterns)]
+                fn visit_seq<V>(self, mut seq: V) -> Result<(), V::Error>
+                where
+                    V: SeqAccess<'de>,
+                {
+                    match self.0 {
+                        #(#seq_arms)*
+                        _ => {}
+                    }
+
+                    Ok(())
+                }
+
+                #[allow(unused_mut, unused_variables, unreachable_patterns)]
+                fn visit_map<V>(self, mut map: V) -> Result<(), V::Error>
+                where
+                    V: MapAccess<'de>,
+                {
+                    match self.0 {
+                        #(#map_arms)*
+                        _ => {}
+                    }
+
+                    Ok(())
+                }
+            }
+
+            struct NewVariantVisitor<E>(&'static str, std::marker::PhantomData<E>);
+
+            impl #impl_generics Visitor<'de> for NewVariantVisitor<#ident #ty_generics>
+                #where_clause
+            {
+                type Value = #ident #ty_generics;
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    formatter.write_str(concat!("variant of enum ", #name))
+                }
+
+                #[allow(unused_mut)]
+                fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+                where
+                    V: SeqAccess<'de>,
+                {
+                    match self.0 {
+                        #(#new_seq_arms)*
+                        _ => Err(V::Error::unknown_variant(self.0, VARIANTS)),
+                    }
+                }
+
+                #[allow(unused_mut, unused_variables)]
+                fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
+                where
+                    V: MapAccess<'de>,
+                {
+                    match self.0 {
+                        #(#new_map_arms)*
+                        _ => Err(V::Error::invalid_type(Unexpected::Map, &self)),
+                    }
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    quote! {
+        #[allow(unused_imports)]
+        use quartz_engine::core::serde::de::{
+            Deserializer, EnumAccess, Error, IgnoredAny, MapAccess, SeqAccess, Unexpected,
+            VariantAccess, Visitor,
+        };
+        #[allow(unused_imports)]
+        use quartz_engine::core::reflect::field::Identifier;
+
+        #variant_visitor
+
+        impl #impl_generics Visitor<'de> for &mut #ident #ty_generics #where_clause {
+            type Value = ();
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str(concat!("enum ", #name))
+            }
+
+            fn visit_enum<A>(self, data: A) -> Result<(), A::Error>
+            where
+                A: EnumAccess<'de>,
+            {
+                let (Identifier(variant), access) = data.variant::<Identifier>()?;
+
+                match variant.as_str() {
+                    #(#arms)*
+                    _ => return Err(A::Error::unknown_variant(&variant, VARIANTS)),
+                }
+
+                Ok(())
+            }
+        }
+
+        const VARIANTS: &[&str] = &[#(#variant_names),*];
+        deserializer.deserialize_enum(#name, VARIANTS, self)
+    }
+}
+
+fn fields(ident: &Ident, data: &Data) -> TokenStream {
+    let (fields, field, field_mut) = match data {
+        Data::Struct(data) => {
+            if let Fields::Unit = data.fields {
+                return quote!();
+            }
+
+            let members = struct_members(&data.fields);
+
+            let names = members.iter().map(|(_, name, _)| name).collect::<Vec<_>>();
+            let tys = members.iter().map(|(f, _, _)| &f.ty);
+            let members = members
+                .iter()
+                .map(|(_, _, member)| member)
+                .collect::<Vec<_>>();
+
+            (
+                quote! {
+                    vec![
+                        #(
+                            quartz_engine::core::reflect::FieldInfo::new(
+                                #names,
+                                std::any::type_name::<#tys>(),
+                            ),
+                        )*
+                    ]
+                },
+                quote! {
+                    match name {
+                        #(
+                            #names => (&FieldRef(&self.#members)).as_reflect(),
+                        )*
+                        _ => None,
+                    }
+                },
+                quote! {
+                    match name {
+                        #(
+                            #names => (&FieldMut::new(&mut self.#members)).as_reflect_mut(),
+                        )*
+                        _ => None,
+                    }
+                },
+            )
+        }
+        Data::Enum(data) => {
+            let mut fields = Vec::new();
+            let mut field = Vec::new();
+            let mut field_mut = Vec::new();
+
+            for variant in &data.variants {
+                let pattern = variant_pattern(ident, variant);
+                let any_pattern = variant_any_pattern(ident, variant);
+                let variant_fields = variant_fields(variant);
+
+                let names = variant_fields
+                    .iter()
+                    .map(|(_, name, _)| name)
+                    .collect::<Vec<_>>();
+                let tys = variant_fields.iter().map(|(f, _, _)| &f.ty);
+                let bindings = variant_fields
+                    .iter()
+                    .map(|(_, _, binding)| binding)
+                    .collect::<Vec<_>>();
+
+                fields.push(quote! {
+                    #any_pattern => vec![
+                        #(
+                            quartz_engine::core::reflect::FieldInfo::new(
+                                #names,
+                                std::any::type_name::<#tys>(),
+                            ),
+                        )*
+                    ],
+                });
+
+                field.push(quote! {
+                    #pattern => match name {
+                        #(
+                            #names => (&FieldRef(#bindings)).as_reflect(),
+                        )*
+                        _ => None,
+                    },
+                });
+
+                field_mut.push(quote! {
+                    #pattern => match name {
+                        #(
+                            #names => (&FieldMut::new(#bindings)).as_reflect_mut(),
+                        )*
+                        _ => None,
+                    },
+                });
+            }
+
+            (
+                quote!(match self { #(#fields)* }),
+                quote!(match self { #(#field)* }),
+                quote!(match self { #(#field_mut)* }),
+            )
+        }
+        _ => unimplemented!(),
+    };
+
+    quote! {
+        fn fields(&self) -> Vec<quartz_engine::core::reflect::FieldInfo> {
+            #fields
+        }
+
+        #[allow(unused_imports)]
+        fn field(&self, name: &str) -> Option<&dyn quartz_engine::core::reflect::Reflect> {
+            use quartz_engine::core::reflect::field::{
+                AsReflect, AsReflectFallback, FieldRef,
+            };
+
+            #field
+        }
+
+        #[allow(unused_imports)]
+        fn field_mut(&mut self, name: &str) -> Option<&mut dyn quartz_engine::core::reflect::Reflect> {
+            use quartz_engine::core::reflect::field::{
+                AsReflectMut, AsReflectMutFallback, FieldMut,
+            };
+
+            #field_mut
+        }
+    }
+}
+
 fn serialize(ident: &Ident, data: &Data) -> TokenStream {
     let name = ident.to_string();
 
@@ -360,8 +1232,109 @@ fn serialize(ident: &Ident, data: &Data) -> TokenStream {
                     }
                 }
             }
-            _ => unimplemented!(),
+            Fields::Unnamed(fields) => {
+                let members = struct_members(&data.fields);
+                let len = members.len();
+
+                if fields.unnamed.len() == 1 && len == 1 {
+                    let member = &members[0].2;
+
+                    quote! {
+                        serializer.serialize_newtype_struct(#name, &self.#member)
+                    }
+                } else {
+                    let members = members.iter().map(|(_, _, member)| member);
+
+                    quote! {
+                        use quartz_engine::core::serde::ser::SerializeTupleStruct;
+
+                        let mut state = serializer.serialize_tuple_struct(#name, #len)?;
+
+                        #(
+                            state.serialize_field(&self.#members)?;
+                        )*
+
+                        state.end()
+                    }
+                }
+            }
+            Fields::Unit => quote! {
+                serializer.serialize_unit_struct(#name)
+            },
         },
+        Data::Enum(data) => {
+            let arms = data.variants.iter().enumerate().map(|(index, variant)| {
+                let index = index as u32;
+                let variant_name = variant.ident.to_string();
+                let pattern = variant_pattern(ident, variant);
+                let fields = variant_fields(variant);
+                let len = fields.len();
+
+                let names = fields.iter().map(|(_, name, _)| name);
+                let bindings = fields.iter().map(|(_, _, binding)| binding);
+
+                match &variant.fields {
+                    Fields::Unit => quote! {
+                        #pattern => serializer.serialize_unit_variant(#name, #index, #variant_name),
+                    },
+                    Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 && len == 1 => {
+                        let binding = &fields[0].2;
+
+                        quote! {
+                            #pattern => serializer.serialize_newtype_variant(
+                                #name,
+                                #index,
+                                #variant_name,
+                                #binding,
+                            ),
+                        }
+                    }
+                    Fields::Unnamed(_) => quote! {
+                        #pattern => {
+                            let mut state = serializer.serialize_tuple_variant(
+                                #name,
+                                #index,
+                                #variant_name,
+                                #len,
+                            )?;
+
+                            #(
+                                state.serialize_field(#bindings)?;
+                            )*
+
+                            state.end()
+                        }
+                    },
+                    Fields::Named(_) => quote! {
+                        #pattern => {
+                            let mut state = serializer.serialize_struct_variant(
+                                #name,
+                                #index,
+                                #variant_name,
+                                #len,
+                            )?;
+
+                            #(
+                                state.serialize_field(#names, #bindings)?;
+                            )*
+
+                            state.end()
+                        }
+                    },
+                }
+            });
+
+            quote! {
+                #[allow(unused_imports)]
+                use quartz_engine::core::serde::ser::{
+                    SerializeStructVariant, SerializeTupleVariant,
+                };
+
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
         _ => unimplemented!(),
     }
 }
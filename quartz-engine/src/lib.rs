@@ -1,5 +1,6 @@
 pub use quartz_engine_core as core;
 pub use quartz_engine_core::egui;
+pub use quartz_engine_core::reflect_value;
 pub use quartz_engine_core::register_types;
 pub use quartz_engine_core::render;
 use quartz_engine_core::types::Types;
@@ -14,11 +14,7 @@ pub struct Input<T: InputEvent> {
 
 impl<T: InputEvent> Default for Input<T> {
     fn default() -> Self {
-        Self {
-            pressed: Default::default(),
-            held: Default::default(),
-            released: Default::default(),
-        }
+        Self::new()
     }
 }
 
@@ -32,7 +28,10 @@ impl<T: InputEvent> Input<T> {
     }
 
     pub fn press(&mut self, event: T) {
-        self.pressed.insert(event.clone());
+        if !self.held.contains(&event) {
+            self.pressed.insert(event.clone());
+        }
+
         self.held.insert(event);
     }
 
@@ -41,6 +40,12 @@ impl<T: InputEvent> Input<T> {
         self.released.insert(event);
     }
 
+    pub fn release_all(&mut self) {
+        for event in self.held.drain() {
+            self.released.insert(event);
+        }
+    }
+
     pub fn update(&mut self) {
         self.pressed.clear();
         self.released.clear();
@@ -57,4 +62,16 @@ impl<T: InputEvent> Input<T> {
     pub fn released(&self, event: &T) -> bool {
         self.released.contains(event)
     }
+
+    pub fn iter_pressed(&self) -> impl Iterator<Item = &T> {
+        self.pressed.iter()
+    }
+
+    pub fn iter_held(&self) -> impl Iterator<Item = &T> {
+        self.held.iter()
+    }
+
+    pub fn iter_released(&self) -> impl Iterator<Item = &T> {
+        self.released.iter()
+    }
 }
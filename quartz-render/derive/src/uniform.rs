@@ -70,7 +70,11 @@ fn data(crate_path: &TokenStream, data: &Data) -> TokenStream {
                     let ty = &f.ty;
 
                     quote_spanned! {ident.span()=>
-                        #crate_path::uniform::append_aligned(&mut data, &self.#ident, #ty::alignment());
+                        #crate_path::uniform::append_aligned(
+                            &mut data,
+                            &self.#ident,
+                            <#ty as #crate_path::uniform::Uniform>::alignment(),
+                        );
                     }
                 });
 
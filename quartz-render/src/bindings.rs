@@ -1,7 +1,8 @@
+use crate::buffer::Buffer;
 use crate::instance::*;
+use crate::render_pipeline::PipelineLayout;
 use serde::de;
 use std::{collections::HashMap, ops::Deref, sync::Arc};
-use wgpu::util::DeviceExt;
 
 pub trait Bindable {
     fn new_binding(&self) -> Result<Binding, ()>;
@@ -21,7 +22,18 @@ pub enum Binding {
     },
     UniformBlock {
         data: Vec<u8>,
-        buffer: Option<Arc<wgpu::Buffer>>,
+        buffer: Option<Buffer<u8>>,
+    },
+    UniformBuffer {
+        buffer: Arc<wgpu::Buffer>,
+    },
+    StorageBuffer {
+        buffer: Arc<wgpu::Buffer>,
+    },
+    StorageTexture {
+        view: Arc<wgpu::TextureView>,
+        dimension: wgpu::TextureViewDimension,
+        format: wgpu::TextureFormat,
     },
 }
 
@@ -40,18 +52,9 @@ impl Binding {
         match self {
             Binding::UniformBlock { data, buffer } => {
                 if let Some(buffer) = buffer {
-                    instance.queue.write_buffer(buffer, 0, data);
+                    buffer.set_data(data, instance);
                 } else {
-                    let new_buffer =
-                        instance
-                            .device
-                            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                                label: Some("Uniform Buffer Binding"),
-                                contents: data,
-                                usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::UNIFORM,
-                            });
-
-                    *buffer = Some(Arc::new(new_buffer));
+                    *buffer = Some(Buffer::new(data, wgpu::BufferUsage::UNIFORM, instance));
                 };
             }
             _ => {}
@@ -62,7 +65,12 @@ impl Binding {
         let binding_resource = match self {
             Binding::Texture { view, .. } => wgpu::BindingResource::TextureView(view),
             Binding::Sampler { sampler, .. } => wgpu::BindingResource::Sampler(sampler),
-            Binding::UniformBlock { buffer, .. } => buffer.as_ref().unwrap().as_entire_binding(),
+            Binding::UniformBlock { buffer, .. } => {
+                buffer.as_ref().unwrap().buffer.as_entire_binding()
+            }
+            Binding::UniformBuffer { buffer } => buffer.as_entire_binding(),
+            Binding::StorageBuffer { buffer } => buffer.as_entire_binding(),
+            Binding::StorageTexture { view, .. } => wgpu::BindingResource::TextureView(view),
         };
 
         binding_resource
@@ -73,7 +81,8 @@ impl Binding {
 pub struct Bindings {
     pub(crate) bindings: HashMap<u32, HashMap<u32, (Binding, bool)>>,
     pub(crate) bind_groups: HashMap<u32, Arc<wgpu::BindGroup>>,
-    pub(crate) layout: HashMap<u32, wgpu::BindGroupLayout>,
+    /// Layout entries the bind groups were created with.
+    pub(crate) layout_entries: HashMap<u32, Vec<wgpu::BindGroupLayoutEntry>>,
 }
 
 impl Bindings {
@@ -81,7 +90,7 @@ impl Bindings {
         Self {
             bindings: HashMap::new(),
             bind_groups: HashMap::new(),
-            layout: HashMap::new(),
+            layout_entries: HashMap::new(),
         }
     }
 
@@ -99,8 +108,26 @@ impl Bindings {
         self.bindings.len()
     }
 
-    pub fn generate_groups(&mut self, instance: &Instance) {
-        for (set, group) in &mut self.bindings {
+    /// Prepares the bindings and recreates the bind groups of the sets `layout` uses, with
+    /// the layouts reflected from its shaders. Sets missing a binding are skipped.
+    pub fn generate_groups(&mut self, layout: &PipelineLayout, instance: &Instance) {
+        for (set, bind_group) in layout.bind_groups.iter().enumerate() {
+            let set = set as u32;
+
+            let bind_group_layout = match &bind_group.layout {
+                Some(bind_group_layout) => bind_group_layout,
+                None => continue,
+            };
+
+            let group = match self.bindings.get_mut(&set) {
+                Some(group) => group,
+                None => {
+                    log::error!("Bind group '{}' has no bindings", set);
+                    self.bind_groups.remove(&set);
+                    continue;
+                }
+            };
+
             let mut recreate = false;
 
             for (_index, (binding, changed)) in group.iter_mut() {
@@ -110,65 +137,56 @@ impl Bindings {
                 *changed = false;
             }
 
-            if !recreate {
+            let mut layout_entries = bind_group
+                .bindings
+                .values()
+                .map(|entry| entry.layout_entry())
+                .collect::<Vec<_>>();
+            layout_entries.sort_by_key(|entry| entry.binding);
+
+            if !recreate
+                && self.bind_groups.contains_key(&set)
+                && self.layout_entries.get(&set) == Some(&layout_entries)
+            {
                 continue;
             }
 
             log::trace!("Recreating bind group '{}'", set);
 
             let mut entries = Vec::new();
-            let mut layout_entries = Vec::new();
-
-            for (index, (binding, _changed)) in group {
-                layout_entries.push(wgpu::BindGroupLayoutEntry {
-                    binding: *index,
-                    count: None,
-                    visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
-                    ty: match binding {
-                        Binding::Sampler { .. } => wgpu::BindingType::Sampler {
-                            filtering: true,
-                            comparison: false,
-                        },
-                        Binding::Texture {
-                            dimension,
-                            multisampled,
-                            ..
-                        } => wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                            view_dimension: *dimension,
-                            multisampled: *multisampled,
-                        },
-                        Binding::UniformBlock { .. } => wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                    },
-                });
-
-                entries.push(wgpu::BindGroupEntry {
-                    binding: *index,
-                    resource: binding.binding_resource(),
-                });
-            }
 
-            let layout_desc = wgpu::BindGroupLayoutDescriptor {
-                label: Some("Binding group layout"),
-                entries: &layout_entries,
-            };
+            for entry in &layout_entries {
+                match group.get(&entry.binding) {
+                    Some((binding, _changed)) => entries.push(wgpu::BindGroupEntry {
+                        binding: entry.binding,
+                        resource: binding.binding_resource(),
+                    }),
+                    None => {
+                        log::error!(
+                            "Binding '{}' of bind group '{}' is not bound",
+                            entry.binding,
+                            set
+                        );
+                        break;
+                    }
+                }
+            }
 
-            let layout = instance.device.create_bind_group_layout(&layout_desc);
+            if entries.len() < layout_entries.len() {
+                self.bind_groups.remove(&set);
+                continue;
+            }
 
             let desc = wgpu::BindGroupDescriptor {
                 label: Some("Bind group"),
                 entries: &entries,
-                layout: &layout,
+                layout: bind_group_layout,
             };
 
             let bind_group = instance.device.create_bind_group(&desc);
 
-            self.layout.insert(*set, layout);
-            self.bind_groups.insert(*set, Arc::new(bind_group));
+            self.layout_entries.insert(set, layout_entries);
+            self.bind_groups.insert(set, Arc::new(bind_group));
         }
     }
 }
@@ -0,0 +1,269 @@
+use glam::*;
+
+/// Axis aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest box containing `points`, [`None`] if there are none.
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+
+        Some(points.fold(Self::new(first, first), |aabb, point| {
+            Self::new(aabb.min.min(*point), aabb.max.max(*point))
+        }))
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    /// Box containing this one after being transformed by `matrix`.
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let center = matrix.transform_point3(self.center());
+        let half_extents = self.half_extents();
+
+        let extents = matrix.x_axis.truncate().abs() * half_extents.x
+            + matrix.y_axis.truncate().abs() * half_extents.y
+            + matrix.z_axis.truncate().abs() * half_extents.z;
+
+        Self::new(center - extents, center + extents)
+    }
+
+    pub fn bounding_sphere(&self) -> Sphere {
+        Sphere {
+            center: self.center(),
+            radius: self.half_extents().length(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// The six planes bounding what a camera sees, normals pointing inwards.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near and far, as `normal.extend(distance)`.
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes of a wgpu style view projection, with depth from 0 to 1.
+    pub fn from_view_proj(view_proj: &Mat4) -> Self {
+        let rows = view_proj.transpose();
+        let (x, y, z, w) = (rows.x_axis, rows.y_axis, rows.z_axis, rows.w_axis);
+
+        let mut planes = [w + x, w - x, w + y, w - y, z, w - z];
+
+        for plane in &mut planes {
+            *plane /= plane.truncate().length();
+        }
+
+        Self { planes }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(point) + plane.w >= 0.0)
+    }
+
+    /// Returns true if any part of `sphere` may be inside.
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+
+    /// Returns true if any part of `aabb` may be inside.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+
+            // the corner furthest along the normal
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(-Vec3::ONE, Vec3::ONE)
+    }
+
+    /// Looks down -z, 1 to 100 units in front of the origin, with a 90 degree field of view.
+    fn perspective() -> Frustum {
+        let view_proj = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 100.0)
+            * Mat4::look_at_rh(Vec3::ZERO, -Vec3::Z, Vec3::Y);
+
+        Frustum::from_view_proj(&view_proj)
+    }
+
+    #[test]
+    fn from_points() {
+        let points = [
+            vec3(1.0, -2.0, 0.0),
+            vec3(-1.0, 3.0, 2.0),
+            vec3(0.0, 0.0, -4.0),
+        ];
+
+        assert_eq!(
+            Aabb::from_points(&points),
+            Some(Aabb::new(vec3(-1.0, -2.0, -4.0), vec3(1.0, 3.0, 2.0)))
+        );
+        assert_eq!(Aabb::from_points(&[]), None);
+    }
+
+    #[test]
+    fn transformed_translation_and_scale() {
+        let matrix =
+            Mat4::from_scale_rotation_translation(vec3(2.0, 1.0, 3.0), Quat::IDENTITY, Vec3::X);
+        let aabb = unit_box().transformed(&matrix);
+
+        assert!(aabb.min.abs_diff_eq(vec3(-1.0, -1.0, -3.0), 1e-5));
+        assert!(aabb.max.abs_diff_eq(vec3(3.0, 1.0, 3.0), 1e-5));
+    }
+
+    #[test]
+    fn transformed_rotation() {
+        let matrix = Mat4::from_rotation_y(std::f32::consts::FRAC_PI_4);
+        let aabb = unit_box().transformed(&matrix);
+
+        // the corners of the rotated box reach sqrt(2) along x and z
+        let reach = 2.0f32.sqrt();
+        assert!(aabb.min.abs_diff_eq(vec3(-reach, -1.0, -reach), 1e-5));
+        assert!(aabb.max.abs_diff_eq(vec3(reach, 1.0, reach), 1e-5));
+    }
+
+    #[test]
+    fn orthographic_planes() {
+        let view_proj = Mat4::orthographic_rh(-1.0, 1.0, -2.0, 2.0, 0.0, 10.0);
+        let frustum = Frustum::from_view_proj(&view_proj);
+
+        let expected = [
+            vec4(1.0, 0.0, 0.0, 1.0),
+            vec4(-1.0, 0.0, 0.0, 1.0),
+            vec4(0.0, 1.0, 0.0, 2.0),
+            vec4(0.0, -1.0, 0.0, 2.0),
+            vec4(0.0, 0.0, -1.0, 0.0),
+            vec4(0.0, 0.0, 1.0, 10.0),
+        ];
+
+        for (plane, expected) in frustum.planes.iter().zip(&expected) {
+            assert!(
+                plane.abs_diff_eq(*expected, 1e-5),
+                "{} != {}",
+                plane,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn perspective_planes() {
+        let frustum = perspective();
+        let side = std::f32::consts::FRAC_1_SQRT_2;
+
+        let expected = [
+            vec4(side, 0.0, -side, 0.0),
+            vec4(-side, 0.0, -side, 0.0),
+            vec4(0.0, side, -side, 0.0),
+            vec4(0.0, -side, -side, 0.0),
+            vec4(0.0, 0.0, -1.0, -1.0),
+            vec4(0.0, 0.0, 1.0, 100.0),
+        ];
+
+        for (plane, expected) in frustum.planes.iter().zip(&expected) {
+            assert!(
+                plane.abs_diff_eq(*expected, 1e-3),
+                "{} != {}",
+                plane,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn contains_point() {
+        let frustum = perspective();
+
+        assert!(frustum.contains_point(vec3(0.0, 0.0, -10.0)));
+        assert!(frustum.contains_point(vec3(4.0, -4.0, -5.0)));
+        assert!(!frustum.contains_point(vec3(0.0, 0.0, -0.5)));
+        assert!(!frustum.contains_point(vec3(0.0, 0.0, -101.0)));
+        assert!(!frustum.contains_point(vec3(6.0, 0.0, -5.0)));
+        assert!(!frustum.contains_point(vec3(0.0, 0.0, 10.0)));
+    }
+
+    #[test]
+    fn intersects_inside() {
+        let frustum = perspective();
+        let aabb = Aabb::new(vec3(-1.0, -1.0, -12.0), vec3(1.0, 1.0, -10.0));
+
+        assert!(frustum.intersects_aabb(&aabb));
+        assert!(frustum.intersects_sphere(&aabb.bounding_sphere()));
+    }
+
+    #[test]
+    fn intersects_outside() {
+        let frustum = perspective();
+
+        // behind the camera, past the far plane and beside the left plane
+        let boxes = [
+            Aabb::new(vec3(-1.0, -1.0, 2.0), vec3(1.0, 1.0, 4.0)),
+            Aabb::new(vec3(-1.0, -1.0, -120.0), vec3(1.0, 1.0, -110.0)),
+            Aabb::new(vec3(-20.0, -1.0, -11.0), vec3(-15.0, 1.0, -9.0)),
+        ];
+
+        for aabb in &boxes {
+            assert!(!frustum.intersects_aabb(aabb), "{:?}", aabb);
+            assert!(
+                !frustum.intersects_sphere(&aabb.bounding_sphere()),
+                "{:?}",
+                aabb
+            );
+        }
+    }
+
+    #[test]
+    fn intersects_straddling() {
+        let frustum = perspective();
+
+        // crossing the near, far and right planes
+        let boxes = [
+            Aabb::new(vec3(-1.0, -1.0, -2.0), vec3(1.0, 1.0, 0.0)),
+            Aabb::new(vec3(-1.0, -1.0, -105.0), vec3(1.0, 1.0, -95.0)),
+            Aabb::new(vec3(8.0, -1.0, -11.0), vec3(12.0, 1.0, -9.0)),
+        ];
+
+        for aabb in &boxes {
+            assert!(frustum.intersects_aabb(aabb), "{:?}", aabb);
+            assert!(
+                frustum.intersects_sphere(&aabb.bounding_sphere()),
+                "{:?}",
+                aabb
+            );
+        }
+    }
+}
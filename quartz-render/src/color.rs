@@ -29,24 +29,26 @@ impl Color {
 
     pub fn from_bytes(bytes: &[u8], format: &wgpu::TextureFormat) -> Self {
         match format {
-            wgpu::TextureFormat::Rgba8UnormSrgb => Self::rgba(
+            wgpu::TextureFormat::Rgba8UnormSrgb | wgpu::TextureFormat::Rgba8Unorm => Self::rgba(
                 bytes[0] as f32 / 255.0,
                 bytes[1] as f32 / 255.0,
                 bytes[2] as f32 / 255.0,
                 bytes[3] as f32 / 255.0,
             ),
+            wgpu::TextureFormat::Rgba32Float => *bytemuck::from_bytes(&bytes[..16]),
             _ => panic!("format not supported"),
         }
     }
 
     pub fn into_bytes(&self, format: &wgpu::TextureFormat) -> Vec<u8> {
         match format {
-            wgpu::TextureFormat::Rgba8UnormSrgb => vec![
+            wgpu::TextureFormat::Rgba8UnormSrgb | wgpu::TextureFormat::Rgba8Unorm => vec![
                 (self.r * 255.0).round() as u8,
                 (self.g * 255.0).round() as u8,
                 (self.b * 255.0).round() as u8,
                 (self.a * 255.0).round() as u8,
             ],
+            wgpu::TextureFormat::Rgba32Float => bytemuck::bytes_of(self).to_vec(),
             _ => panic!("format not supported"),
         }
     }
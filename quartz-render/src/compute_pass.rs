@@ -0,0 +1,118 @@
+use crate::prelude::*;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub(crate) enum ComputeCommand {
+    SetPipeline {
+        pipeline: Arc<wgpu::ComputePipeline>,
+    },
+    SetBindGroup {
+        set: u32,
+        bind_group: Arc<wgpu::BindGroup>,
+    },
+    Dispatch {
+        x: u32,
+        y: u32,
+        z: u32,
+    },
+    DispatchIndirect {
+        buffer: Arc<wgpu::Buffer>,
+        offset: wgpu::BufferAddress,
+    },
+}
+
+/// Records dispatches, they're encoded when the pass is dropped.
+pub struct ComputePass<'a, 'b> {
+    pub(crate) commands: Vec<ComputeCommand>,
+    pub(crate) ctx: &'a mut RenderCtx<'b>,
+    pub(crate) layout: Option<&'a PipelineLayout>,
+}
+
+impl<'a, 'b> ComputePass<'a, 'b> {
+    pub fn set_pipeline(&mut self, pipeline: &'a ComputePipeline) -> &mut Self {
+        self.layout = Some(&pipeline.layout);
+
+        self.commands.push(ComputeCommand::SetPipeline {
+            pipeline: pipeline.pipeline.clone(),
+        });
+
+        self
+    }
+
+    pub fn set_bindings(&mut self, bindings: &mut Bindings) -> &mut Self {
+        if let Some(layout) = self.layout {
+            bindings.generate_groups(layout, self.ctx.instance);
+        }
+
+        for (set, bind_group) in &bindings.bind_groups {
+            self.commands.push(ComputeCommand::SetBindGroup {
+                set: (*set) as u32,
+                bind_group: bind_group.clone(),
+            });
+        }
+
+        self
+    }
+
+    pub fn set_bind_groups(&mut self, bind_groups: &Vec<Arc<wgpu::BindGroup>>) -> &mut Self {
+        for (set, bind_group) in bind_groups.iter().enumerate() {
+            self.commands.push(ComputeCommand::SetBindGroup {
+                set: set as u32,
+                bind_group: bind_group.clone(),
+            });
+        }
+
+        self
+    }
+
+    /// Dispatches `x * y * z` work groups, the group size is declared in the shader.
+    pub fn dispatch(&mut self, x: u32, y: u32, z: u32) -> &mut Self {
+        self.commands.push(ComputeCommand::Dispatch { x, y, z });
+
+        self
+    }
+
+    /// Dispatches with the group counts at element `offset` of `buffer`.
+    pub fn dispatch_indirect(
+        &mut self,
+        buffer: &Buffer<DispatchIndirect>,
+        offset: u64,
+    ) -> &mut Self {
+        self.commands.push(ComputeCommand::DispatchIndirect {
+            buffer: buffer.raw().clone(),
+            offset: offset * std::mem::size_of::<DispatchIndirect>() as u64,
+        });
+
+        self
+    }
+}
+
+impl Drop for ComputePass<'_, '_> {
+    fn drop(&mut self) {
+        let mut compute_pass =
+            self.ctx
+                .encoder
+                .as_mut()
+                .unwrap()
+                .begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Compute Pass"),
+                });
+
+        for command in &self.commands {
+            match command {
+                ComputeCommand::SetPipeline { pipeline } => {
+                    compute_pass.set_pipeline(&pipeline);
+                }
+                ComputeCommand::SetBindGroup { set, bind_group } => {
+                    compute_pass.set_bind_group(*set, &bind_group, &[]);
+                }
+                ComputeCommand::Dispatch { x, y, z } => {
+                    compute_pass.dispatch(*x, *y, *z);
+                }
+                ComputeCommand::DispatchIndirect { buffer, offset } => {
+                    compute_pass.dispatch_indirect(&buffer, *offset);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Pipeline for [`ComputePass`]es.
+///
+/// The layout is reflected from the shader like [`RenderPipeline`]'s, and is used with
+/// [`Bindings`] the same way.
+pub struct ComputePipeline {
+    pub(crate) shader: ComputeShader,
+    pub(crate) layout: PipelineLayout,
+    pub(crate) pipeline: Arc<wgpu::ComputePipeline>,
+}
+
+impl ComputePipeline {
+    /// Creates a pipeline.
+    pub fn new(
+        shader: ComputeShader,
+        instance: &Instance,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (layout, pipeline) = Self::build(&shader, instance)?;
+
+        Ok(Self {
+            shader,
+            layout,
+            pipeline: Arc::new(pipeline),
+        })
+    }
+
+    pub fn shader(&self) -> &ComputeShader {
+        &self.shader
+    }
+
+    pub fn layout(&self) -> &PipelineLayout {
+        &self.layout
+    }
+
+    /// Rebuilds the pipeline with a new shader, keeping the old one if it fails.
+    pub fn rebuild(
+        &mut self,
+        shader: ComputeShader,
+        instance: &Instance,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (layout, pipeline) = Self::build(&shader, instance)?;
+
+        self.shader = shader;
+        self.layout = layout;
+        self.pipeline = Arc::new(pipeline);
+
+        Ok(())
+    }
+
+    /// Reloads the shader and rebuilds the pipeline if the shader was loaded with
+    /// [`ComputeShader::load`] and its file changed since.
+    ///
+    /// Returns true if the pipeline was rebuilt. A failed reload isn't retried until the
+    /// file changes again.
+    pub fn reload(&mut self, instance: &Instance) -> Result<bool, Box<dyn std::error::Error>> {
+        let modified = match self.shader.changed() {
+            Some(modified) => modified,
+            None => return Ok(false),
+        };
+
+        self.shader.modified = Some(modified);
+
+        let shader = self.shader.reload()?;
+        self.rebuild(shader, instance)?;
+
+        Ok(true)
+    }
+
+    fn build(
+        shader: &ComputeShader,
+        instance: &Instance,
+    ) -> Result<(PipelineLayout, wgpu::ComputePipeline), Box<dyn std::error::Error>> {
+        let reflection = spirv_reflect::ShaderModule::load_u8_data(shader.spirv.as_binary_u8())?;
+
+        let mut bind_groups = crate::render_pipeline::reflect_bind_groups(
+            &[(
+                &reflection,
+                shader.spirv.as_binary(),
+                wgpu::ShaderStage::COMPUTE,
+            )],
+            instance,
+        )?;
+        let layouts = crate::render_pipeline::create_bind_group_layouts(&mut bind_groups, instance);
+
+        let layout = instance
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Compute Pipeline Layout"),
+                bind_group_layouts: &layouts,
+                push_constant_ranges: &[],
+            });
+
+        let module = shader.to_module(instance);
+
+        let pipeline = instance
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Compute Pipeline"),
+                layout: Some(&layout),
+                module: &module,
+                entry_point: "main",
+            });
+
+        let layout = PipelineLayout {
+            bind_groups,
+            vertex_attributes: HashMap::new(),
+        };
+
+        Ok((layout, pipeline))
+    }
+}
@@ -0,0 +1,600 @@
+use anyhow::{anyhow, bail};
+use std::path::Path;
+
+/// Decoded image data ready to be uploaded to a texture.
+///
+/// Decoding happens entirely on the cpu, the upload is done by `Texture::from_image`.
+#[derive(Clone, Debug)]
+pub struct ImageData {
+    pub width: u32,
+    pub height: u32,
+    /// Number of array layers, 6 for cube maps.
+    pub layers: u32,
+    pub format: wgpu::TextureFormat,
+    /// Mip levels starting at full size, each containing every layer tightly packed.
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl ImageData {
+    /// Loads an image file, picking the decoder by extension.
+    ///
+    /// `.ktx2` and `.dds` files keep the format they're stored in if it only differs from
+    /// `format` by srgb, everything else is decoded with [`ImageData::from_image_bytes`].
+    pub fn load(path: impl AsRef<Path>, format: wgpu::TextureFormat) -> anyhow::Result<Self> {
+        let (bytes, extension) = read(path.as_ref())?;
+
+        match extension.as_str() {
+            "ktx2" => Self::from_ktx2(&bytes)?.with_format(format),
+            "dds" => Self::from_dds(&bytes)?.with_format(format),
+            _ => Self::from_image_bytes(&bytes, format),
+        }
+    }
+
+    /// Loads an image file keeping the format it's stored in.
+    ///
+    /// `.ktx2` and `.dds` files keep their format, including compressed ones, everything else
+    /// is decoded with [`ImageData::from_image_bytes_native`].
+    pub fn load_native(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let (bytes, extension) = read(path.as_ref())?;
+
+        match extension.as_str() {
+            "ktx2" => Self::from_ktx2(&bytes),
+            "dds" => Self::from_dds(&bytes),
+            _ => Self::from_image_bytes_native(&bytes),
+        }
+    }
+
+    /// Decodes a png, jpeg or hdr image, hdr images into `Rgba16Float` to keep values above 1
+    /// and everything else into `Rgba8UnormSrgb`.
+    pub fn from_image_bytes_native(bytes: &[u8]) -> anyhow::Result<Self> {
+        let format = if image::guess_format(bytes)? == image::ImageFormat::Hdr {
+            wgpu::TextureFormat::Rgba16Float
+        } else {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        };
+
+        Self::from_image_bytes(bytes, format)
+    }
+
+    /// Decodes a png, jpeg or hdr image into `format`.
+    ///
+    /// Supported formats are `Rgba8UnormSrgb`, `Rgba8Unorm`, `Rgba16Float` and `Rgba32Float`.
+    /// Pixels are stored as they are in the file, the format only decides how they're
+    /// sampled, so color textures should use srgb and data like normal maps should use a
+    /// linear format.
+    pub fn from_image_bytes(bytes: &[u8], format: wgpu::TextureFormat) -> anyhow::Result<Self> {
+        let (width, height, data) = match format {
+            wgpu::TextureFormat::Rgba8UnormSrgb | wgpu::TextureFormat::Rgba8Unorm => {
+                let image = image::load_from_memory(bytes)?.into_rgba8();
+                let (width, height) = image.dimensions();
+
+                (width, height, image.into_raw())
+            }
+            wgpu::TextureFormat::Rgba16Float => {
+                let (width, height, pixels) = decode_float(bytes)?;
+
+                let data = pixels
+                    .into_iter()
+                    .flat_map(|value| half::f16::from_f32(value).to_ne_bytes().to_vec())
+                    .collect();
+
+                (width, height, data)
+            }
+            wgpu::TextureFormat::Rgba32Float => {
+                let (width, height, pixels) = decode_float(bytes)?;
+
+                let data = pixels
+                    .into_iter()
+                    .flat_map(|value| value.to_ne_bytes().to_vec())
+                    .collect();
+
+                (width, height, data)
+            }
+            _ => bail!("can't decode images into {:?}", format),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            layers: 1,
+            format,
+            levels: vec![data],
+        })
+    }
+
+    /// Reads a ktx2 file, supercompressed and basis universal files aren't supported.
+    pub fn from_ktx2(bytes: &[u8]) -> anyhow::Result<Self> {
+        let reader = ktx2::Reader::new(bytes)?;
+        let header = reader.header();
+
+        if header.supercompression_scheme.is_some() {
+            bail!("supercompressed ktx2 files aren't supported");
+        }
+
+        if header.pixel_depth > 1 {
+            bail!("3d ktx2 textures aren't supported");
+        }
+
+        let format = header
+            .format
+            .ok_or_else(|| anyhow!("ktx2 files without a format aren't supported"))?;
+
+        let format = ktx2_format(format)
+            .ok_or_else(|| anyhow!("ktx2 format {:?} isn't supported", format))?;
+
+        Ok(Self {
+            width: header.pixel_width,
+            height: header.pixel_height.max(1),
+            layers: header.layer_count.max(1) * header.face_count,
+            format,
+            levels: reader.levels().map(|level| level.to_vec()).collect(),
+        })
+    }
+
+    /// Reads a dds file, both legacy and dx10 headers are supported.
+    pub fn from_dds(bytes: &[u8]) -> anyhow::Result<Self> {
+        let dds = ddsfile::Dds::read(bytes)?;
+
+        if dds.get_depth() > 1 {
+            bail!("3d dds textures aren't supported");
+        }
+
+        let format = match (dds.get_dxgi_format(), dds.get_d3d_format()) {
+            (Some(format), _) => dxgi_format(format)
+                .ok_or_else(|| anyhow!("dds format {:?} isn't supported", format))?,
+            (None, Some(format)) => d3d_format(format)
+                .ok_or_else(|| anyhow!("dds format {:?} isn't supported", format))?,
+            (None, None) => bail!("dds file has an unknown format"),
+        };
+
+        let width = dds.get_width();
+        let height = dds.get_height();
+        let layers = dds.get_num_array_layers();
+        let level_count = dds.get_num_mipmap_levels().max(1);
+
+        let mut levels = vec![Vec::new(); level_count as usize];
+
+        // dds stores every mip level of a layer before the next layer, while we store every
+        // layer of a mip level together
+        for layer in 0..layers {
+            let mut data = dds.get_data(layer)?;
+
+            for (level, level_data) in levels.iter_mut().enumerate() {
+                let size = level_size(format, width >> level, height >> level);
+
+                if data.len() < size {
+                    bail!("dds file is truncated");
+                }
+
+                level_data.extend_from_slice(&data[..size]);
+                data = &data[size..];
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            layers,
+            format,
+            levels,
+        })
+    }
+
+    /// Changes between the srgb and linear version of the same format, the data is kept as is.
+    pub fn with_format(mut self, format: wgpu::TextureFormat) -> anyhow::Result<Self> {
+        if linear_format(self.format) != linear_format(format) {
+            bail!("image format {:?} doesn't match {:?}", self.format, format);
+        }
+
+        self.format = format;
+
+        Ok(self)
+    }
+
+    /// Size of a single layer of mip `level` in bytes.
+    pub fn level_size(&self, level: u32) -> usize {
+        level_size(self.format, self.width >> level, self.height >> level)
+    }
+}
+
+/// Reads a file and its lowercase extension.
+fn read(path: &Path) -> anyhow::Result<(Vec<u8>, String)> {
+    let bytes = std::fs::read(path)?;
+
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    Ok((bytes, extension))
+}
+
+/// Decodes an image into rgba floats, hdr images are decoded directly to keep values above 1.
+fn decode_float(bytes: &[u8]) -> anyhow::Result<(u32, u32, Vec<f32>)> {
+    if image::guess_format(bytes)? == image::ImageFormat::Hdr {
+        let decoder = image::codecs::hdr::HdrDecoder::new(bytes)?;
+        let metadata = decoder.metadata();
+
+        let pixels = decoder
+            .read_image_hdr()?
+            .into_iter()
+            .flat_map(|pixel| {
+                let [r, g, b] = pixel.0;
+                vec![r, g, b, 1.0]
+            })
+            .collect();
+
+        Ok((metadata.width, metadata.height, pixels))
+    } else {
+        let image = image::load_from_memory(bytes)?.into_rgba16();
+        let (width, height) = image.dimensions();
+
+        let pixels = image
+            .into_raw()
+            .into_iter()
+            .map(|value| value as f32 / u16::MAX as f32)
+            .collect();
+
+        Ok((width, height, pixels))
+    }
+}
+
+/// Size in bytes of a `width` by `height` image, rounded up to whole blocks.
+fn level_size(format: wgpu::TextureFormat, width: u32, height: u32) -> usize {
+    let info = format.describe();
+    let (block_width, block_height) = info.block_dimensions;
+
+    let blocks_x = (width.max(1) + block_width as u32 - 1) / block_width as u32;
+    let blocks_y = (height.max(1) + block_height as u32 - 1) / block_height as u32;
+
+    blocks_x as usize * blocks_y as usize * info.block_size as usize
+}
+
+fn linear_format(format: wgpu::TextureFormat) -> wgpu::TextureFormat {
+    use wgpu::TextureFormat::*;
+
+    match format {
+        Rgba8UnormSrgb => Rgba8Unorm,
+        Bgra8UnormSrgb => Bgra8Unorm,
+        Bc1RgbaUnormSrgb => Bc1RgbaUnorm,
+        Bc2RgbaUnormSrgb => Bc2RgbaUnorm,
+        Bc3RgbaUnormSrgb => Bc3RgbaUnorm,
+        Bc7RgbaUnormSrgb => Bc7RgbaUnorm,
+        Etc2RgbUnormSrgb => Etc2RgbUnorm,
+        Etc2RgbA1UnormSrgb => Etc2RgbA1Unorm,
+        Etc2RgbA8UnormSrgb => Etc2RgbA8Unorm,
+        Astc4x4RgbaUnormSrgb => Astc4x4RgbaUnorm,
+        format => format,
+    }
+}
+
+fn ktx2_format(format: ktx2::Format) -> Option<wgpu::TextureFormat> {
+    use ktx2::Format;
+    use wgpu::TextureFormat::*;
+
+    Some(match format {
+        Format::R8_UNORM => R8Unorm,
+        Format::R8G8_UNORM => Rg8Unorm,
+        Format::R8G8B8A8_UNORM => Rgba8Unorm,
+        Format::R8G8B8A8_SRGB => Rgba8UnormSrgb,
+        Format::B8G8R8A8_UNORM => Bgra8Unorm,
+        Format::B8G8R8A8_SRGB => Bgra8UnormSrgb,
+        Format::R16G16B16A16_SFLOAT => Rgba16Float,
+        Format::R32_SFLOAT => R32Float,
+        Format::R32G32B32A32_SFLOAT => Rgba32Float,
+        Format::BC1_RGBA_UNORM_BLOCK => Bc1RgbaUnorm,
+        Format::BC1_RGBA_SRGB_BLOCK => Bc1RgbaUnormSrgb,
+        Format::BC2_UNORM_BLOCK => Bc2RgbaUnorm,
+        Format::BC2_SRGB_BLOCK => Bc2RgbaUnormSrgb,
+        Format::BC3_UNORM_BLOCK => Bc3RgbaUnorm,
+        Format::BC3_SRGB_BLOCK => Bc3RgbaUnormSrgb,
+        Format::BC4_UNORM_BLOCK => Bc4RUnorm,
+        Format::BC4_SNORM_BLOCK => Bc4RSnorm,
+        Format::BC5_UNORM_BLOCK => Bc5RgUnorm,
+        Format::BC5_SNORM_BLOCK => Bc5RgSnorm,
+        Format::BC6H_UFLOAT_BLOCK => Bc6hRgbUfloat,
+        Format::BC6H_SFLOAT_BLOCK => Bc6hRgbSfloat,
+        Format::BC7_UNORM_BLOCK => Bc7RgbaUnorm,
+        Format::BC7_SRGB_BLOCK => Bc7RgbaUnormSrgb,
+        Format::ETC2_R8G8B8_UNORM_BLOCK => Etc2RgbUnorm,
+        Format::ETC2_R8G8B8_SRGB_BLOCK => Etc2RgbUnormSrgb,
+        Format::ETC2_R8G8B8A1_UNORM_BLOCK => Etc2RgbA1Unorm,
+        Format::ETC2_R8G8B8A1_SRGB_BLOCK => Etc2RgbA1UnormSrgb,
+        Format::ETC2_R8G8B8A8_UNORM_BLOCK => Etc2RgbA8Unorm,
+        Format::ETC2_R8G8B8A8_SRGB_BLOCK => Etc2RgbA8UnormSrgb,
+        Format::ASTC_4x4_UNORM_BLOCK => Astc4x4RgbaUnorm,
+        Format::ASTC_4x4_SRGB_BLOCK => Astc4x4RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+fn dxgi_format(format: ddsfile::DxgiFormat) -> Option<wgpu::TextureFormat> {
+    use ddsfile::DxgiFormat;
+    use wgpu::TextureFormat::*;
+
+    Some(match format {
+        DxgiFormat::R8_UNorm => R8Unorm,
+        DxgiFormat::R8G8_UNorm => Rg8Unorm,
+        DxgiFormat::R8G8B8A8_UNorm => Rgba8Unorm,
+        DxgiFormat::R8G8B8A8_UNorm_sRGB => Rgba8UnormSrgb,
+        DxgiFormat::B8G8R8A8_UNorm => Bgra8Unorm,
+        DxgiFormat::B8G8R8A8_UNorm_sRGB => Bgra8UnormSrgb,
+        DxgiFormat::R16G16B16A16_Float => Rgba16Float,
+        DxgiFormat::R32_Float => R32Float,
+        DxgiFormat::R32G32B32A32_Float => Rgba32Float,
+        DxgiFormat::BC1_UNorm => Bc1RgbaUnorm,
+        DxgiFormat::BC1_UNorm_sRGB => Bc1RgbaUnormSrgb,
+        DxgiFormat::BC2_UNorm => Bc2RgbaUnorm,
+        DxgiFormat::BC2_UNorm_sRGB => Bc2RgbaUnormSrgb,
+        DxgiFormat::BC3_UNorm => Bc3RgbaUnorm,
+        DxgiFormat::BC3_UNorm_sRGB => Bc3RgbaUnormSrgb,
+        DxgiFormat::BC4_UNorm => Bc4RUnorm,
+        DxgiFormat::BC4_SNorm => Bc4RSnorm,
+        DxgiFormat::BC5_UNorm => Bc5RgUnorm,
+        DxgiFormat::BC5_SNorm => Bc5RgSnorm,
+        DxgiFormat::BC6H_UF16 => Bc6hRgbUfloat,
+        DxgiFormat::BC6H_SF16 => Bc6hRgbSfloat,
+        DxgiFormat::BC7_UNorm => Bc7RgbaUnorm,
+        DxgiFormat::BC7_UNorm_sRGB => Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+fn d3d_format(format: ddsfile::D3DFormat) -> Option<wgpu::TextureFormat> {
+    use ddsfile::D3DFormat;
+    use wgpu::TextureFormat::*;
+
+    Some(match format {
+        D3DFormat::A8B8G8R8 => Rgba8Unorm,
+        D3DFormat::A8R8G8B8 => Bgra8Unorm,
+        D3DFormat::A16B16G16R16F => Rgba16Float,
+        D3DFormat::A32B32G32R32F => Rgba32Float,
+        D3DFormat::DXT1 => Bc1RgbaUnorm,
+        D3DFormat::DXT3 => Bc2RgbaUnorm,
+        D3DFormat::DXT5 => Bc3RgbaUnorm,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut bytes)
+            .encode(pixels, width, height, image::ColorType::Rgba8)
+            .unwrap();
+        bytes
+    }
+
+    fn hdr(width: u32, height: u32, pixels: &[[f32; 3]]) -> Vec<u8> {
+        let pixels: Vec<_> = pixels.iter().map(|pixel| image::Rgb(*pixel)).collect();
+
+        let mut bytes = Vec::new();
+        image::codecs::hdr::HdrEncoder::new(&mut bytes)
+            .encode(&pixels, width as usize, height as usize)
+            .unwrap();
+        bytes
+    }
+
+    fn floats(data: &[u8]) -> Vec<f32> {
+        data.chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect()
+    }
+
+    fn halfs(data: &[u8]) -> Vec<f32> {
+        data.chunks_exact(2)
+            .map(|bytes| half::f16::from_ne_bytes([bytes[0], bytes[1]]).to_f32())
+            .collect()
+    }
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        assert_eq!(a.len(), b.len());
+
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 0.02, "{:?} != {:?}", a, b);
+        }
+    }
+
+    /// A ktx2 file with `levels` of `vk_format` data, `face_count` faces and no data format
+    /// descriptor.
+    fn ktx2(vk_format: u32, width: u32, height: u32, face_count: u32, levels: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&[
+            0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+        ]);
+
+        let header = [
+            vk_format,
+            1,
+            width,
+            height,
+            0,
+            0,
+            face_count,
+            levels.len() as u32,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ];
+
+        for value in &header {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        // supercompression global data
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+
+        let mut offset = (bytes.len() + levels.len() * 24) as u64;
+
+        for level in levels {
+            let length = level.len() as u64;
+
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes.extend_from_slice(&length.to_le_bytes());
+
+            offset += length;
+        }
+
+        for level in levels {
+            bytes.extend_from_slice(level);
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn decode_png() {
+        let pixels = [255, 0, 0, 255, 0, 128, 255, 64];
+        let image =
+            ImageData::from_image_bytes(&png(2, 1, &pixels), wgpu::TextureFormat::Rgba8UnormSrgb)
+                .unwrap();
+
+        assert_eq!((image.width, image.height, image.layers), (2, 1, 1));
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(image.levels, vec![pixels.to_vec()]);
+    }
+
+    #[test]
+    fn decode_png_to_float() {
+        let pixels = [255, 0, 51, 255];
+        let image =
+            ImageData::from_image_bytes(&png(1, 1, &pixels), wgpu::TextureFormat::Rgba32Float)
+                .unwrap();
+
+        assert_eq!(image.levels[0].len(), 16);
+        assert_close(&floats(&image.levels[0]), &[1.0, 0.0, 0.2, 1.0]);
+    }
+
+    #[test]
+    fn decode_hdr_keeps_range() {
+        let bytes = hdr(2, 1, &[[2.0, 0.5, 0.25], [8.0, 0.0, 1.0]]);
+        let expected = [2.0, 0.5, 0.25, 1.0, 8.0, 0.0, 1.0, 1.0];
+
+        let image = ImageData::from_image_bytes(&bytes, wgpu::TextureFormat::Rgba32Float).unwrap();
+        assert_close(&floats(&image.levels[0]), &expected);
+
+        let image = ImageData::from_image_bytes(&bytes, wgpu::TextureFormat::Rgba16Float).unwrap();
+        assert_eq!(image.levels[0].len(), 16);
+        assert_close(&halfs(&image.levels[0]), &expected);
+    }
+
+    #[test]
+    fn decode_unsupported_format() {
+        let bytes = png(1, 1, &[0, 0, 0, 255]);
+
+        assert!(ImageData::from_image_bytes(&bytes, wgpu::TextureFormat::R8Unorm).is_err());
+        assert!(ImageData::from_image_bytes(&[1, 2, 3], wgpu::TextureFormat::Rgba8Unorm).is_err());
+    }
+
+    #[test]
+    fn native_formats() {
+        let image = ImageData::from_image_bytes_native(&png(1, 1, &[0, 0, 0, 255])).unwrap();
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba8UnormSrgb);
+
+        let image = ImageData::from_image_bytes_native(&hdr(1, 1, &[[4.0, 1.0, 0.0]])).unwrap();
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba16Float);
+        assert_close(&halfs(&image.levels[0]), &[4.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn read_ktx2_compressed() {
+        // bc1 stores 4x4 blocks in 8 bytes
+        let level_0 = [1; 32];
+        let level_1 = [2; 8];
+        let bytes = ktx2(133, 8, 8, 1, &[&level_0, &level_1]);
+
+        let image = ImageData::from_ktx2(&bytes).unwrap();
+
+        assert_eq!(image.format, wgpu::TextureFormat::Bc1RgbaUnorm);
+        assert_eq!((image.width, image.height, image.layers), (8, 8, 1));
+        assert_eq!(image.levels, vec![level_0.to_vec(), level_1.to_vec()]);
+        assert_eq!(image.level_size(0), 32);
+        assert_eq!(image.level_size(1), 8);
+    }
+
+    #[test]
+    fn read_ktx2_cube() {
+        let level = [7; 6 * 4];
+        let bytes = ktx2(43, 1, 1, 6, &[&level]);
+
+        let image = ImageData::from_ktx2(&bytes).unwrap();
+
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(image.layers, 6);
+        assert_eq!(image.levels[0].len(), image.level_size(0) * 6);
+    }
+
+    #[test]
+    fn read_dds_layers() {
+        let mut dds = ddsfile::Dds::new_dxgi(ddsfile::NewDxgiParams {
+            height: 8,
+            width: 8,
+            depth: None,
+            format: ddsfile::DxgiFormat::BC1_UNorm_sRGB,
+            mipmap_levels: Some(2),
+            array_layers: Some(2),
+            caps2: None,
+            is_cubemap: false,
+            resource_dimension: ddsfile::D3D10ResourceDimension::Texture2D,
+            alpha_mode: ddsfile::AlphaMode::Unknown,
+        })
+        .unwrap();
+
+        // each layer stores all of its levels, 32 bytes then 8 bytes
+        assert_eq!(dds.data.len(), 80);
+        for (i, byte) in dds.data.iter_mut().enumerate() {
+            *byte = match i {
+                0..=31 => 0,
+                32..=39 => 1,
+                40..=71 => 10,
+                _ => 11,
+            };
+        }
+
+        let mut bytes = Vec::new();
+        dds.write(&mut bytes).unwrap();
+
+        let image = ImageData::from_dds(&bytes).unwrap();
+
+        assert_eq!(image.format, wgpu::TextureFormat::Bc1RgbaUnormSrgb);
+        assert_eq!((image.width, image.height, image.layers), (8, 8, 2));
+        assert_eq!(image.levels.len(), 2);
+        assert_eq!(image.levels[0], [vec![0; 32], vec![10; 32]].concat());
+        assert_eq!(image.levels[1], [vec![1; 8], vec![11; 8]].concat());
+    }
+
+    #[test]
+    fn change_format() {
+        let image =
+            ImageData::from_image_bytes(&png(1, 1, &[1, 2, 3, 4]), wgpu::TextureFormat::Rgba8Unorm)
+                .unwrap();
+
+        let srgb = image
+            .clone()
+            .with_format(wgpu::TextureFormat::Rgba8UnormSrgb)
+            .unwrap();
+        assert_eq!(srgb.format, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(srgb.levels, image.levels);
+
+        assert!(image
+            .with_format(wgpu::TextureFormat::Bc1RgbaUnorm)
+            .is_err());
+    }
+
+    #[test]
+    fn block_level_sizes() {
+        assert_eq!(level_size(wgpu::TextureFormat::Rgba8Unorm, 3, 2), 24);
+        // partial blocks are rounded up
+        assert_eq!(level_size(wgpu::TextureFormat::Bc1RgbaUnorm, 5, 5), 32);
+        assert_eq!(level_size(wgpu::TextureFormat::Bc7RgbaUnorm, 1, 1), 16);
+        assert_eq!(level_size(wgpu::TextureFormat::Rgba8Unorm, 0, 0), 4);
+    }
+}
@@ -1,10 +1,13 @@
+use crate::mipmap::MipmapGenerator;
 use crate::prelude::*;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
 pub struct Instance {
     pub(crate) device: Arc<wgpu::Device>,
     pub(crate) queue: Arc<wgpu::Queue>,
+    /// Created the first time mipmaps are generated.
+    pub(crate) mipmap_generator: Arc<Mutex<Option<MipmapGenerator>>>,
 }
 
 pub struct SwapChain {
@@ -32,17 +35,7 @@ impl Instance {
             .await
             .unwrap();
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("Device"),
-                    features,
-                    limits: wgpu::Limits::default(),
-                },
-                None,
-            )
-            .await
-            .unwrap();
+        let instance = Self::request_device(&adapter, features).await.unwrap();
 
         let sc_desc = wgpu::SwapChainDescriptor {
             usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
@@ -51,12 +44,7 @@ impl Instance {
             height,
             present_mode: wgpu::PresentMode::Fifo,
         };
-        let swap_chain = device.create_swap_chain(&surface, &sc_desc);
-
-        let instance = Instance {
-            device: Arc::new(device),
-            queue: Arc::new(queue),
-        };
+        let swap_chain = instance.device.create_swap_chain(&surface, &sc_desc);
 
         let swap_chain = SwapChain {
             surface,
@@ -69,6 +57,54 @@ impl Instance {
         (instance, swap_chain)
     }
 
+    /// Creates an instance without a surface, for running without a display.
+    ///
+    /// Falls back to secondary backends if no primary adapter is found, or if it can't create
+    /// a device with `features`. Returns [`None`] if no backend works.
+    pub async fn headless(features: wgpu::Features) -> Option<Instance> {
+        for backends in [wgpu::BackendBit::PRIMARY, wgpu::BackendBit::SECONDARY].iter() {
+            let instance = wgpu::Instance::new(*backends);
+
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: None,
+                })
+                .await;
+
+            if let Some(adapter) = adapter {
+                match Self::request_device(&adapter, features).await {
+                    Ok(instance) => return Some(instance),
+                    Err(err) => log::warn!("failed to create headless device: {}", err),
+                }
+            }
+        }
+
+        None
+    }
+
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+        features: wgpu::Features,
+    ) -> Result<Instance, wgpu::RequestDeviceError> {
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("Device"),
+                    features,
+                    limits: wgpu::Limits::default(),
+                },
+                None,
+            )
+            .await?;
+
+        Ok(Instance {
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            mipmap_generator: Arc::new(Mutex::new(None)),
+        })
+    }
+
     pub fn poll(&self, maintain: wgpu::Maintain) {
         self.device.poll(maintain);
     }
@@ -115,7 +151,7 @@ impl SwapChain {
                 depth_or_array_layers: 1,
             },
             dimension: wgpu::TextureViewDimension::D2,
-            multisampled: false,
+            sample_count: 1,
             format: self.format(),
         };
 
@@ -124,3 +160,101 @@ impl SwapChain {
         Ok(())
     }
 }
+
+/// Offscreen stand-in for a [`SwapChain`].
+pub struct RenderTarget {
+    pub(crate) texture: Texture2d<format::TargetFormat>,
+}
+
+impl RenderTarget {
+    pub fn new(width: u32, height: u32, format: format::TargetFormat, instance: &Instance) -> Self {
+        let texture = Texture::new(
+            &TextureDescriptor::new(D2::new(width, height), format),
+            instance,
+        );
+
+        Self { texture }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32, instance: &Instance) {
+        *self = Self::new(width, height, self.format(), instance);
+    }
+
+    pub fn width(&self) -> u32 {
+        self.texture.dimensions.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture.dimensions.height
+    }
+
+    pub fn format(&self) -> format::TargetFormat {
+        self.texture.format
+    }
+
+    pub fn texture(&self) -> &Texture2d<format::TargetFormat> {
+        &self.texture
+    }
+
+    pub fn view(&self) -> TextureView<'static, format::TargetFormat> {
+        self.texture.view()
+    }
+
+    pub fn next_frame(&self, mut f: impl FnMut(TextureView<'_, format::TargetFormat>)) {
+        f(self.view());
+    }
+
+    /// Reads back the rendered frame, indexed `[x][y]`.
+    pub fn read<T>(&self, instance: &Instance, f: impl FnMut(&Vec<Vec<Color>>) -> T) -> T {
+        self.texture.read(instance, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headless_clear_and_read() {
+        let instance = match futures::executor::block_on(Instance::headless(Default::default())) {
+            Some(instance) => instance,
+            // nothing to test against without an adapter
+            None => return,
+        };
+
+        let format = format::TargetFormat(wgpu::TextureFormat::Rgba8Unorm);
+        let target = RenderTarget::new(4, 2, format, &instance);
+
+        let clear = Color::rgb(1.0, 0.0, 1.0);
+
+        target.next_frame(|view| {
+            let desc = RenderPassDescriptor::<'_, format::TargetFormat, ()> {
+                label: None,
+                color_attachments: ColorAttachment {
+                    texture: view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(clear.into()),
+                        store: true,
+                    },
+                },
+                depth_attachment: (),
+            };
+
+            let mut render_ctx = instance.render();
+            render_ctx.render_pass_empty(&desc);
+        });
+
+        target.read(&instance, |data| {
+            assert_eq!(data.len(), 4);
+
+            for column in data {
+                assert_eq!(column.len(), 2);
+
+                for color in column {
+                    assert_eq!(*color, clear);
+                }
+            }
+        });
+    }
+}
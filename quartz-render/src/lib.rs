@@ -1,7 +1,12 @@
 pub mod bindings;
+pub mod bounds;
 pub mod buffer;
 pub mod color;
+pub mod compute_pass;
+pub mod compute_pipeline;
+pub mod image_data;
 pub mod instance;
+mod mipmap;
 pub mod projection;
 pub mod render_ctx;
 pub mod render_pass;
@@ -18,7 +23,12 @@ pub use wgpu;
 
 pub mod prelude {
     pub use crate::bindings::*;
+    pub use crate::bounds::*;
+    pub use crate::buffer::*;
     pub use crate::color::*;
+    pub use crate::compute_pass::*;
+    pub use crate::compute_pipeline::*;
+    pub use crate::image_data::*;
     pub use crate::instance::*;
     pub use crate::projection::*;
     pub use crate::render_ctx::*;
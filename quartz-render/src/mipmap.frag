@@ -0,0 +1,12 @@
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 o_color;
+
+layout(set = 0, binding = 0) uniform texture2D Source;
+layout(set = 0, binding = 1) uniform sampler SourceSampler;
+
+void main() {
+	o_color = texture(sampler2D(Source, SourceSampler), v_uv);
+}
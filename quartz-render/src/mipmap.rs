@@ -0,0 +1,214 @@
+use crate::prelude::*;
+use anyhow::bail;
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+
+/// Downsamples mip levels with a render pass per level, pipelines are created per format.
+pub(crate) struct MipmapGenerator {
+    vs_module: wgpu::ShaderModule,
+    fs_module: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl MipmapGenerator {
+    fn new(instance: &Instance) -> anyhow::Result<Self> {
+        let shader = Shader::from_glsl(include_str!("mipmap.vert"), include_str!("mipmap.frag"))?;
+        let (vs_module, fs_module) = shader.to_modules(instance);
+
+        let bind_group_layout =
+            instance
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Mipmap Bind Group Layout"),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStage::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: wgpu::ShaderStage::FRAGMENT,
+                            ty: wgpu::BindingType::Sampler {
+                                filtering: true,
+                                comparison: false,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+
+        let pipeline_layout =
+            instance
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Mipmap Pipeline Layout"),
+                    bind_group_layouts: &[&bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        let sampler = instance.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mipmap Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Ok(Self {
+            vs_module,
+            fs_module,
+            bind_group_layout,
+            pipeline_layout,
+            sampler,
+            pipelines: HashMap::new(),
+        })
+    }
+
+    /// Creates the pipeline for `format` if it doesn't exist yet.
+    fn prepare_pipeline(&mut self, format: wgpu::TextureFormat, instance: &Instance) {
+        let Self {
+            vs_module,
+            fs_module,
+            pipeline_layout,
+            pipelines,
+            ..
+        } = self;
+
+        pipelines.entry(format).or_insert_with(|| {
+            instance
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Mipmap Pipeline"),
+                    layout: Some(pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: vs_module,
+                        entry_point: "main",
+                        buffers: &[],
+                    },
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: fs_module,
+                        entry_point: "main",
+                        targets: &[wgpu::ColorTargetState {
+                            format,
+                            blend: None,
+                            write_mask: wgpu::ColorWrite::ALL,
+                        }],
+                    }),
+                })
+        });
+    }
+}
+
+/// Returns true if mipmaps for `format` can be generated by rendering.
+pub(crate) fn can_generate_mipmaps(format: wgpu::TextureFormat) -> bool {
+    let info = format.describe();
+
+    info.block_dimensions == (1, 1)
+        && info.sample_type == wgpu::TextureSampleType::Float { filterable: true }
+        && info
+            .guaranteed_format_features
+            .allowed_usages
+            .contains(wgpu::TextureUsage::RENDER_ATTACHMENT)
+}
+
+/// Fills mip levels `1..mip_levels` of every layer of a 2d texture from level 0.
+pub(crate) fn generate_mipmaps(
+    texture: &wgpu::Texture,
+    format: wgpu::TextureFormat,
+    mip_levels: u32,
+    layers: u32,
+    instance: &Instance,
+) -> anyhow::Result<()> {
+    if !can_generate_mipmaps(format) {
+        bail!("can't generate mipmaps for {:?}", format);
+    }
+
+    let mut generator = instance.mipmap_generator.lock().unwrap();
+
+    if generator.is_none() {
+        *generator = Some(MipmapGenerator::new(instance)?);
+    }
+
+    let generator = generator.as_mut().unwrap();
+    generator.prepare_pipeline(format, instance);
+    let pipeline = &generator.pipelines[&format];
+
+    let mut encoder = instance
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap Encoder"),
+        });
+
+    let view = |layer: u32, level: u32| {
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Mipmap View"),
+            format: None,
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: level,
+            mip_level_count: NonZeroU32::new(1),
+            base_array_layer: layer,
+            array_layer_count: NonZeroU32::new(1),
+        })
+    };
+
+    for layer in 0..layers {
+        for level in 1..mip_levels {
+            let source = view(layer, level - 1);
+            let target = view(layer, level);
+
+            let bind_group = instance
+                .device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Mipmap Bind Group"),
+                    layout: &generator.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&source),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&generator.sampler),
+                        },
+                    ],
+                });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+
+    instance.queue.submit(std::iter::once(encoder.finish()));
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::bounds::Frustum;
 use glam::*;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -23,6 +24,11 @@ impl PerspectiveProjection {
     pub fn matrix(&self) -> Mat4 {
         Mat4::perspective_rh(self.fov, self.aspect, self.near, self.far)
     }
+
+    /// Frustum of a camera with this projection and the global transform `camera`.
+    pub fn frustum(&self, camera: &Mat4) -> Frustum {
+        Frustum::from_view_proj(&(self.matrix() * camera.inverse()))
+    }
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -58,4 +64,9 @@ impl OrthographicProjection {
             self.far,
         )
     }
+
+    /// Frustum of a camera with this projection and the global transform `camera`.
+    pub fn frustum(&self, camera: &Mat4) -> Frustum {
+        Frustum::from_view_proj(&(self.matrix() * camera.inverse()))
+    }
 }
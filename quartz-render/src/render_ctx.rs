@@ -45,6 +45,18 @@ impl<'a> RenderCtx<'a> {
 
         pass
     }
+
+    pub fn compute_pass<'b>(&'b mut self, pipeline: &'b ComputePipeline) -> ComputePass<'b, 'a> {
+        let mut pass = ComputePass {
+            commands: Vec::new(),
+            ctx: self,
+            layout: None,
+        };
+
+        pass.set_pipeline(pipeline);
+
+        pass
+    }
 }
 
 impl<'a> Drop for RenderCtx<'a> {
@@ -6,6 +6,9 @@ pub trait ToColorAttachment<'a> {
     type State;
 
     fn to_color_attachment(state: &Self::State) -> Vec<wgpu::RenderPassColorAttachment>;
+
+    /// Sample count of the first attachment, if any.
+    fn sample_count(state: &Self::State) -> Option<u32>;
 }
 
 impl<'a> ToColorAttachment<'a> for () {
@@ -14,6 +17,10 @@ impl<'a> ToColorAttachment<'a> for () {
     fn to_color_attachment(_state: &Self::State) -> Vec<wgpu::RenderPassColorAttachment> {
         vec![]
     }
+
+    fn sample_count(_state: &Self::State) -> Option<u32> {
+        None
+    }
 }
 
 macro_rules! impl_color_attachment {
@@ -29,11 +36,16 @@ macro_rules! impl_color_attachment {
                 vec![$(
                     wgpu::RenderPassColorAttachment {
                         view: $ident.texture.view(),
-                        resolve_target: None,
+                        resolve_target: $ident.resolve_target.as_ref().map(|target| target.view()),
                         ops: $ident.ops.clone(),
                     }
                 ),*]
             }
+
+            #[allow(non_snake_case, unused_variables, unreachable_code)]
+            fn sample_count(($($ident),*): &Self::State) -> Option<u32> {
+                $(return Some($ident.texture.sample_count);)*
+            }
         }
     };
 }
@@ -51,6 +63,8 @@ pub trait ToDepthAttachment<'a> {
     type State;
 
     fn to_depth_attachment(state: &Self::State) -> Option<wgpu::RenderPassDepthStencilAttachment>;
+
+    fn sample_count(state: &Self::State) -> Option<u32>;
 }
 
 impl<'a> ToDepthAttachment<'a> for () {
@@ -59,6 +73,10 @@ impl<'a> ToDepthAttachment<'a> for () {
     fn to_depth_attachment(_state: &Self::State) -> Option<wgpu::RenderPassDepthStencilAttachment> {
         None
     }
+
+    fn sample_count(_state: &Self::State) -> Option<u32> {
+        None
+    }
 }
 
 impl<'a, F: TextureFormat> ToDepthAttachment<'a> for F {
@@ -71,6 +89,10 @@ impl<'a, F: TextureFormat> ToDepthAttachment<'a> for F {
             stencil_ops: state.stencil_ops.clone(),
         })
     }
+
+    fn sample_count(state: &Self::State) -> Option<u32> {
+        Some(state.texture.sample_count)
+    }
 }
 
 pub struct ColorAttachment<'a, F: TextureFormat> {
@@ -121,6 +143,15 @@ pub struct RenderPassDescriptor<
     pub depth_attachment: D::State,
 }
 
+impl<'a, T: ToColorAttachment<'a>, D: ToDepthAttachment<'a>> RenderPassDescriptor<'a, T, D> {
+    /// Sample count of the attachments, pipelines are built to match it.
+    pub fn sample_count(&self) -> u32 {
+        T::sample_count(&self.color_attachments)
+            .or_else(|| D::sample_count(&self.depth_attachment))
+            .unwrap_or(1)
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum Command {
     SetPipeline {
@@ -139,6 +170,10 @@ pub(crate) enum Command {
         base_vertex: i32,
         instances: std::ops::Range<u32>,
     },
+    DrawIndirect {
+        buffer: Arc<wgpu::Buffer>,
+        offset: wgpu::BufferAddress,
+    },
     SetVertexBuffer {
         buffer: Arc<wgpu::Buffer>,
         slot: u32,
@@ -147,6 +182,12 @@ pub(crate) enum Command {
         buffer: Arc<wgpu::Buffer>,
         format: wgpu::IndexFormat,
     },
+    SetViewport {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
 }
 
 pub struct RenderPass<
@@ -169,7 +210,7 @@ where
 {
     pub fn set_pipeline(&mut self, pipeline: &'a RenderPipeline<T, D>) -> &mut Self {
         self.commands.push(Command::SetPipeline {
-            pipeline: pipeline.pipeline.clone(),
+            pipeline: pipeline.sampled(self.descriptor.sample_count(), self.ctx.instance),
         });
 
         self.pipeline = pipeline;
@@ -178,7 +219,7 @@ where
     }
 
     pub fn set_bindings(&mut self, bindings: &mut Bindings) -> &mut Self {
-        bindings.generate_groups(self.ctx.instance);
+        bindings.generate_groups(&self.pipeline.layout, self.ctx.instance);
 
         for (set, bind_group) in &bindings.bind_groups {
             self.commands.push(Command::SetBindGroup {
@@ -201,6 +242,18 @@ where
         self
     }
 
+    /// Restricts drawing to a rectangle of the attachments, in pixels.
+    pub fn set_viewport(&mut self, x: f32, y: f32, width: f32, height: f32) -> &mut Self {
+        self.commands.push(Command::SetViewport {
+            x,
+            y,
+            width,
+            height,
+        });
+
+        self
+    }
+
     pub fn draw(&mut self, vertices: std::ops::Range<u32>) -> &mut Self {
         self.commands.push(Command::Draw {
             vertices,
@@ -210,38 +263,97 @@ where
         self
     }
 
+    /// Draws with the arguments at element `offset` of `buffer`, written by a compute shader
+    /// for example.
+    pub fn draw_indirect(&mut self, buffer: &Buffer<DrawIndirect>, offset: u64) -> &mut Self {
+        self.commands.push(Command::DrawIndirect {
+            buffer: buffer.raw().clone(),
+            offset: offset * std::mem::size_of::<DrawIndirect>() as u64,
+        });
+
+        self
+    }
+
     pub fn draw_mesh(&mut self, mesh: &Mesh) -> &mut Self {
-        if mesh.index_buffer.lock().unwrap().is_none() {
-            mesh.create_index_buffer(self.ctx.instance);
-        }
+        mesh_commands(
+            &mut self.commands,
+            &self.pipeline.layout,
+            mesh,
+            None,
+            self.ctx.instance,
+        );
 
-        mesh.create_vertex_buffers(&self.pipeline.layout, self.ctx.instance);
+        self
+    }
 
-        for (name, attribute) in &self.pipeline.layout.vertex_attributes {
-            let data = mesh.vertex_data.get(name).unwrap();
+    /// Draws `instances.len()` copies of `mesh` in one draw call.
+    pub fn draw_mesh_instanced(&mut self, mesh: &Mesh, instances: &Instances) -> &mut Self {
+        mesh_commands(
+            &mut self.commands,
+            &self.pipeline.layout,
+            mesh,
+            Some(instances),
+            self.ctx.instance,
+        );
 
-            if data.format == attribute.format {
-                let buffer = mesh.get_vertex_buffer(name).unwrap();
+        self
+    }
+}
 
-                self.commands.push(Command::SetVertexBuffer {
-                    slot: attribute.shader_location,
-                    buffer: buffer,
-                });
-            }
-        }
+/// Sets the vertex buffers of `mesh` and `instances` and draws them.
+fn mesh_commands(
+    commands: &mut Vec<Command>,
+    layout: &PipelineLayout,
+    mesh: &Mesh,
+    instances: Option<&Instances>,
+    instance: &Instance,
+) {
+    if mesh.index_buffer.lock().unwrap().is_none() {
+        mesh.create_index_buffer(instance);
+    }
 
-        self.commands.push(Command::SetIndexBuffer {
-            buffer: mesh.index_buffer.lock().unwrap().clone().unwrap(),
-            format: wgpu::IndexFormat::Uint32,
-        });
-        self.commands.push(Command::DrawIndexed {
-            indices: 0..mesh.indices.len() as u32,
-            base_vertex: 0,
-            instances: 0..1,
-        });
+    mesh.create_vertex_buffers(layout, instance);
 
-        self
+    for (name, attribute) in &layout.vertex_attributes {
+        let buffer = match attribute.step_mode {
+            wgpu::InputStepMode::Vertex => {
+                let data = mesh.vertex_data.get(name).unwrap();
+
+                if data.format == attribute.format {
+                    mesh.get_vertex_buffer(name)
+                } else {
+                    None
+                }
+            }
+            wgpu::InputStepMode::Instance => {
+                instances.and_then(|instances| instances.get_buffer(attribute, name))
+            }
+        };
+
+        if let Some(buffer) = buffer {
+            commands.push(Command::SetVertexBuffer {
+                slot: attribute.slot,
+                buffer,
+            });
+        }
     }
+
+    commands.push(Command::SetIndexBuffer {
+        buffer: mesh
+            .index_buffer
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .raw()
+            .clone(),
+        format: wgpu::IndexFormat::Uint32,
+    });
+    commands.push(Command::DrawIndexed {
+        indices: 0..mesh.indices.len() as u32,
+        base_vertex: 0,
+        instances: 0..instances.map_or(1, |instances| instances.len()),
+    });
 }
 
 pub(crate) fn execute_commands<'a, T: ToColorAttachment<'a>, D: ToDepthAttachment<'a>>(
@@ -285,12 +397,23 @@ pub(crate) fn execute_commands<'a, T: ToColorAttachment<'a>, D: ToDepthAttachmen
             } => {
                 render_pass.draw_indexed(indices.clone(), *base_vertex, instances.clone());
             }
+            Command::DrawIndirect { buffer, offset } => {
+                render_pass.draw_indirect(&buffer, *offset);
+            }
             Command::SetVertexBuffer { buffer, slot } => {
                 render_pass.set_vertex_buffer(*slot, buffer.slice(..));
             }
             Command::SetIndexBuffer { buffer, format } => {
                 render_pass.set_index_buffer(buffer.slice(..), *format);
             }
+            Command::SetViewport {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                render_pass.set_viewport(*x, *y, *width, *height, 0.0, 1.0);
+            }
         }
     }
 }
@@ -356,7 +479,7 @@ where
 {
     pub fn set_pipeline(&mut self, pipeline: &'rp RenderPipeline<T, D>) -> &mut Self {
         self.pass.commands.push(Command::SetPipeline {
-            pipeline: pipeline.pipeline.clone(),
+            pipeline: pipeline.sampled(self.pass.descriptor.sample_count(), self.pass.ctx.instance),
         });
 
         self.pipeline = pipeline;
@@ -365,7 +488,7 @@ where
     }
 
     pub fn set_bindings(&mut self, bindings: &mut Bindings) -> &mut Self {
-        bindings.generate_groups(self.pass.ctx.instance);
+        bindings.generate_groups(&self.pipeline.layout, self.pass.ctx.instance);
 
         for (set, bind_group) in &bindings.bind_groups {
             self.pass.commands.push(Command::SetBindGroup {
@@ -388,6 +511,18 @@ where
         self
     }
 
+    /// Restricts drawing to a rectangle of the attachments, in pixels.
+    pub fn set_viewport(&mut self, x: f32, y: f32, width: f32, height: f32) -> &mut Self {
+        self.pass.commands.push(Command::SetViewport {
+            x,
+            y,
+            width,
+            height,
+        });
+
+        self
+    }
+
     pub fn draw(&mut self, vertices: std::ops::Range<u32>) -> &mut Self {
         self.pass.commands.push(Command::Draw {
             vertices,
@@ -397,35 +532,38 @@ where
         self
     }
 
-    pub fn draw_mesh(&mut self, mesh: &Mesh) -> &mut Self {
-        if mesh.index_buffer.lock().unwrap().is_none() {
-            mesh.create_index_buffer(self.pass.ctx.instance);
-        }
-
-        mesh.create_vertex_buffers(&self.pipeline.layout, self.pass.ctx.instance);
+    /// Draws with the arguments at element `offset` of `buffer`, written by a compute shader
+    /// for example.
+    pub fn draw_indirect(&mut self, buffer: &Buffer<DrawIndirect>, offset: u64) -> &mut Self {
+        self.pass.commands.push(Command::DrawIndirect {
+            buffer: buffer.raw().clone(),
+            offset: offset * std::mem::size_of::<DrawIndirect>() as u64,
+        });
 
-        for (name, attribute) in &self.pipeline.layout.vertex_attributes {
-            let data = mesh.vertex_data.get(name).unwrap();
+        self
+    }
 
-            if data.format == attribute.format {
-                let buffer = mesh.get_vertex_buffer(name).unwrap();
+    pub fn draw_mesh(&mut self, mesh: &Mesh) -> &mut Self {
+        mesh_commands(
+            &mut self.pass.commands,
+            &self.pipeline.layout,
+            mesh,
+            None,
+            self.pass.ctx.instance,
+        );
 
-                self.pass.commands.push(Command::SetVertexBuffer {
-                    slot: attribute.shader_location,
-                    buffer: buffer,
-                });
-            }
-        }
+        self
+    }
 
-        self.pass.commands.push(Command::SetIndexBuffer {
-            buffer: mesh.index_buffer.lock().unwrap().clone().unwrap(),
-            format: wgpu::IndexFormat::Uint32,
-        });
-        self.pass.commands.push(Command::DrawIndexed {
-            indices: 0..mesh.indices.len() as u32,
-            base_vertex: 0,
-            instances: 0..1,
-        });
+    /// Draws `instances.len()` copies of `mesh` in one draw call.
+    pub fn draw_mesh_instanced(&mut self, mesh: &Mesh, instances: &Instances) -> &mut Self {
+        mesh_commands(
+            &mut self.pass.commands,
+            &self.pipeline.layout,
+            mesh,
+            Some(instances),
+            self.pass.ctx.instance,
+        );
 
         self
     }
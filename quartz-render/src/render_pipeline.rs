@@ -1,9 +1,11 @@
 use crate::prelude::*;
 use spirv_reflect::types::{
-    descriptor::ReflectDescriptorType, image::ReflectFormat, variable::ReflectDimension,
+    descriptor::ReflectDescriptorType,
+    image::{ReflectFormat, ReflectImageFormat},
+    variable::ReflectDimension,
 };
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
@@ -27,10 +29,64 @@ pub enum BindingType {
         multisampled: bool,
     },
     Sampler,
-    Buffer,
+    /// Storage buffer, read only if declared `readonly`.
+    Buffer {
+        read_only: bool,
+    },
     UniformBuffer {
         size: u64,
     },
+    /// Storage texture, the access is read from the `readonly` and `writeonly` qualifiers.
+    StorageTexture {
+        view_dimension: wgpu::TextureViewDimension,
+        format: wgpu::TextureFormat,
+        access: wgpu::StorageTextureAccess,
+    },
+}
+
+impl BindingType {
+    pub fn is_storage(&self) -> bool {
+        match self {
+            BindingType::Buffer { .. } | BindingType::StorageTexture { .. } => true,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn to_layout_type(&self) -> wgpu::BindingType {
+        match *self {
+            BindingType::Sampler => wgpu::BindingType::Sampler {
+                filtering: true,
+                comparison: false,
+            },
+            BindingType::Texture {
+                view_dimension,
+                multisampled,
+            } => wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension,
+                multisampled,
+            },
+            BindingType::UniformBuffer { .. } => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            BindingType::Buffer { read_only } => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            BindingType::StorageTexture {
+                view_dimension,
+                format,
+                access,
+            } => wgpu::BindingType::StorageTexture {
+                access,
+                format,
+                view_dimension,
+            },
+        }
+    }
 }
 
 /// Shader side descriptor set binding.
@@ -39,6 +95,19 @@ pub struct BindGroupEntry {
     pub ident: String,
     pub binding: u32,
     pub ty: BindingType,
+    /// The stages the binding is declared in.
+    pub visibility: wgpu::ShaderStage,
+}
+
+impl BindGroupEntry {
+    pub(crate) fn layout_entry(&self) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: self.binding,
+            visibility: self.visibility,
+            ty: self.ty.to_layout_type(),
+            count: None,
+        }
+    }
 }
 
 /// Shader side descriptor set.
@@ -60,14 +129,21 @@ impl BindGroup {
 }
 
 /// Shader side input descriptor.
+///
+/// Inputs named `instance_*` step per instance, see [`Instances`].
 #[derive(Clone, Debug)]
 pub struct VertexAttributeLayout {
     /// Buffer offset.
     pub offset: u64,
     /// Location in shader.
     pub shader_location: u32,
-    /// Vertex format.
+    /// Vertex buffer slot, each attribute has its own buffer.
+    pub slot: u32,
+    /// Vertex format, of a single column for matrices.
     pub format: wgpu::VertexFormat,
+    /// Number of matrix columns, 1 for vectors, each column takes a location.
+    pub columns: u32,
+    pub step_mode: wgpu::InputStepMode,
 }
 
 /// Layout of the shader side of a [`RenderPipeline`].
@@ -197,6 +273,9 @@ pub struct PipelineDescriptor<T: ColorTargetState, D: DepthStencilState> {
     pub targets: T::State,
     pub depth_stencil: D::State,
     pub primitive: PrimitiveState,
+    /// The count is only used for render passes with the same sample count, the pipeline is
+    /// rebuilt with the pass' count for others.
+    pub multisample: wgpu::MultisampleState,
 }
 
 impl<T: ColorTargetState, D: DepthStencilState> PipelineDescriptor<T, D> {
@@ -214,6 +293,7 @@ impl<T: ColorTargetState, D: DepthStencilState> PipelineDescriptor<T, D> {
                 clamp_depth: false,
                 conservative: false,
             },
+            multisample: wgpu::MultisampleState::default(),
         }
     }
 }
@@ -228,6 +308,8 @@ pub struct RenderPipeline<
     pub(crate) descriptor: PipelineDescriptor<T, D>,
     pub(crate) layout: PipelineLayout,
     pub(crate) pipeline: Arc<wgpu::RenderPipeline>,
+    /// Built when used in render passes with other sample counts.
+    pub(crate) variants: Mutex<HashMap<u32, Arc<wgpu::RenderPipeline>>>,
 }
 
 impl<T: ColorTargetState, D: DepthStencilState> RenderPipeline<T, D> {
@@ -236,72 +318,129 @@ impl<T: ColorTargetState, D: DepthStencilState> RenderPipeline<T, D> {
         descriptor: PipelineDescriptor<T, D>,
         instance: &Instance,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let limis = instance.device.limits();
+        let (layout, pipeline) = Self::build(&descriptor, descriptor.multisample.count, instance)?;
 
-        let mut bind_groups = (0..limis.max_bind_groups as usize)
-            .into_iter()
-            .map(|_| BindGroup::new())
-            .collect::<Vec<_>>();
+        Ok(Self {
+            descriptor,
+            layout,
+            pipeline: Arc::new(pipeline),
+            variants: Mutex::new(HashMap::new()),
+        })
+    }
 
-        let vs_reflection =
-            spirv_reflect::ShaderModule::load_u8_data(descriptor.shader.vs_spirv.as_binary_u8())?;
-        let fs_reflection =
-            spirv_reflect::ShaderModule::load_u8_data(descriptor.shader.fs_spirv.as_binary_u8())?;
+    pub fn shader(&self) -> &Shader {
+        &self.descriptor.shader
+    }
 
-        for binding in vs_reflection
-            .enumerate_descriptor_bindings(None)?
-            .into_iter()
-            .chain(fs_reflection.enumerate_descriptor_bindings(None)?)
-        {
-            let entry = BindGroupEntry {
-                ident: match binding.descriptor_type {
-                    ReflectDescriptorType::UniformBuffer => {
-                        binding.type_description.unwrap().type_name
-                    }
-                    _ => binding.name,
-                },
-                binding: binding.binding,
-                ty: match binding.descriptor_type {
-                    ReflectDescriptorType::StorageBuffer => BindingType::Buffer,
-                    ReflectDescriptorType::UniformBuffer => BindingType::UniformBuffer {
-                        size: binding.block.size as u64,
-                    },
-                    ReflectDescriptorType::SampledImage => BindingType::Texture {
-                        view_dimension: match binding.image.dim {
-                            ReflectDimension::Type1d => wgpu::TextureViewDimension::D1,
-                            ReflectDimension::Type2d => {
-                                if binding.image.arrayed > 0 {
-                                    wgpu::TextureViewDimension::D2Array
-                                } else {
-                                    wgpu::TextureViewDimension::D2
-                                }
-                            }
-                            ReflectDimension::Type3d => wgpu::TextureViewDimension::D3,
-                            ReflectDimension::Cube => wgpu::TextureViewDimension::Cube,
-                            _ => panic!("Texture type unsupported"),
-                        },
-                        multisampled: binding.image.ms > 1,
-                    },
-                    ReflectDescriptorType::Sampler => BindingType::Sampler,
-                    _ => return Err("Descriptor type unsupported".into()),
-                },
-            };
+    /// Rebuilds the pipeline with a new shader, keeping the old one if it fails.
+    pub fn rebuild(
+        &mut self,
+        shader: Shader,
+        instance: &Instance,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let old = std::mem::replace(&mut self.descriptor.shader, shader);
+
+        match Self::build(
+            &self.descriptor,
+            self.descriptor.multisample.count,
+            instance,
+        ) {
+            Ok((layout, pipeline)) => {
+                self.layout = layout;
+                self.pipeline = Arc::new(pipeline);
+                self.variants.lock().unwrap().clear();
+
+                Ok(())
+            }
+            Err(err) => {
+                self.descriptor.shader = old;
 
-            if let Some(existing_entry) = bind_groups[binding.set as usize]
-                .bindings
-                .get(&binding.binding)
-            {
-                if *existing_entry != entry {
-                    return Err("Overlapping bindings".into());
-                }
-            } else {
-                bind_groups[binding.set as usize]
-                    .bindings
-                    .insert(binding.binding, entry);
+                Err(err)
             }
         }
+    }
+
+    /// Reloads the shader and rebuilds the pipeline if the shader was loaded with
+    /// [`Shader::load`] and its files changed since.
+    ///
+    /// Returns true if the pipeline was rebuilt. A failed reload isn't retried until the
+    /// files change again.
+    pub fn reload(&mut self, instance: &Instance) -> Result<bool, Box<dyn std::error::Error>> {
+        let modified = match self.descriptor.shader.changed() {
+            Some(modified) => modified,
+            None => return Ok(false),
+        };
+
+        self.descriptor.shader.modified = Some(modified);
+
+        let shader = self.descriptor.shader.reload()?;
+        self.rebuild(shader, instance)?;
+
+        Ok(true)
+    }
+
+    /// Returns the pipeline built for `sample_count`.
+    pub(crate) fn sampled(
+        &self,
+        sample_count: u32,
+        instance: &Instance,
+    ) -> Arc<wgpu::RenderPipeline> {
+        if sample_count == self.descriptor.multisample.count {
+            return self.pipeline.clone();
+        }
+
+        let mut variants = self.variants.lock().unwrap();
+
+        if let Some(pipeline) = variants.get(&sample_count) {
+            return pipeline.clone();
+        }
+
+        // the shader already built once, so this only fails on invalid sample counts, which
+        // wgpu reports when the pass is used
+        match Self::build(&self.descriptor, sample_count, instance) {
+            Ok((_, pipeline)) => {
+                let pipeline = Arc::new(pipeline);
+                variants.insert(sample_count, pipeline.clone());
+                pipeline
+            }
+            Err(err) => {
+                log::error!(
+                    "failed to build pipeline with {} samples: {}",
+                    sample_count,
+                    err
+                );
+                self.pipeline.clone()
+            }
+        }
+    }
+
+    fn build(
+        descriptor: &PipelineDescriptor<T, D>,
+        sample_count: u32,
+        instance: &Instance,
+    ) -> Result<(PipelineLayout, wgpu::RenderPipeline), Box<dyn std::error::Error>> {
+        let vs_reflection =
+            spirv_reflect::ShaderModule::load_u8_data(descriptor.shader.vs_spirv.as_binary_u8())?;
+        let fs_reflection =
+            spirv_reflect::ShaderModule::load_u8_data(descriptor.shader.fs_spirv.as_binary_u8())?;
 
-        let vertex_attributes = vs_reflection
+        let mut bind_groups = reflect_bind_groups(
+            &[
+                (
+                    &vs_reflection,
+                    descriptor.shader.vs_spirv.as_binary(),
+                    wgpu::ShaderStage::VERTEX,
+                ),
+                (
+                    &fs_reflection,
+                    descriptor.shader.fs_spirv.as_binary(),
+                    wgpu::ShaderStage::FRAGMENT,
+                ),
+            ],
+            instance,
+        )?;
+
+        let mut vertex_attributes = vs_reflection
             .enumerate_input_variables(None)?
             .into_iter()
             .filter(|input| {
@@ -310,10 +449,17 @@ impl<T: ColorTargetState, D: DepthStencilState> RenderPipeline<T, D> {
                     .contains(spirv_reflect::types::variable::ReflectDecorationFlags::BUILT_IN)
             })
             .map(|input| {
-                let layout = VertexAttributeLayout {
-                    offset: 0,
-                    shader_location: input.location,
-                    format: match input.format {
+                let columns = input.numeric.matrix.column_count.max(1);
+
+                let format = if columns > 1 {
+                    match input.numeric.matrix.row_count {
+                        2 => wgpu::VertexFormat::Float32x2,
+                        3 => wgpu::VertexFormat::Float32x3,
+                        4 => wgpu::VertexFormat::Float32x4,
+                        _ => return Err(format!("Unsupported input matrix {:?}", input)),
+                    }
+                } else {
+                    match input.format {
                         ReflectFormat::R32_SFLOAT => wgpu::VertexFormat::Float32,
                         ReflectFormat::R32G32_SFLOAT => wgpu::VertexFormat::Float32x2,
                         ReflectFormat::R32G32B32_SFLOAT => wgpu::VertexFormat::Float32x3,
@@ -322,87 +468,65 @@ impl<T: ColorTargetState, D: DepthStencilState> RenderPipeline<T, D> {
                         ReflectFormat::R32G32_SINT => wgpu::VertexFormat::Sint32x2,
                         ReflectFormat::R32G32B32_SINT => wgpu::VertexFormat::Sint32x3,
                         ReflectFormat::R32G32B32A32_SINT => wgpu::VertexFormat::Sint32x4,
-                        _ => panic!("Unsupported input format {:?}", input),
-                    },
+                        _ => return Err(format!("Unsupported input format {:?}", input)),
+                    }
+                };
+
+                let step_mode = if input.name.starts_with("instance_") {
+                    wgpu::InputStepMode::Instance
+                } else {
+                    wgpu::InputStepMode::Vertex
                 };
 
-                (input.name, layout)
+                let layout = VertexAttributeLayout {
+                    offset: 0,
+                    shader_location: input.location,
+                    slot: 0,
+                    format,
+                    columns,
+                    step_mode,
+                };
+
+                Ok((input.name, layout))
             })
-            .collect::<HashMap<_, _>>();
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        let mut sorted = vertex_attributes.values_mut().collect::<Vec<_>>();
+        sorted.sort_by_key(|attr| attr.shader_location);
+
+        for (slot, attr) in sorted.into_iter().enumerate() {
+            attr.slot = slot as u32;
+        }
 
         let mut attributes = vertex_attributes
-            .iter()
-            .map(|(_, attr)| {
-                vec![wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: attr.shader_location,
-                    format: attr.format,
-                }]
+            .values()
+            .map(|attr| {
+                let columns = (0..attr.columns)
+                    .map(|column| wgpu::VertexAttribute {
+                        offset: column as u64 * attr.format.size(),
+                        shader_location: attr.shader_location + column,
+                        format: attr.format,
+                    })
+                    .collect::<Vec<_>>();
+
+                (attr, columns)
             })
             .collect::<Vec<_>>();
 
-        attributes.sort_by(|a, b| a[0].shader_location.cmp(&b[0].shader_location));
+        attributes.sort_by_key(|(attr, _)| attr.slot);
 
         let buffers = attributes
             .iter()
-            .map(|attr| wgpu::VertexBufferLayout {
-                array_stride: attr[0].format.size(),
-                step_mode: wgpu::InputStepMode::Vertex,
-                attributes: &attr,
+            .map(|(attr, columns)| wgpu::VertexBufferLayout {
+                array_stride: attr.format.size() * attr.columns as u64,
+                step_mode: attr.step_mode,
+                attributes: columns,
             })
             .collect::<Vec<_>>();
 
         let (vs_module, fs_module) = descriptor.shader.to_modules(instance);
 
-        let layouts = bind_groups
-            .iter_mut()
-            .filter_map(|bind_group| {
-                let entries = bind_group
-                    .bindings
-                    .iter()
-                    .map(|(_binding, entry)| wgpu::BindGroupLayoutEntry {
-                        binding: entry.binding,
-                        visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
-                        ty: match entry.ty {
-                            BindingType::Sampler => wgpu::BindingType::Sampler {
-                                filtering: true,
-                                comparison: false,
-                            },
-                            BindingType::Texture {
-                                view_dimension,
-                                multisampled,
-                            } => wgpu::BindingType::Texture {
-                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                                view_dimension,
-                                multisampled,
-                            },
-                            BindingType::UniformBuffer { .. } => wgpu::BindingType::Buffer {
-                                ty: wgpu::BufferBindingType::Uniform,
-                                has_dynamic_offset: false,
-                                min_binding_size: None,
-                            },
-                            _ => panic!(),
-                        },
-                        count: None,
-                    })
-                    .collect::<Vec<_>>();
-
-                if entries.len() > 0 {
-                    let layout = instance.device.create_bind_group_layout(
-                        &wgpu::BindGroupLayoutDescriptor {
-                            label: Some("Bind Group Layout"),
-                            entries: &entries,
-                        },
-                    );
-
-                    bind_group.layout = Some(Arc::new(layout));
-
-                    Some(&**bind_group.layout.as_ref().unwrap())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+        let layouts = create_bind_group_layouts(&mut bind_groups, instance);
 
         let layout = instance
             .device
@@ -433,9 +557,8 @@ impl<T: ColorTargetState, D: DepthStencilState> RenderPipeline<T, D> {
                 primitive: descriptor.primitive.clone(),
                 depth_stencil,
                 multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
+                    count: sample_count,
+                    ..descriptor.multisample
                 },
             });
 
@@ -444,10 +567,289 @@ impl<T: ColorTargetState, D: DepthStencilState> RenderPipeline<T, D> {
             vertex_attributes,
         };
 
-        Ok(Self {
-            descriptor,
-            layout,
-            pipeline: Arc::new(pipeline),
+        Ok((layout, pipeline))
+    }
+}
+
+/// Collects the descriptor bindings of every shader stage into bind groups, each stage is
+/// given as its reflection, spirv words and stage.
+pub(crate) fn reflect_bind_groups(
+    stages: &[(&spirv_reflect::ShaderModule, &[u32], wgpu::ShaderStage)],
+    instance: &Instance,
+) -> Result<Vec<BindGroup>, Box<dyn std::error::Error>> {
+    let limits = instance.device.limits();
+
+    let mut bind_groups = (0..limits.max_bind_groups as usize)
+        .into_iter()
+        .map(|_| BindGroup::new())
+        .collect::<Vec<_>>();
+
+    for (module, words, stage) in stages {
+        let access = Access::parse(words);
+
+        for binding in module.enumerate_descriptor_bindings(None)? {
+            let entry = BindGroupEntry {
+                ident: match binding.descriptor_type {
+                    ReflectDescriptorType::UniformBuffer | ReflectDescriptorType::StorageBuffer => {
+                        binding
+                            .type_description
+                            .as_ref()
+                            .ok_or("Buffer without type description")?
+                            .type_name
+                            .clone()
+                    }
+                    _ => binding.name.clone(),
+                },
+                binding: binding.binding,
+                ty: match binding.descriptor_type {
+                    ReflectDescriptorType::StorageBuffer => BindingType::Buffer {
+                        read_only: access.read_only_block(&binding),
+                    },
+                    ReflectDescriptorType::UniformBuffer => BindingType::UniformBuffer {
+                        size: binding.block.size as u64,
+                    },
+                    ReflectDescriptorType::SampledImage => BindingType::Texture {
+                        view_dimension: view_dimension(&binding)?,
+                        multisampled: binding.image.ms > 1,
+                    },
+                    ReflectDescriptorType::StorageImage => BindingType::StorageTexture {
+                        view_dimension: view_dimension(&binding)?,
+                        format: storage_format(binding.image.image_format)
+                            .ok_or("Storage texture format unsupported")?,
+                        access: access.image_access(binding.spirv_id),
+                    },
+                    ReflectDescriptorType::Sampler => BindingType::Sampler,
+                    _ => return Err("Descriptor type unsupported".into()),
+                },
+                visibility: *stage,
+            };
+
+            if let Some(existing_entry) = bind_groups[binding.set as usize]
+                .bindings
+                .get_mut(&binding.binding)
+            {
+                if existing_entry.ident != entry.ident || existing_entry.ty != entry.ty {
+                    return Err("Overlapping bindings".into());
+                }
+
+                existing_entry.visibility |= entry.visibility;
+            } else {
+                bind_groups[binding.set as usize]
+                    .bindings
+                    .insert(binding.binding, entry);
+            }
+        }
+    }
+
+    Ok(bind_groups)
+}
+
+/// Creates the layouts of the bind groups with entries.
+pub(crate) fn create_bind_group_layouts<'a>(
+    bind_groups: &'a mut [BindGroup],
+    instance: &Instance,
+) -> Vec<&'a wgpu::BindGroupLayout> {
+    bind_groups
+        .iter_mut()
+        .filter_map(|bind_group| {
+            let entries = bind_group
+                .bindings
+                .iter()
+                .map(|(_binding, entry)| entry.layout_entry())
+                .collect::<Vec<_>>();
+
+            if entries.len() > 0 {
+                let layout =
+                    instance
+                        .device
+                        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                            label: Some("Bind Group Layout"),
+                            entries: &entries,
+                        });
+
+                bind_group.layout = Some(Arc::new(layout));
+
+                Some(&**bind_group.layout.as_ref().unwrap())
+            } else {
+                None
+            }
         })
+        .collect()
+}
+
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+const DECORATION_NON_WRITABLE: u32 = 24;
+const DECORATION_NON_READABLE: u32 = 25;
+
+/// The `readonly` and `writeonly` qualifiers of a shader, read from the spirv decorations
+/// since spirv_reflect doesn't report them for variables.
+#[derive(Default)]
+struct Access {
+    non_writable: HashSet<u32>,
+    non_readable: HashSet<u32>,
+    /// Number of members decorated `NonWritable` of each struct type.
+    non_writable_members: HashMap<u32, usize>,
+}
+
+impl Access {
+    fn parse(words: &[u32]) -> Self {
+        let mut access = Self::default();
+
+        // instructions start after the 5 word header
+        let mut index = 5;
+
+        while index < words.len() {
+            let word_count = (words[index] >> 16) as usize;
+            let opcode = words[index] & 0xffff;
+
+            if word_count == 0 || index + word_count > words.len() {
+                break;
+            }
+
+            match (opcode, &words[index + 1..index + word_count]) {
+                (OP_DECORATE, [id, DECORATION_NON_WRITABLE, ..]) => {
+                    access.non_writable.insert(*id);
+                }
+                (OP_DECORATE, [id, DECORATION_NON_READABLE, ..]) => {
+                    access.non_readable.insert(*id);
+                }
+                (OP_MEMBER_DECORATE, [id, _, DECORATION_NON_WRITABLE, ..]) => {
+                    *access.non_writable_members.entry(*id).or_default() += 1;
+                }
+                _ => {}
+            }
+
+            index += word_count;
+        }
+
+        access
+    }
+
+    /// Storage blocks are read only if the variable or every member is `NonWritable`.
+    fn read_only_block(&self, binding: &spirv_reflect::types::ReflectDescriptorBinding) -> bool {
+        if self.non_writable.contains(&binding.spirv_id) {
+            return true;
+        }
+
+        match &binding.type_description {
+            Some(ty) if !ty.members.is_empty() => {
+                self.non_writable_members.get(&ty.id).copied() == Some(ty.members.len())
+            }
+            _ => false,
+        }
+    }
+
+    fn image_access(&self, id: u32) -> wgpu::StorageTextureAccess {
+        if self.non_writable.contains(&id) {
+            wgpu::StorageTextureAccess::ReadOnly
+        } else if self.non_readable.contains(&id) {
+            wgpu::StorageTextureAccess::WriteOnly
+        } else {
+            wgpu::StorageTextureAccess::ReadWrite
+        }
+    }
+}
+
+fn view_dimension(
+    binding: &spirv_reflect::types::ReflectDescriptorBinding,
+) -> Result<wgpu::TextureViewDimension, Box<dyn std::error::Error>> {
+    Ok(match binding.image.dim {
+        ReflectDimension::Type1d => wgpu::TextureViewDimension::D1,
+        ReflectDimension::Type2d => {
+            if binding.image.arrayed > 0 {
+                wgpu::TextureViewDimension::D2Array
+            } else {
+                wgpu::TextureViewDimension::D2
+            }
+        }
+        ReflectDimension::Type3d => wgpu::TextureViewDimension::D3,
+        ReflectDimension::Cube => {
+            if binding.image.arrayed > 0 {
+                wgpu::TextureViewDimension::CubeArray
+            } else {
+                wgpu::TextureViewDimension::Cube
+            }
+        }
+        _ => return Err("Texture type unsupported".into()),
+    })
+}
+
+/// Formats usable as storage textures.
+fn storage_format(format: ReflectImageFormat) -> Option<wgpu::TextureFormat> {
+    Some(match format {
+        ReflectImageFormat::R32_FLOAT => wgpu::TextureFormat::R32Float,
+        ReflectImageFormat::R32_UINT => wgpu::TextureFormat::R32Uint,
+        ReflectImageFormat::R32_INT => wgpu::TextureFormat::R32Sint,
+        ReflectImageFormat::RG32_FLOAT => wgpu::TextureFormat::Rg32Float,
+        ReflectImageFormat::RG32_UINT => wgpu::TextureFormat::Rg32Uint,
+        ReflectImageFormat::RG32_INT => wgpu::TextureFormat::Rg32Sint,
+        ReflectImageFormat::RGBA8 => wgpu::TextureFormat::Rgba8Unorm,
+        ReflectImageFormat::RGBA8_SNORM => wgpu::TextureFormat::Rgba8Snorm,
+        ReflectImageFormat::RGBA8_UINT => wgpu::TextureFormat::Rgba8Uint,
+        ReflectImageFormat::RGBA8_INT => wgpu::TextureFormat::Rgba8Sint,
+        ReflectImageFormat::RGBA16_FLOAT => wgpu::TextureFormat::Rgba16Float,
+        ReflectImageFormat::RGBA16_UINT => wgpu::TextureFormat::Rgba16Uint,
+        ReflectImageFormat::RGBA16_INT => wgpu::TextureFormat::Rgba16Sint,
+        ReflectImageFormat::RGBA32_FLOAT => wgpu::TextureFormat::Rgba32Float,
+        ReflectImageFormat::RGBA32_UINT => wgpu::TextureFormat::Rgba32Uint,
+        ReflectImageFormat::RGBA32_INT => wgpu::TextureFormat::Rgba32Sint,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instruction(opcode: u32, operands: &[u32]) -> Vec<u32> {
+        let mut words = vec![(operands.len() as u32 + 1) << 16 | opcode];
+        words.extend_from_slice(operands);
+        words
+    }
+
+    #[test]
+    fn parse_access() {
+        let mut words = vec![0x07230203, 0x00010000, 0, 100, 0];
+        words.extend(instruction(OP_DECORATE, &[1, DECORATION_NON_WRITABLE]));
+        words.extend(instruction(OP_DECORATE, &[2, DECORATION_NON_READABLE]));
+        // binding decoration
+        words.extend(instruction(OP_DECORATE, &[3, 33, 0]));
+        words.extend(instruction(
+            OP_MEMBER_DECORATE,
+            &[4, 0, DECORATION_NON_WRITABLE],
+        ));
+        words.extend(instruction(
+            OP_MEMBER_DECORATE,
+            &[4, 1, DECORATION_NON_WRITABLE],
+        ));
+        // offset decoration
+        words.extend(instruction(OP_MEMBER_DECORATE, &[5, 0, 35, 0]));
+
+        let access = Access::parse(&words);
+
+        assert_eq!(access.image_access(1), wgpu::StorageTextureAccess::ReadOnly);
+        assert_eq!(
+            access.image_access(2),
+            wgpu::StorageTextureAccess::WriteOnly
+        );
+        assert_eq!(
+            access.image_access(3),
+            wgpu::StorageTextureAccess::ReadWrite
+        );
+        assert_eq!(access.non_writable_members.get(&4), Some(&2));
+        assert_eq!(access.non_writable_members.get(&5), None);
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let mut words = vec![0x07230203, 0x00010000, 0, 100, 0];
+        words.extend(instruction(OP_DECORATE, &[1, DECORATION_NON_WRITABLE]));
+        words.push(10 << 16 | OP_DECORATE);
+
+        let access = Access::parse(&words);
+
+        assert!(access.non_writable.contains(&1));
+        assert_eq!(access.non_writable.len(), 1);
     }
 }
@@ -33,7 +33,7 @@ impl Sampler {
             min_filter: descriptor.filter,
             mipmap_filter: descriptor.filter,
             lod_min_clamp: 0.0,
-            lod_max_clamp: 0.0,
+            lod_max_clamp: std::f32::MAX,
             compare: None,
             anisotropy_clamp: None,
             border_color: None,
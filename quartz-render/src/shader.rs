@@ -1,17 +1,57 @@
 use crate::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 pub struct Shader {
     pub(crate) vs_spirv: shaderc::CompilationArtifact,
     pub(crate) fs_spirv: shaderc::CompilationArtifact,
+    /// Vertex and fragment source paths, if loaded from files.
+    pub(crate) paths: Option<(PathBuf, PathBuf)>,
+    pub(crate) modified: Option<SystemTime>,
 }
 
 impl Shader {
     pub fn load(vs_path: impl AsRef<Path>, fs_path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let vs_path = vs_path.as_ref();
+        let fs_path = fs_path.as_ref();
+
+        let modified = modified(vs_path, fs_path);
+
         let vs_src = std::fs::read_to_string(vs_path)?;
         let fs_src = std::fs::read_to_string(fs_path)?;
 
-        Self::from_glsl(&vs_src, &fs_src)
+        let mut shader = Self::from_glsl(&vs_src, &fs_src)?;
+        shader.paths = Some((vs_path.into(), fs_path.into()));
+        shader.modified = modified;
+
+        Ok(shader)
+    }
+
+    /// Loads the shader again from the files it was loaded from.
+    pub fn reload(&self) -> anyhow::Result<Self> {
+        match &self.paths {
+            Some((vs_path, fs_path)) => Self::load(vs_path, fs_path),
+            None => Err(anyhow::Error::msg("Shader wasn't loaded from files")),
+        }
+    }
+
+    pub fn paths(&self) -> Option<(&Path, &Path)> {
+        self.paths
+            .as_ref()
+            .map(|(vs_path, fs_path)| (vs_path.as_path(), fs_path.as_path()))
+    }
+
+    /// Returns the new modification time if the source files changed since the shader was
+    /// loaded.
+    pub fn changed(&self) -> Option<SystemTime> {
+        let (vs_path, fs_path) = self.paths()?;
+        let modified = modified(vs_path, fs_path)?;
+
+        if Some(modified) > self.modified {
+            Some(modified)
+        } else {
+            None
+        }
     }
 
     pub fn from_glsl(vs_src: &str, fs_src: &str) -> anyhow::Result<Self> {
@@ -33,7 +73,12 @@ impl Shader {
             None,
         )?;
 
-        Ok(Self { vs_spirv, fs_spirv })
+        Ok(Self {
+            vs_spirv,
+            fs_spirv,
+            paths: None,
+            modified: None,
+        })
     }
 
     pub fn to_modules(&self, instance: &Instance) -> (wgpu::ShaderModule, wgpu::ShaderModule) {
@@ -58,3 +103,87 @@ impl Shader {
         (vs_module, fs_module)
     }
 }
+
+/// A GLSL compute shader, see [`ComputePipeline`].
+pub struct ComputeShader {
+    pub(crate) spirv: shaderc::CompilationArtifact,
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) modified: Option<SystemTime>,
+}
+
+impl ComputeShader {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        let modified = std::fs::metadata(path).ok().and_then(|m| m.modified().ok());
+
+        let src = std::fs::read_to_string(path)?;
+
+        let mut shader = Self::from_glsl(&src)?;
+        shader.path = Some(path.into());
+        shader.modified = modified;
+
+        Ok(shader)
+    }
+
+    /// Loads the shader again from the file it was loaded from.
+    pub fn reload(&self) -> anyhow::Result<Self> {
+        match &self.path {
+            Some(path) => Self::load(path),
+            None => Err(anyhow::Error::msg("Shader wasn't loaded from a file")),
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref().map(|path| path.as_path())
+    }
+
+    /// Returns the new modification time if the source file changed since the shader was
+    /// loaded.
+    pub fn changed(&self) -> Option<SystemTime> {
+        let modified = std::fs::metadata(self.path()?).ok()?.modified().ok()?;
+
+        if Some(modified) > self.modified {
+            Some(modified)
+        } else {
+            None
+        }
+    }
+
+    pub fn from_glsl(src: &str) -> anyhow::Result<Self> {
+        let mut compiler = shaderc::Compiler::new()
+            .ok_or(anyhow::Error::msg("Failed to create shaderc compiler"))?;
+        let spirv = compiler.compile_into_spirv(
+            src,
+            shaderc::ShaderKind::Compute,
+            "compute shader",
+            "main",
+            None,
+        )?;
+
+        Ok(Self {
+            spirv,
+            path: None,
+            modified: None,
+        })
+    }
+
+    pub fn to_module(&self, instance: &Instance) -> wgpu::ShaderModule {
+        let data = wgpu::util::make_spirv(self.spirv.as_binary_u8());
+
+        instance
+            .device
+            .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("Compute Shader"),
+                source: data,
+                flags: wgpu::ShaderFlags::default(),
+            })
+    }
+}
+
+fn modified(vs_path: &Path, fs_path: &Path) -> Option<SystemTime> {
+    let vs_modified = std::fs::metadata(vs_path).ok()?.modified().ok()?;
+    let fs_modified = std::fs::metadata(fs_path).ok()?.modified().ok()?;
+
+    Some(vs_modified.max(fs_modified))
+}
@@ -1,5 +1,8 @@
 use crate::color::*;
+use crate::image_data::ImageData;
+use crate::mipmap::{can_generate_mipmaps, generate_mipmaps};
 use crate::prelude::*;
+use anyhow::bail;
 use format::*;
 use futures::executor::block_on;
 use std::borrow::BorrowMut;
@@ -40,6 +43,20 @@ pub struct D2Array {
     pub layers: u32,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Cube {
+    pub size: u32,
+}
+
+/// `cubes` cube maps of 6 faces each, stored as layers in the order +x, -x, +y, -y, +z and -z.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CubeArray {
+    pub size: u32,
+    pub cubes: u32,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct D3 {
@@ -289,18 +306,177 @@ impl<D: TextureData> TextureDimension<D> for D2Array {
     }
 }
 
+impl Cube {
+    pub const fn new(size: u32) -> Self {
+        Self { size }
+    }
+}
+
+impl<D: TextureData> TextureDimension<D> for Cube {
+    type Data = Vec<Vec<Vec<D>>>;
+
+    fn init_data(&self) -> Self::Data {
+        vec![vec![vec![D::default(); 6]; self.size as usize]; self.size as usize]
+    }
+
+    fn data_to_bytes<F: TextureFormat>(data: &Self::Data, format: F) -> Vec<u8> {
+        data.iter()
+            .map(|data| {
+                data.iter()
+                    .map(|data| {
+                        data.iter()
+                            .map(|color| color.to_bytes(format.clone()))
+                            .flatten()
+                    })
+                    .flatten()
+            })
+            .flatten()
+            .collect()
+    }
+
+    fn bytes_to_data<F: TextureFormat>(&self, data: &mut Self::Data, bytes: &[u8], format: F) {
+        let info = format.format().describe();
+        let block_size = info.block_size as usize;
+        let row_size = data_width(self.size) as usize * block_size;
+        let image_size = row_size * self.size as usize;
+
+        data.iter_mut().enumerate().for_each(|(x, data)| {
+            data.iter_mut().enumerate().for_each(|(y, data)| {
+                data.iter_mut().enumerate().for_each(|(z, color)| {
+                    let index = z * image_size + y * row_size + x * block_size;
+
+                    *color = D::from_bytes(&bytes[index..index + block_size], format.clone());
+                });
+            });
+        });
+    }
+
+    fn get_dimension(&self) -> wgpu::TextureDimension {
+        wgpu::TextureDimension::D2
+    }
+
+    fn get_view_dimension(&self) -> wgpu::TextureViewDimension {
+        wgpu::TextureViewDimension::Cube
+    }
+
+    fn extent(&self) -> wgpu::Extent3d {
+        wgpu::Extent3d {
+            width: self.size,
+            height: self.size,
+            depth_or_array_layers: 6,
+        }
+    }
+}
+
+impl CubeArray {
+    pub const fn new(size: u32, cubes: u32) -> Self {
+        Self { size, cubes }
+    }
+
+    pub const fn layers(&self) -> u32 {
+        self.cubes * 6
+    }
+}
+
+impl<D: TextureData> TextureDimension<D> for CubeArray {
+    type Data = Vec<Vec<Vec<D>>>;
+
+    fn init_data(&self) -> Self::Data {
+        vec![
+            vec![vec![D::default(); self.layers() as usize]; self.size as usize];
+            self.size as usize
+        ]
+    }
+
+    fn data_to_bytes<F: TextureFormat>(data: &Self::Data, format: F) -> Vec<u8> {
+        <D2Array as TextureDimension<D>>::data_to_bytes(data, format)
+    }
+
+    fn bytes_to_data<F: TextureFormat>(&self, data: &mut Self::Data, bytes: &[u8], format: F) {
+        D2Array::new(self.size, self.size, self.layers()).bytes_to_data(data, bytes, format)
+    }
+
+    fn get_dimension(&self) -> wgpu::TextureDimension {
+        wgpu::TextureDimension::D2
+    }
+
+    fn get_view_dimension(&self) -> wgpu::TextureViewDimension {
+        wgpu::TextureViewDimension::CubeArray
+    }
+
+    fn extent(&self) -> wgpu::Extent3d {
+        wgpu::Extent3d {
+            width: self.size,
+            height: self.size,
+            depth_or_array_layers: self.layers(),
+        }
+    }
+}
+
 pub struct TextureDescriptor<D: TextureDimension<F::Data>, F: TextureFormat> {
     pub dimension: D,
     pub format: F,
+    pub mip_levels: u32,
+    pub sample_count: u32,
+    pub usage: wgpu::TextureUsage,
 }
 
-impl<D: TextureDimension<F::Data>, F: TextureFormat + Default> TextureDescriptor<D, F> {
-    pub fn default_settings(dimension: D) -> Self {
+impl<D: TextureDimension<F::Data>, F: TextureFormat> TextureDescriptor<D, F> {
+    pub fn new(dimension: D, format: F) -> Self {
         Self {
             dimension,
-            format: Default::default(),
+            format,
+            mip_levels: 1,
+            sample_count: 1,
+            usage: wgpu::TextureUsage::SAMPLED
+                | wgpu::TextureUsage::RENDER_ATTACHMENT
+                | wgpu::TextureUsage::COPY_SRC
+                | wgpu::TextureUsage::COPY_DST,
         }
     }
+
+    /// Sets [`TextureDescriptor::mip_levels`] to the full chain, down to 1x1.
+    pub fn with_mipmaps(mut self) -> Self {
+        self.mip_levels = mip_level_count(&self.dimension);
+        self
+    }
+
+    /// Makes a multisampled render target, these can't be copied so the usage is limited to
+    /// rendering and sampling.
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self.usage = wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED;
+        self
+    }
+
+    /// Allows binding the texture as a storage texture, see [`TextureView::storage`]. Not
+    /// every format supports this, srgb formats don't.
+    pub fn with_storage(mut self) -> Self {
+        self.usage |= wgpu::TextureUsage::STORAGE;
+        self
+    }
+}
+
+impl<D: TextureDimension<F::Data>, F: TextureFormat + Default> TextureDescriptor<D, F> {
+    pub fn default_settings(dimension: D) -> Self {
+        Self::new(dimension, Default::default())
+    }
+}
+
+/// Number of mip levels needed to go from `dimension` down to 1x1.
+pub fn mip_level_count<T: TextureData>(dimension: &impl TextureDimension<T>) -> u32 {
+    let extent = dimension.extent();
+
+    let size = match dimension.get_dimension() {
+        wgpu::TextureDimension::D1 => extent.width,
+        wgpu::TextureDimension::D2 => extent.width.max(extent.height),
+        wgpu::TextureDimension::D3 => extent
+            .width
+            .max(extent.height)
+            .max(extent.depth_or_array_layers),
+    };
+
+    32 - size.max(1).leading_zeros()
 }
 
 #[inline(always)]
@@ -315,6 +491,9 @@ pub struct Texture<D: TextureDimension<F::Data>, F: TextureFormat = Rgba8UnormSr
     pub(crate) data: RwLock<Option<D::Data>>,
     pub(crate) download: Arc<AtomicBool>,
     pub(crate) format: F,
+    pub(crate) mip_levels: u32,
+    pub(crate) sample_count: u32,
+    pub(crate) usage: wgpu::TextureUsage,
     pub dimensions: D,
 }
 
@@ -323,20 +502,17 @@ impl<D: TextureDimension<F::Data>, F: TextureFormat> Texture<D, F> {
         let texture = instance.device.create_texture(&wgpu::TextureDescriptor {
             label: None,
             size: texture_descriptor.dimension.extent(),
-            mip_level_count: 1,
-            sample_count: 1,
+            mip_level_count: texture_descriptor.mip_levels,
+            sample_count: texture_descriptor.sample_count,
             dimension: texture_descriptor.dimension.get_dimension(),
             format: texture_descriptor.format.format(),
-            usage: wgpu::TextureUsage::SAMPLED
-                | wgpu::TextureUsage::RENDER_ATTACHMENT
-                | wgpu::TextureUsage::COPY_SRC
-                | wgpu::TextureUsage::COPY_DST,
+            usage: texture_descriptor.usage,
         });
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor {
             label: None,
             format: None,
-            dimension: None,
+            dimension: Some(texture_descriptor.dimension.get_view_dimension()),
             aspect: wgpu::TextureAspect::All,
             base_mip_level: 0,
             mip_level_count: None,
@@ -352,9 +528,154 @@ impl<D: TextureDimension<F::Data>, F: TextureFormat> Texture<D, F> {
             dimensions: texture_descriptor.dimension.clone(),
             download: Arc::new(AtomicBool::new(true)),
             format: texture_descriptor.format.clone(),
+            mip_levels: texture_descriptor.mip_levels,
+            sample_count: texture_descriptor.sample_count,
+            usage: texture_descriptor.usage,
         }
     }
 
+    /// Creates a texture from decoded image data, uploading every mip level.
+    ///
+    /// Images with a single level get a full mip chain generated if the format allows it, see
+    /// [`Texture::generate_mipmaps`]. Fails if the image doesn't match `texture_descriptor` or
+    /// the device is missing the features needed for the format.
+    fn from_image_data(
+        texture_descriptor: &TextureDescriptor<D, F>,
+        image: &ImageData,
+        instance: &Instance,
+    ) -> anyhow::Result<Self> {
+        use std::num::NonZeroU32;
+
+        let format = texture_descriptor.format.format();
+        let info = format.describe();
+
+        if image.format != format {
+            bail!("image format {:?} doesn't match {:?}", image.format, format);
+        }
+
+        if !instance.device.features().contains(info.required_features) {
+            bail!(
+                "{:?} requires device features {:?}",
+                format,
+                info.required_features
+            );
+        }
+
+        let extent = texture_descriptor.dimension.extent();
+
+        if image.width != extent.width
+            || image.height != extent.height
+            || image.layers != extent.depth_or_array_layers
+        {
+            bail!(
+                "image is {}x{} with {} layers, expected {}x{} with {} layers",
+                image.width,
+                image.height,
+                image.layers,
+                extent.width,
+                extent.height,
+                extent.depth_or_array_layers
+            );
+        }
+
+        let generate_mipmaps = image.levels.len() == 1 && can_generate_mipmaps(format);
+
+        let mut usage = texture_descriptor.usage;
+
+        // compressed formats can't be rendered to
+        if info.block_dimensions != (1, 1) {
+            usage.remove(wgpu::TextureUsage::RENDER_ATTACHMENT);
+        }
+
+        let descriptor = TextureDescriptor {
+            dimension: texture_descriptor.dimension.clone(),
+            format: texture_descriptor.format.clone(),
+            mip_levels: if generate_mipmaps {
+                mip_level_count(&texture_descriptor.dimension)
+            } else {
+                image.levels.len() as u32
+            },
+            sample_count: 1,
+            usage,
+        };
+
+        let texture = Self::new(&descriptor, instance);
+
+        let (block_width, block_height) = info.block_dimensions;
+        let (block_width, block_height) = (block_width as u32, block_height as u32);
+
+        for (level, data) in image.levels.iter().enumerate() {
+            let level = level as u32;
+
+            if data.len() < image.level_size(level) * image.layers as usize {
+                bail!("mip level {} is truncated", level);
+            }
+
+            // copies of compressed textures must cover whole blocks
+            let width =
+                (((extent.width >> level).max(1) + block_width - 1) / block_width) * block_width;
+            let height = (((extent.height >> level).max(1) + block_height - 1) / block_height)
+                * block_height;
+
+            instance.queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture.texture,
+                    mip_level: level,
+                    origin: wgpu::Origin3d::ZERO,
+                },
+                data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(width / block_width * info.block_size as u32),
+                    rows_per_image: NonZeroU32::new(height),
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: extent.depth_or_array_layers,
+                },
+            );
+        }
+
+        if generate_mipmaps {
+            texture.generate_mipmaps(instance)?;
+        }
+
+        Ok(texture)
+    }
+
+    pub fn mip_levels(&self) -> u32 {
+        self.mip_levels
+    }
+
+    /// Fills every mip level after the first by repeatedly downsampling the previous one.
+    ///
+    /// The texture needs [`wgpu::TextureUsage::RENDER_ATTACHMENT`] and a filterable,
+    /// uncompressed format.
+    pub fn generate_mipmaps(&self, instance: &Instance) -> anyhow::Result<()> {
+        if !self
+            .usage
+            .contains(wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::RENDER_ATTACHMENT)
+        {
+            bail!("generating mipmaps requires the SAMPLED and RENDER_ATTACHMENT usages");
+        }
+
+        let extent = self.dimensions.extent();
+
+        let layers = match self.dimensions.get_dimension() {
+            wgpu::TextureDimension::D2 => extent.depth_or_array_layers,
+            dimension => bail!("can't generate mipmaps for {:?} textures", dimension),
+        };
+
+        generate_mipmaps(
+            &self.texture,
+            self.format.format(),
+            self.mip_levels,
+            layers,
+            instance,
+        )
+    }
+
     fn create_staging_buffer(&self, size: u64, instance: &Instance) {
         let mut staging_buffer = self.staging_buffer.lock().unwrap();
 
@@ -487,21 +808,62 @@ impl<D: TextureDimension<F::Data>, F: TextureFormat> Texture<D, F> {
             download: Some(self.download.clone()),
             extent: self.dimensions.extent(),
             dimension: self.dimensions.get_view_dimension(),
-            multisampled: false,
+            sample_count: self.sample_count,
+            format: self.format.clone(),
+        }
+    }
+
+    /// View of the mip levels in `mips`, sampling level 0 of the view reads `mips.start`.
+    pub fn mip_view(&self, mips: std::ops::Range<u32>) -> TextureView<'static, F> {
+        let view = self.texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Texture Mip View"),
+            format: None,
+            dimension: Some(self.dimensions.get_view_dimension()),
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: mips.start,
+            mip_level_count: std::num::NonZeroU32::new(mips.end - mips.start),
+            base_array_layer: 0,
+            array_layer_count: None,
+        });
+
+        TextureView {
+            view: ViewInner::Owned(Arc::new(view)),
+            download: Some(self.download.clone()),
+            extent: mip_extent(self.dimensions.extent(), mips.start),
+            dimension: self.dimensions.get_view_dimension(),
+            sample_count: self.sample_count,
             format: self.format.clone(),
         }
     }
 }
 
+/// Size of mip `level`, array layers are kept.
+fn mip_extent(extent: wgpu::Extent3d, level: u32) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width: (extent.width >> level).max(1),
+        height: (extent.height >> level).max(1),
+        depth_or_array_layers: extent.depth_or_array_layers,
+    }
+}
+
 impl<F: TextureFormat> Texture<D2Array, F> {
     pub fn layer_view(&self, layer: u32) -> TextureView<'static, F> {
+        self.layer_mip_view(layer, 0..self.mip_levels)
+    }
+
+    /// View of a single layer, limited to the mip levels in `mips`.
+    pub fn layer_mip_view(
+        &self,
+        layer: u32,
+        mips: std::ops::Range<u32>,
+    ) -> TextureView<'static, F> {
         let view = self.texture.create_view(&wgpu::TextureViewDescriptor {
             label: Some("Texture Array Layer View"),
             format: None,
             dimension: Some(wgpu::TextureViewDimension::D2Array),
             aspect: wgpu::TextureAspect::All,
-            base_mip_level: 0,
-            mip_level_count: None,
+            base_mip_level: mips.start,
+            mip_level_count: std::num::NonZeroU32::new(mips.end - mips.start),
             base_array_layer: layer,
             array_layer_count: None,
         });
@@ -509,14 +871,134 @@ impl<F: TextureFormat> Texture<D2Array, F> {
         TextureView {
             view: ViewInner::Owned(Arc::new(view)),
             download: Some(self.download.clone()),
-            extent: <D2Array as TextureDimension<F::Data>>::extent(&self.dimensions),
+            extent: mip_extent(
+                <D2Array as TextureDimension<F::Data>>::extent(&self.dimensions),
+                mips.start,
+            ),
             dimension: wgpu::TextureViewDimension::D2,
-            multisampled: false,
+            sample_count: self.sample_count,
             format: self.format.clone(),
         }
     }
 }
 
+impl<F: TextureFormat + Default> Texture<D2, F> {
+    /// Loads a png, jpeg, hdr, ktx2 or dds file, see [`ImageData::load`].
+    ///
+    /// The texture format decides how colors are sampled, use an srgb format like the default
+    /// [`format::Rgba8UnormSrgb`] for colors, and a linear one for data like normal maps.
+    pub fn load(path: impl AsRef<std::path::Path>, instance: &Instance) -> anyhow::Result<Self> {
+        Self::from_image(&ImageData::load(path, F::default().format())?, instance)
+    }
+
+    /// Decodes a png, jpeg or hdr image, see [`ImageData::from_image_bytes`].
+    pub fn from_image_bytes(bytes: &[u8], instance: &Instance) -> anyhow::Result<Self> {
+        Self::from_image(
+            &ImageData::from_image_bytes(bytes, F::default().format())?,
+            instance,
+        )
+    }
+
+    pub fn from_image(image: &ImageData, instance: &Instance) -> anyhow::Result<Self> {
+        Self::from_image_data(
+            &TextureDescriptor::default_settings(D2::new(image.width, image.height)),
+            image,
+            instance,
+        )
+    }
+}
+
+impl Texture<D2, AnyFormat> {
+    /// Loads a png, jpeg, hdr, ktx2 or dds file keeping the format it's stored in, see
+    /// [`ImageData::load_native`].
+    pub fn load_native(
+        path: impl AsRef<std::path::Path>,
+        instance: &Instance,
+    ) -> anyhow::Result<Self> {
+        Self::from_native_image(&ImageData::load_native(path)?, instance)
+    }
+
+    /// Creates a texture in the format of `image`.
+    pub fn from_native_image(image: &ImageData, instance: &Instance) -> anyhow::Result<Self> {
+        Self::from_image_data(
+            &TextureDescriptor::new(D2::new(image.width, image.height), AnyFormat(image.format)),
+            image,
+            instance,
+        )
+    }
+}
+
+impl<F: TextureFormat + Default> Texture<D2Array, F> {
+    pub fn load(path: impl AsRef<std::path::Path>, instance: &Instance) -> anyhow::Result<Self> {
+        Self::from_image(&ImageData::load(path, F::default().format())?, instance)
+    }
+
+    pub fn from_image(image: &ImageData, instance: &Instance) -> anyhow::Result<Self> {
+        Self::from_image_data(
+            &TextureDescriptor::default_settings(D2Array::new(
+                image.width,
+                image.height,
+                image.layers,
+            )),
+            image,
+            instance,
+        )
+    }
+}
+
+impl<F: TextureFormat> Texture<CubeArray, F> {
+    /// 2d view of a single face, for rendering to. Faces are in the order +x, -x, +y, -y, +z
+    /// and -z.
+    pub fn face_view(&self, cube: u32, face: u32) -> TextureView<'static, F> {
+        let view = self.texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Texture Cube Face View"),
+            format: None,
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: 0,
+            mip_level_count: std::num::NonZeroU32::new(1),
+            base_array_layer: cube * 6 + face,
+            array_layer_count: std::num::NonZeroU32::new(1),
+        });
+
+        TextureView {
+            view: ViewInner::Owned(Arc::new(view)),
+            download: Some(self.download.clone()),
+            extent: wgpu::Extent3d {
+                width: self.dimensions.size,
+                height: self.dimensions.size,
+                depth_or_array_layers: 1,
+            },
+            dimension: wgpu::TextureViewDimension::D2,
+            sample_count: self.sample_count,
+            format: self.format.clone(),
+        }
+    }
+}
+
+impl<F: TextureFormat + Default> Texture<Cube, F> {
+    /// Loads a cube map from a ktx2 or dds file with 6 faces.
+    pub fn load(path: impl AsRef<std::path::Path>, instance: &Instance) -> anyhow::Result<Self> {
+        Self::from_image(&ImageData::load(path, F::default().format())?, instance)
+    }
+
+    pub fn from_image(image: &ImageData, instance: &Instance) -> anyhow::Result<Self> {
+        if image.width != image.height {
+            bail!(
+                "cube map faces must be square, found {}x{}",
+                image.width,
+                image.height
+            );
+        }
+
+        Self::from_image_data(
+            &TextureDescriptor::default_settings(Cube::new(image.width)),
+            image,
+            instance,
+        )
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum ViewInner<'a> {
     Owned(Arc<wgpu::TextureView>),
@@ -538,7 +1020,7 @@ pub struct TextureView<'a, F: TextureFormat = TargetFormat> {
     pub(crate) download: Option<Arc<AtomicBool>>,
     pub(crate) extent: wgpu::Extent3d,
     pub(crate) dimension: wgpu::TextureViewDimension,
-    pub(crate) multisampled: bool,
+    pub(crate) sample_count: u32,
     pub(crate) format: F,
 }
 
@@ -559,13 +1041,23 @@ impl<'a, F: TextureFormat> TextureView<'a, F> {
         self.format.clone()
     }
 
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Binds the view as a storage texture, the access is declared in the shader. The texture
+    /// must be created [`TextureDescriptor::with_storage`].
+    pub fn storage(self) -> StorageTextureView<'a, F> {
+        StorageTextureView { view: self }
+    }
+
     pub fn map_format<NF: TextureFormat>(self, f: fn(F) -> NF) -> TextureView<'a, NF> {
         TextureView {
             view: self.view,
             download: self.download,
             extent: self.extent,
             dimension: self.dimension,
-            multisampled: self.multisampled,
+            sample_count: self.sample_count,
             format: f(self.format),
         }
     }
@@ -583,7 +1075,7 @@ impl<F: TextureFormat> Bindable for TextureView<'_, F> {
             ViewInner::Owned(view) => Ok(Binding::Texture {
                 view: view.clone(),
                 dimension: self.dimension,
-                multisampled: self.multisampled,
+                multisampled: self.sample_count > 1,
             }),
             _ => Err(()),
         }
@@ -616,7 +1108,59 @@ impl<F: TextureFormat> Bindable for TextureView<'_, F> {
     }
 }
 
+/// A [`TextureView`] bound as a storage texture.
+#[derive(Clone, Debug)]
+pub struct StorageTextureView<'a, F: TextureFormat = TargetFormat> {
+    pub(crate) view: TextureView<'a, F>,
+}
+
+impl<F: TextureFormat> Bindable for StorageTextureView<'_, F> {
+    fn new_binding(&self) -> Result<Binding, ()> {
+        // the texture is written by the shader, so downloaded data is stale
+        if let Some(download) = &self.view.download {
+            download.store(true, Ordering::SeqCst);
+        }
+
+        match &self.view.view {
+            ViewInner::Owned(view) => Ok(Binding::StorageTexture {
+                view: view.clone(),
+                dimension: self.view.dimension,
+                format: self.view.format.format(),
+            }),
+            _ => Err(()),
+        }
+    }
+
+    fn set(&self, binding: &mut Binding) -> Result<bool, ()> {
+        match binding {
+            Binding::StorageTexture { view, .. } => {
+                if let Some(download) = &self.view.download {
+                    download.store(true, Ordering::SeqCst);
+                }
+
+                let new_view = match &self.view.view {
+                    ViewInner::Owned(view) => view.clone(),
+                    _ => return Err(()),
+                };
+
+                let recreate = !Arc::ptr_eq(view, &new_view);
+
+                *view = new_view;
+
+                Ok(recreate)
+            }
+            _ => {
+                *binding = self.new_binding()?;
+
+                Ok(true)
+            }
+        }
+    }
+}
+
 pub type Texture1d<F = Rgba8UnormSrgb> = Texture<D1, F>;
 pub type Texture2d<F = Rgba8UnormSrgb> = Texture<D2, F>;
 pub type Texture3d<F = Rgba8UnormSrgb> = Texture<D3, F>;
 pub type Texture2dArray<F = Rgba8UnormSrgb> = Texture<D2Array, F>;
+pub type TextureCube<F = Rgba8UnormSrgb> = Texture<Cube, F>;
+pub type TextureCubeArray<F = Rgba8UnormSrgb> = Texture<CubeArray, F>;
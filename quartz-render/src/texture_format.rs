@@ -23,6 +23,20 @@ impl TextureFormat for TargetFormat {
     }
 }
 
+/// A format only known at runtime, eg. the format a texture file is stored in.
+///
+/// Texels of compressed formats can't be read or written on the cpu.
+#[derive(Clone, Copy, Debug)]
+pub struct AnyFormat(pub wgpu::TextureFormat);
+
+impl TextureFormat for AnyFormat {
+    type Data = Color;
+
+    fn format(&self) -> wgpu::TextureFormat {
+        self.0
+    }
+}
+
 macro_rules! format {
     ($ident:ident) => {
         #[derive(Clone, Copy, Debug, Default)]
@@ -55,6 +69,17 @@ format!(R32Sint: i32);
 format!(R32Float: f32);
 format!(Rgba8Unorm);
 format!(Rgba8UnormSrgb);
+format!(Rgba16Float);
+format!(Rgba32Float);
+format!(Bc1RgbaUnorm);
+format!(Bc1RgbaUnormSrgb);
+format!(Bc3RgbaUnorm);
+format!(Bc3RgbaUnormSrgb);
+format!(Bc4RUnorm);
+format!(Bc5RgUnorm);
+format!(Bc6hRgbUfloat);
+format!(Bc7RgbaUnorm);
+format!(Bc7RgbaUnormSrgb);
 format!(Depth32Float: f32);
 
 impl TextureData for u32 {
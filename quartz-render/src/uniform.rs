@@ -277,3 +277,24 @@ impl Uniform for Mat4 {
         bytes_of(self).to_vec()
     }
 }
+
+/// Laid out like a glsl array in a uniform block, each element is padded to 16 bytes.
+impl<T: Uniform, const N: usize> Uniform for [T; N] {
+    fn alignment() -> wgpu::BufferAddress {
+        16
+    }
+
+    fn size() -> wgpu::BufferAddress {
+        aligned_size(T::size(), 16) * N as u64
+    }
+
+    fn data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(Self::size() as usize);
+
+        for uniform in self {
+            append_aligned(&mut data, uniform, 16);
+        }
+
+        data
+    }
+}
@@ -5,11 +5,16 @@ use glam::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use wgpu::util::DeviceExt;
 
 pub trait VertexAttribute: Pod + Zeroable {
+    /// Format of a single column for matrices.
     fn format() -> wgpu::VertexFormat;
 
+    /// Number of matrix columns, matrices are only supported as [`Instances`] attributes.
+    fn columns() -> u32 {
+        1
+    }
+
     fn size() -> wgpu::BufferAddress {
         std::mem::size_of::<Self>() as wgpu::BufferAddress
     }
@@ -49,9 +54,12 @@ pub struct Mesh {
     pub(crate) vertex_data: HashMap<String, VertexAttributeData>,
     pub(crate) indices: Vec<u32>,
     #[serde(skip)]
-    pub(crate) index_buffer: Mutex<Option<Arc<wgpu::Buffer>>>,
+    pub(crate) index_buffer: Mutex<Option<Buffer<u32>>>,
+    #[serde(skip)]
+    pub(crate) vertex_buffers: Mutex<HashMap<String, Buffer<u8>>>,
+    /// Bounds of `vertex_position`, invalidated with its vertex buffer.
     #[serde(skip)]
-    pub(crate) vertex_buffers: Mutex<HashMap<String, Arc<wgpu::Buffer>>>,
+    pub(crate) aabb: Mutex<Option<Aabb>>,
 }
 
 impl Clone for Mesh {
@@ -61,6 +69,7 @@ impl Clone for Mesh {
             indices: self.indices.clone(),
             index_buffer: Mutex::new(None),
             vertex_buffers: Mutex::new(HashMap::new()),
+            aabb: Mutex::new(None),
         }
     }
 }
@@ -72,6 +81,7 @@ impl Mesh {
             indices: Vec::new(),
             index_buffer: Mutex::new(None),
             vertex_buffers: Mutex::new(HashMap::new()),
+            aabb: Mutex::new(None),
         }
     }
 
@@ -80,6 +90,7 @@ impl Mesh {
     }
 
     pub fn vertex_data_mut(&mut self) -> &mut HashMap<String, VertexAttributeData> {
+        *self.aabb.lock().unwrap() = None;
         &mut self.vertex_data
     }
 
@@ -115,7 +126,7 @@ impl Mesh {
         let attr = VertexAttributeData::new(name.into(), data);
 
         self.vertex_data.insert(name.into(), attr);
-        self.vertex_buffers.lock().unwrap().remove(name);
+        self.invalidate_vertex_buffer(name);
     }
 
     pub fn get_attribute<V: VertexAttribute>(&self, name: &str) -> Option<&[V]> {
@@ -131,9 +142,9 @@ impl Mesh {
     }
 
     pub fn get_attribute_mut<V: VertexAttribute>(&mut self, name: &str) -> Option<&mut [V]> {
-        if let Some(data) = self.vertex_data.get_mut(name) {
-            self.vertex_buffers.lock().unwrap().remove(name);
+        self.invalidate_vertex_buffer(name);
 
+        if let Some(data) = self.vertex_data.get_mut(name) {
             if V::format() == data.format {
                 Some(cast_slice_mut(&mut data.data))
             } else {
@@ -161,22 +172,31 @@ impl Mesh {
 
     pub fn invalidate_vertex_buffer(&self, name: &str) {
         self.vertex_buffers.lock().unwrap().remove(name);
+
+        if name == "vertex_position" {
+            *self.aabb.lock().unwrap() = None;
+        }
+    }
+
+    /// Bounds of the `vertex_position` attribute, [`None`] if the mesh has no positions.
+    pub fn aabb(&self) -> Option<Aabb> {
+        let mut aabb = self.aabb.lock().unwrap();
+
+        if aabb.is_none() {
+            *aabb = Aabb::from_points(self.get_attribute::<Vec3>("vertex_position")?);
+        }
+
+        *aabb
     }
 
     pub fn create_vertex_buffer(&self, name: &String, instance: &Instance) {
         if let Some(data) = self.vertex_data.get(name) {
-            let buffer = instance
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Vertex Buffer"),
-                    contents: cast_slice(&data.data),
-                    usage: wgpu::BufferUsage::VERTEX,
-                });
+            let buffer = Buffer::new(&data.data, wgpu::BufferUsage::VERTEX, instance);
 
             self.vertex_buffers
                 .lock()
                 .unwrap()
-                .insert(name.clone(), Arc::new(buffer));
+                .insert(name.clone(), buffer);
         }
     }
 
@@ -193,19 +213,94 @@ impl Mesh {
     }
 
     pub fn get_vertex_buffer(&self, name: &String) -> Option<Arc<wgpu::Buffer>> {
-        self.vertex_buffers.lock().unwrap().get(name).cloned()
+        self.vertex_buffers
+            .lock()
+            .unwrap()
+            .get(name)
+            .map(|buffer| buffer.raw().clone())
     }
 
     pub fn create_index_buffer(&self, instance: &Instance) {
-        let index_buffer = instance
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: cast_slice(&self.indices),
-                usage: wgpu::BufferUsage::INDEX,
-            });
+        let index_buffer = Buffer::new(&self.indices, wgpu::BufferUsage::INDEX, instance);
+
+        *self.index_buffer.lock().unwrap() = Some(index_buffer);
+    }
+}
+
+struct InstanceAttribute {
+    format: wgpu::VertexFormat,
+    columns: u32,
+    len: u32,
+    buffer: Buffer<u8>,
+}
+
+/// Per instance vertex attributes, drawn with [`RenderPass::draw_mesh_instanced`].
+///
+/// Attributes are matched by name to shader inputs named `instance_*`, the buffers are kept
+/// and reused when attributes are set again.
+pub struct Instances {
+    attributes: HashMap<String, InstanceAttribute>,
+}
+
+impl Instances {
+    pub fn new() -> Self {
+        Self {
+            attributes: HashMap::new(),
+        }
+    }
+
+    /// Uploads `data` as the attribute `name`.
+    pub fn set_attribute<V: VertexAttribute>(
+        &mut self,
+        name: &str,
+        data: &[V],
+        instance: &Instance,
+    ) {
+        let bytes = cast_slice(data);
+
+        match self.attributes.get_mut(name) {
+            Some(attr) if attr.format == V::format() && attr.columns == V::columns() => {
+                attr.buffer.set_data(bytes, instance);
+                attr.len = data.len() as u32;
+            }
+            _ => {
+                let attr = InstanceAttribute {
+                    format: V::format(),
+                    columns: V::columns(),
+                    len: data.len() as u32,
+                    buffer: Buffer::new(bytes, wgpu::BufferUsage::VERTEX, instance),
+                };
+
+                self.attributes.insert(name.into(), attr);
+            }
+        }
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) {
+        self.attributes.remove(name);
+    }
+
+    /// Number of instances, the length of the shortest attribute.
+    pub fn len(&self) -> u32 {
+        self.attributes
+            .values()
+            .map(|attr| attr.len)
+            .min()
+            .unwrap_or(0)
+    }
 
-        *self.index_buffer.lock().unwrap() = Some(Arc::new(index_buffer));
+    pub(crate) fn get_buffer(
+        &self,
+        layout: &VertexAttributeLayout,
+        name: &str,
+    ) -> Option<Arc<wgpu::Buffer>> {
+        let attr = self.attributes.get(name)?;
+
+        if attr.format == layout.format && attr.columns == layout.columns {
+            Some(attr.buffer.raw().clone())
+        } else {
+            None
+        }
     }
 }
 
@@ -233,6 +328,16 @@ impl VertexAttribute for Vec4 {
     }
 }
 
+impl VertexAttribute for Mat4 {
+    fn format() -> wgpu::VertexFormat {
+        wgpu::VertexFormat::Float32x4
+    }
+
+    fn columns() -> u32 {
+        4
+    }
+}
+
 impl VertexAttribute for Color {
     fn format() -> wgpu::VertexFormat {
         wgpu::VertexFormat::Float32x4
@@ -0,0 +1,17 @@
+mod runner_state;
+
+use quartz_framework::app::App;
+
+fn main() {
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Warn)
+        .with_module_level("quartz_runner", log::LevelFilter::Info)
+        .with_module_level("quartz_render", log::LevelFilter::Warn)
+        .init()
+        .unwrap();
+
+    App::new()
+        .title("Quartz Game")
+        .run(runner_state::RunnerState::new)
+        .unwrap();
+}
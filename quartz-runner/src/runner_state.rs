@@ -0,0 +1,114 @@
+use clap::{crate_authors, crate_version, Clap};
+use quartz_engine::core::editor_bridge::*;
+use quartz_engine::core::scene::SceneFormat;
+use quartz_engine::prelude::*;
+use quartz_framework::{prelude::*, winit};
+use std::path::PathBuf;
+
+#[cfg(debug_assertions)]
+pub const LIB_PATH: &'static str = "target/debug";
+#[cfg(not(debug_assertions))]
+pub const LIB_PATH: &'static str = "target/release";
+
+#[derive(Clap)]
+#[clap(version = crate_version!(), author = crate_authors!())]
+pub struct Opts {
+    /// The path to your project.
+    #[clap(default_value = ".")]
+    pub project_path: PathBuf,
+    /// The scene to run, relative to the project, either a `.scn` or binary `.scnb` scene.
+    #[clap(short, long, default_value = "scene.scn")]
+    pub scene: PathBuf,
+    /// The name of the project library, defaults to the name of the project directory.
+    #[clap(short, long)]
+    pub lib: Option<String>,
+}
+
+impl Opts {
+    pub fn lib_path(&self) -> PathBuf {
+        let name = self.lib.clone().unwrap_or_else(|| {
+            let path = std::fs::canonicalize(&self.project_path).unwrap();
+
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .replace('-', "_")
+        });
+
+        self.project_path
+            .join(LIB_PATH)
+            .join(libloading::library_filename(name))
+    }
+}
+
+pub struct RunnerState {
+    pub state: Option<GameState>,
+    pub bridge: Option<Bridge>,
+    pub target_format: format::TargetFormat,
+}
+
+impl RunnerState {
+    pub fn new(instance: &Instance, target_format: format::TargetFormat) -> Self {
+        let opts = Opts::parse();
+
+        let lib_path = opts.lib_path();
+        let scene_path = opts.project_path.join(&opts.scene);
+
+        log::info!("Loading game from: {}", lib_path.display());
+        let bridge = unsafe { Bridge::load(&lib_path) }.unwrap();
+
+        log::info!("Loading scene from: {}", scene_path.display());
+        let scene = std::fs::read(&scene_path).unwrap();
+        let format = SceneFormat::detect(&scene_path, &scene);
+
+        let mut state = bridge
+            .load_scene(&scene, format, instance, target_format)
+            .unwrap();
+
+        state.plugins.assets.set_root(&opts.project_path);
+        state.tree.sync_prefabs(&opts.project_path);
+        state.start(target_format, instance);
+
+        Self {
+            state: Some(state),
+            bridge: Some(bridge),
+            target_format,
+        }
+    }
+}
+
+impl Drop for RunnerState {
+    fn drop(&mut self) {
+        drop(self.state.take());
+
+        self.bridge.take().unwrap().close().unwrap();
+    }
+}
+
+impl State for RunnerState {
+    fn update(&mut self, ctx: UpdateCtx<'_>) -> Trans {
+        if let Some(state) = &mut self.state {
+            state.update(ctx.delta_time, self.target_format, ctx.instance);
+        }
+
+        Trans::None
+    }
+
+    fn handle_event(&mut self, _instance: &Instance, event: &winit::event::Event<()>) -> Trans {
+        if let winit::event::Event::WindowEvent { event, .. } = event {
+            if let Some(state) = &mut self.state {
+                state.input.handle_event(event);
+            }
+        }
+
+        Trans::None
+    }
+
+    fn render(&mut self, instance: &Instance, target: TextureView) {
+        if let Some(state) = &mut self.state {
+            let mut render_ctx = instance.render();
+
+            state.render(target, &mut render_ctx, instance);
+        }
+    }
+}